@@ -7,13 +7,36 @@ pub enum AnagramType {
     Loose
 }
 
+/// How to rank anagrams found with `--best`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ArgEnum)]
+pub enum ScoreType {
+    FewerWords,
+    LongerWords
+}
+
+/// How to print the report of `anabot wordlist check`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ArgEnum)]
+pub enum ReportFormat {
+    Text,
+    Json
+}
+
+/// How to order the anagrams found
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ArgEnum)]
+pub enum SortType {
+    Wordlist,
+    Frequency
+}
+
 #[derive(Debug, PartialEq, Eq, Subcommand)]
 pub enum ActionType {
     /// Test if two words are anagrams
     Test { word_a: String, word_b: String },
     /// Find and print anagrams for a word, up to a given limit
     Find {
-        word: String,
+        /// The word to find anagrams of (not used with --batch)
+        #[clap(required_unless_present = "batch")]
+        word: Option<String>,
         /// The maximum number of anagrams to find
         ///
         /// The actual number of anagrams found may be under this limit, but never above.
@@ -23,7 +46,133 @@ pub enum ActionType {
         ///
         /// For example, with this set to 3, no 1 or 2 letter words will appear in the results.
         #[clap(short, long, default_value_t = 1)]
-        min_word_length: usize
+        min_word_length: usize,
+        /// Limit the memory used to cache partial results, in megabytes (only used with loose anagrams)
+        ///
+        /// Lower limits reduce memory usage at the cost of a slower search.
+        #[clap(long, value_name = "MEGABYTES")]
+        cache_limit: Option<usize>,
+        /// Save the search to this file when it stops early, and resume from it if it exists
+        ///
+        /// Only used with loose anagrams. The file is removed once the search is complete.
+        #[clap(long, value_name = "FILE")]
+        checkpoint: Option<String>,
+        /// Search using this many threads (only used with loose anagrams and --batch)
+        ///
+        /// A value of 0 uses one thread per CPU. Loose anagrams found using multiple threads
+        /// are printed in an unpredictable order.
+        #[clap(long, value_name = "THREADS")]
+        threads: Option<usize>,
+        /// Find proper anagrams of every word in this file instead of a single word
+        ///
+        /// The file should contain one word per line; use `-` to read words from standard input.
+        /// Results are grouped by word, and --limit applies to each word separately.
+        #[clap(long, value_name = "FILE", conflicts_with = "word")]
+        batch: Option<String>,
+        /// Count the anagrams instead of printing them (only used with loose anagrams)
+        ///
+        /// Counting is much faster than finding every anagram. If the count takes longer than
+        /// --timeout, a lower bound is printed instead.
+        #[clap(long, conflicts_with_all = &["checkpoint", "threads", "batch"])]
+        count: bool,
+        /// Count anagrams with the same words in a different order only once (requires --count)
+        #[clap(long, requires = "count")]
+        unordered: bool,
+        /// Find only the BEST highest scoring anagrams, best first (only used with loose anagrams)
+        ///
+        /// Only one order of each set of words is printed, along with its score.
+        #[clap(
+            long,
+            value_name = "BEST",
+            conflicts_with_all = &["checkpoint", "threads", "batch", "count"]
+        )]
+        best: Option<usize>,
+        /// How to score anagrams found with --best
+        ///
+        /// `fewer-words`: anagrams with fewer words score higher.
+        ///
+        /// `longer-words`: anagrams made of longer words score higher.
+        #[clap(long, arg_enum, value_parser, default_value_t = ScoreType::FewerWords)]
+        score: ScoreType,
+        /// Only use words with at least this frequency (requires a word list with frequencies)
+        #[clap(long, value_name = "COUNT")]
+        min_frequency: Option<u64>,
+        /// How to order the anagrams found
+        ///
+        /// `wordlist`: the order anagrams are found in (the default).
+        ///
+        /// `frequency`: most frequent words first (requires a word list with frequencies).
+        /// Loose anagrams are ordered by the combined frequency of their words,
+        /// and only one order of each set of words is printed.
+        #[clap(
+            long,
+            arg_enum,
+            value_parser,
+            conflicts_with_all = &["checkpoint", "threads", "count", "best"]
+        )]
+        sort: Option<SortType>,
+        /// Only find phrases matching this part of speech template (only used with loose anagrams)
+        ///
+        /// A template is a list of Universal Dependencies tags, one per word, such as
+        /// "ADJ NOUN". A word can allow several tags separated by `|`, as in "ADJ|NUM NOUN".
+        /// Can be given more than once to allow several templates. Requires a word list with
+        /// part of speech tags: a word, a tab, and comma separated tags on each line.
+        #[clap(
+            long,
+            value_name = "TEMPLATE",
+            multiple_occurrences = true,
+            conflicts_with_all = &["threads", "count", "best", "sort"]
+        )]
+        template: Vec<String>
+    },
+    /// Work with word list index files
+    ///
+    /// An index is a precompiled word list that loads much faster than a text word list.
+    /// Index files can be used with `-w` just like text word lists.
+    Index {
+        #[clap(subcommand)]
+        action: IndexAction
+    },
+    /// Inspect word list files
+    Wordlist {
+        #[clap(subcommand)]
+        action: WordlistAction
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Subcommand)]
+pub enum IndexAction {
+    /// Compile the word list (given with `-w`, or the default word list) into an index file
+    ///
+    /// Word frequencies are kept in the index; part of speech tags are not.
+    Build {
+        /// The index file to write
+        output: String
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Subcommand)]
+pub enum WordlistAction {
+    /// Print statistics about a word list file and the problems found in it
+    ///
+    /// Reports the number of words, their lengths and characters, duplicates, words that are
+    /// the same ignoring case, the largest groups of words that are anagrams of each other,
+    /// and words that are empty or contain unusual whitespace or control characters.
+    /// Lines of text word lists are checked exactly as they are written (except for `#`
+    /// comments), so problems that loading the word list would fix are reported as warnings.
+    ///
+    /// Text word lists must be UTF-8. Exits with an error if any line isn't valid UTF-8, or any
+    /// word contains whitespace other than single spaces between its letters, or a control
+    /// character.
+    Check {
+        /// The word list file to check (use `-` to read it from standard input)
+        file: String,
+        /// How to print the report
+        #[clap(long, arg_enum, value_parser, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+        /// The number of duplicates, case variants, and anagram groups to list
+        #[clap(long, value_name = "COUNT", default_value_t = 10)]
+        top: usize
     }
 }
 
@@ -49,15 +198,73 @@ pub struct CliArgs {
 
     /// Path to a word list file
     ///
-    /// This file should be a text file with one word per line.
-    /// If not provided, a default wordlist will be used as needed (if available)
-    #[clap(long, short)]
-    pub wordlist_path: Option<String>,
+    /// This file should be a text file with one word per line; use `-` to read the word list
+    /// from standard input. If not provided, a default wordlist will be used as needed
+    /// (if available)
+    ///
+    /// Can be given more than once to use the words of several word lists. Frequencies and
+    /// tags are only read from the first word list; the others just add words to it.
+    ///
+    /// Surrounding whitespace, blank lines, lines starting with `#`, and duplicate words are
+    /// ignored. Files can be UTF-8, UTF-16 (with a byte order mark), or Latin-1, and can be
+    /// compressed with gzip or zstd.
+    ///
+    /// Word lists with frequencies have a word, a tab, and the word's frequency on each line.
+    /// Index files built with `anabot index build` can also be used, as can Hunspell
+    /// dictionaries (a `.dic` file, with the `.aff` file of the same name next to it).
+    #[clap(
+        long = "wordlist-path",
+        short = 'w',
+        value_name = "WORDLIST_PATH",
+        multiple_occurrences(true)
+    )]
+    pub wordlist_paths: Vec<String>,
+
+    /// Leave the words of this word list file out of the word list
+    ///
+    /// The file can be in any format accepted by `--wordlist-path`, and this option can be
+    /// given more than once. Useful for blocklists of words that should never be used.
+    #[clap(long, value_name = "FILE", multiple_occurrences(true))]
+    pub exclude_list: Vec<String>,
+
+    /// Never print anagrams containing slurs or strong profanity
+    ///
+    /// Words on the blocklist are left out of the word list, and anagrams that spell a blocked
+    /// pattern across their words are not printed (though they are still counted by --count).
+    /// Uses the blocklist built into anabot unless --blocklist is given.
+    #[clap(long)]
+    pub safe: bool,
+
+    /// Use the blocklist in this file instead of the built-in one (implies --safe)
+    ///
+    /// Each line holds a word to block, or `*text*` to block every word containing `text`.
+    /// Blank lines and lines starting with `#` are skipped.
+    #[clap(long, value_name = "FILE")]
+    pub blocklist: Option<String>,
+
+    /// Leave words with apostrophes (such as possessives like "cat's") out of the word list
+    #[clap(long)]
+    pub exclude_apostrophes: bool,
+
+    /// Leave words with uppercase letters (such as proper nouns) out of the word list
+    #[clap(long)]
+    pub exclude_uppercase: bool,
+
+    /// Skip invalid lines in the word list file (with a warning for each) instead of failing
+    #[clap(long)]
+    pub lenient: bool,
 
     /// Use simplified (machine readable)
     #[clap(long, short)]
     pub simple_output: bool,
 
+    /// Stop searching for anagrams after this many seconds
+    ///
+    /// Anagrams found before the timeout are still printed. Searches can also
+    /// be stopped early by pressing Ctrl-C. Not supported with --batch.
+    #[clap(long, value_name = "SECONDS")]
+    pub timeout: Option<f64>,
+
     #[clap(subcommand)]
     pub action: ActionType
 }
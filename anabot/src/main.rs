@@ -1,50 +1,217 @@
 use anagrambot::{
-    anagram, default_wordlist,
-    wordlist::{OwnedWordList, Wordlist}
+    anagram::{
+        self, AnagramScorer, BestLooseAnagramsIterator, CacheLimit, CancellationToken,
+        CheckpointError, CountMode, FewerWords, FrequencyScorer, LongerWords, LooseAnagramCount,
+        LooseAnagramsCheckpoint, LooseAnagramsIterator, PhraseTemplate, SearchLimits,
+        TemplateError, Truncation
+    },
+    default_wordlist,
+    wordlist::{
+        self as wordlists, Blocklist, BorrowedWordList, CaseFoldedWordlist, CharacterClass,
+        Difference, Encoding, Filtered, FrequencyWordList, HunspellDictionary, HunspellOptions,
+        IndexError, IndexFile, LoadReport, OwnedWordList, SkippedLine, TaggedWordList,
+        TrieWordList, Union, WeightedWordlist, Wordlist, WordlistLoadOptions, WordlistStats
+    }
 };
 use clap::Parser;
 
-use std::path::Path;
+use std::{
+    fmt::Display,
+    fs,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    iter,
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc
+    },
+    time::Duration
+};
 
 mod arg;
-use arg::{ActionType, AnagramType, CliArgs};
+use arg::{
+    ActionType, AnagramType, CliArgs, IndexAction, ReportFormat, ScoreType, SortType,
+    WordlistAction
+};
+
+#[cfg(feature = "parallel")]
+mod parallel;
+
+/// stand-in for the `parallel` module when anabot is built without the `parallel` feature
+#[cfg(not(feature = "parallel"))]
+mod parallel {
+    use anagrambot::{
+        anagram::{CacheLimit, ProperAnagramsBatchResult, SearchLimits, Truncation},
+        wordlist::{Blocklist, Wordlist}
+    };
+
+    const ERR_NO_PARALLEL: &str =
+        "Multithreaded searches are not supported; anabot was built without the `parallel` feature";
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn print_loose_anagrams(
+        _word: &str,
+        _wordlist: &impl Wordlist,
+        _min_word_length: usize,
+        _case_sensitive: bool,
+        _cache_limit: CacheLimit,
+        _limits: SearchLimits,
+        _threads: usize,
+        _limit: usize,
+        _blocklist: &Blocklist
+    ) -> Result<(usize, Option<Truncation>), String> {
+        Err(String::from(ERR_NO_PARALLEL))
+    }
+
+    pub fn find_proper_anagrams_batch<'a, 'b>(
+        _targets: &'b [String],
+        _wordlist: &'a (impl Wordlist + Sync),
+        _case_sensitive: bool,
+        _threads: usize
+    ) -> Result<Vec<ProperAnagramsBatchResult<'a, 'b>>, String> {
+        Err(String::from(ERR_NO_PARALLEL))
+    }
+}
 
 const REASON_DUPLICATES: &str = "a word cannot be an anagram of itself";
 const REASON_FIRST_NOT_WORD: &str = "first provided word is not a valid word";
 const REASON_SECOND_NOT_WORD: &str = "second provided word is not a valid word";
 const REASON_CHARS_DIFFERENT: &str = "words do not contain the same characters in the same amounts";
 
+const ERR_CHECKPOINT_NOT_LOOSE: &str = "Checkpoints are only supported for loose anagram searches";
+const ERR_COUNT_NOT_LOOSE: &str = "Counting is only supported for loose anagram searches";
+const ERR_BEST_NOT_LOOSE: &str = "Scored searches are only supported for loose anagrams";
+const ERR_NO_FREQUENCIES: &str =
+    "Word frequencies are only available with a word list file that has them (word<TAB>count)";
+const ERR_TEMPLATE_NOT_LOOSE: &str = "Phrase templates are only supported for loose anagrams";
+const ERR_NO_TAGS: &str = "Phrase templates are only available with a word list file that has \
+                           part of speech tags (word<TAB>tags)";
+const ERR_BATCH_NOT_PROPER: &str = "Batches are only supported for proper anagram searches";
+const ERR_THREADS_NO_BATCH: &str =
+    "Multithreaded proper anagram searches are only supported with --batch";
+const ERR_TIMEOUT_BATCH: &str = "Timeouts are not supported for batches";
+const ERR_STDIN_TWICE: &str = "Standard input can only be used for one word list or batch file";
+const PANIC_NOT_SEARCH: &str =
+    "Logic Error! Index and word list actions must be handled before searching";
+
+/// exit code used when a second Ctrl-C forces the program to stop immediately
+const EXIT_INTERRUPTED: i32 = 130;
+
+/// the path used to read a word list or batch file from standard input
+const STDIN_PATH: &str = "-";
+
 fn main() -> Result<(), String> {
     let args = CliArgs::parse();
-    handle_args(args)
+    let stops = SearchStops::install(&args)?;
+    handle_args(args, stops)
+}
+
+/// stops searches early when `--timeout` passes or Ctrl-C is pressed
+///
+/// the first Ctrl-C during a search cancels it, so that the results found so far can still be
+/// reported. Ctrl-C exits immediately if no search has started (such as while the word list is
+/// loading), or if it is pressed a second time.
+struct SearchStops {
+    timeout: Option<Duration>,
+    token: CancellationToken,
+    searching: Arc<AtomicBool>
+}
+
+impl SearchStops {
+    /// checks the timeout of `args` and installs the Ctrl-C handler
+    fn install(args: &CliArgs) -> Result<Self, String> {
+        let timeout = args
+            .timeout
+            .map(|timeout| {
+                Duration::try_from_secs_f64(timeout)
+                    .map_err(|_| format!("Invalid timeout {}", timeout))
+            })
+            .transpose()?;
+        let token = CancellationToken::new();
+        let searching = Arc::new(AtomicBool::new(false));
+
+        let handler_token = token.clone();
+        let handler_searching = Arc::clone(&searching);
+        ctrlc::set_handler(move || {
+            if !handler_searching.load(Ordering::SeqCst) || handler_token.is_cancelled() {
+                process::exit(EXIT_INTERRUPTED);
+            }
+            handler_token.cancel();
+        })
+        .map_err(|err| format!("Failed to set Ctrl-C handler: {}", err))?;
+
+        Ok(Self {
+            timeout,
+            token,
+            searching
+        })
+    }
+
+    /// returns the limits of a search starting now, whose timeout starts counting now
+    fn start(&self) -> SearchLimits {
+        self.searching.store(true, Ordering::SeqCst);
+        let limits = SearchLimits::new().with_cancellation(self.token.clone());
+        match self.timeout {
+            Some(timeout) => limits.with_timeout(timeout),
+            None => limits
+        }
+    }
 }
 
 /// main arg handling function
 ///
 /// includes full handling for standard anagrams and delegates other types of anagrams to do_action
-fn handle_args(args: CliArgs) -> Result<(), String> {
+fn handle_args(args: CliArgs, stops: SearchStops) -> Result<(), String> {
+    check_stdin(&args)?;
+    if let ActionType::Index { action } = &args.action {
+        return match action {
+            IndexAction::Build { output } => build_index(&args, Path::new(output))
+        };
+    }
+    if let ActionType::Wordlist { action } = &args.action {
+        return match action {
+            WordlistAction::Check { file, format, top } => check_wordlist(file, *format, *top)
+        };
+    }
+
     // handle Standard first, as it requires no wordlist and thus no wordlist handling
     if args.anagram_type == AnagramType::Standard {
         match &args.action {
             ActionType::Find {
                 word,
                 limit,
-                min_word_length: _
+                checkpoint,
+                batch,
+                count,
+                best,
+                min_frequency,
+                sort,
+                template,
+                ..
             } => {
-                let limit = *limit;
-                let iter = anagram::find_anagrams(word);
-                let mut index: usize = 0;
-                for word in iter {
-                    if index >= limit {
-                        break;
-                    }
-                    println!("{}", word);
-
-                    index += 1;
+                if min_frequency.is_some() || *sort == Some(SortType::Frequency) {
+                    return Err(String::from(ERR_NO_FREQUENCIES));
                 }
-                if !args.simple_output {
-                    println!("found {} standard anagrams", index);
+                if !template.is_empty() {
+                    return Err(String::from(ERR_TEMPLATE_NOT_LOOSE));
                 }
+                if checkpoint.is_some() {
+                    return Err(String::from(ERR_CHECKPOINT_NOT_LOOSE));
+                }
+                if *count {
+                    return Err(String::from(ERR_COUNT_NOT_LOOSE));
+                }
+                if best.is_some() {
+                    return Err(String::from(ERR_BEST_NOT_LOOSE));
+                }
+                if batch.is_some() {
+                    return Err(String::from(ERR_BATCH_NOT_PROPER));
+                }
+                let blocklist = load_blocklist(&args)?;
+                let mut iter = anagram::find_anagrams(find_word(word)).with_limits(stops.start());
+                let count = print_anagrams(&mut unblocked(&mut iter, &blocklist), *limit);
+                print_summary(&args, count, iter.truncation());
             }
             ActionType::Test { word_a, word_b } => {
                 if anagram::are_anagrams(word_a, word_b, !args.case_insensitive) {
@@ -64,22 +231,100 @@ fn handle_args(args: CliArgs) -> Result<(), String> {
                     }
                 }
             }
+            ActionType::Index { .. } | ActionType::Wordlist { .. } => panic!("{}", PANIC_NOT_SEARCH)
         }
     } else {
         // handle getting a wordlist
         // if this fails, return Err(message)
         // if this succeeds, call do_action to perform whatever action
-        if let Some(wordlist_path) = &args.wordlist_path {
-            let wordlist = match OwnedWordList::from_file(Path::new(wordlist_path)) {
-                Ok(wordlist) => wordlist,
-                Err(_) => {
-                    return Err(format!("Failed to read word list file {}", wordlist_path));
-                }
+        let options = load_options(&args);
+        let changes = WordlistChanges::load(&args, &options)?;
+        if let Some(wordlist_path) = args.wordlist_paths.first() {
+            let source = WordlistSource::open(wordlist_path)?;
+            let read_err = |err: io::Error| {
+                format!("Failed to read word list file {}: {}", wordlist_path, err)
             };
+            match wordlist_format(wordlist_path, &source)? {
+                WordlistFormat::Index => {
+                    let index = load_index(source)?;
+                    let wordlist = index.wordlist();
+                    let word_info = WordInfo {
+                        frequencies: wordlist.has_weights().then_some(&wordlist),
+                        ..WordInfo::default()
+                    };
+
+                    let min_frequency = min_frequency(&args).filter(|_| wordlist.has_weights());
+                    match (min_frequency, filters_words(&args)) {
+                        (Some(min_frequency), false) => {
+                            let common_words = wordlist.with_min_weight(min_frequency);
+                            do_action(&args, &common_words, &changes, word_info, &stops)?;
+                        }
+                        (min_frequency, true) => {
+                            // every weight is at least a min_frequency of None
+                            let words = wordlist
+                                .words()
+                                .filter(|word| wordlist.weight(word) >= min_frequency);
+                            let wordlist = BorrowedWordList::new(load_options(&args).filter(words));
+                            do_action(&args, &wordlist, &changes, word_info, &stops)?;
+                        }
+                        (None, false) => do_action(&args, &wordlist, &changes, word_info, &stops)?
+                    }
+                    return Ok(());
+                }
+                WordlistFormat::Frequencies => {
+                    let (wordlist, report) = source
+                        .reader()
+                        .and_then(|reader| {
+                            FrequencyWordList::from_reader_with_report(reader, &options)
+                        })
+                        .map_err(read_err)?;
+                    warn_skipped(wordlist_path, &report);
+                    let word_info = WordInfo {
+                        frequencies: Some(&wordlist),
+                        ..WordInfo::default()
+                    };
 
-            do_action(&args, &wordlist);
+                    match min_frequency(&args) {
+                        Some(min_frequency) => {
+                            let common_words = wordlist.with_min_weight(min_frequency);
+                            do_action(&args, &common_words, &changes, word_info, &stops)?;
+                        }
+                        None => do_action(&args, &wordlist, &changes, word_info, &stops)?
+                    }
+                    return Ok(());
+                }
+                WordlistFormat::Tags => {
+                    let (wordlist, report) = source
+                        .reader()
+                        .and_then(|reader| {
+                            TaggedWordList::from_reader_with_report(reader, &options)
+                        })
+                        .map_err(read_err)?;
+                    warn_skipped(wordlist_path, &report);
+                    let word_info = WordInfo {
+                        tags: Some(&wordlist),
+                        ..WordInfo::plain()
+                    };
+                    do_action(&args, &wordlist, &changes, word_info, &stops)?;
+                    return Ok(());
+                }
+                WordlistFormat::Hunspell(aff_path) => {
+                    let wordlist = load_hunspell(Path::new(wordlist_path), &aff_path, &options)?;
+                    do_action(&args, &wordlist, &changes, WordInfo::plain(), &stops)?;
+                    return Ok(());
+                }
+                WordlistFormat::Plain => ()
+            }
+
+            let (wordlist, report) = source
+                .reader()
+                .and_then(|reader| OwnedWordList::from_reader_with_report(reader, &options))
+                .map_err(read_err)?;
+            warn_skipped(wordlist_path, &report);
+
+            do_action(&args, &wordlist, &changes, WordInfo::plain(), &stops)?;
         } else {
-            let wordlist = match default_wordlist::default_wordlist() {
+            let wordlist = match default_wordlist::default_indexed_wordlist() {
                 Some(wordlist) => wordlist,
                 None => {
                     let errmsg = String::from("No word list was provided, ")
@@ -88,27 +333,352 @@ fn handle_args(args: CliArgs) -> Result<(), String> {
                     return Err(errmsg);
                 }
             };
-            do_action(&args, &wordlist)
+            if filters_words(&args) {
+                let wordlist = BorrowedWordList::new(options.filter(wordlist.words()));
+                do_action(&args, &wordlist, &changes, WordInfo::plain(), &stops)?;
+            } else {
+                do_action(&args, &wordlist, &changes, WordInfo::plain(), &stops)?;
+            }
         }
     }
 
     Ok(())
 }
 
+/// returns the options used to load word list files
+///
+/// text word lists are always trimmed, and blank lines, `#` comments, and duplicates skipped
+fn load_options(args: &CliArgs) -> WordlistLoadOptions {
+    let mut options = WordlistLoadOptions::cleaned()
+        .with_encoding(Encoding::Detect)
+        .with_lenient(args.lenient);
+    if args.exclude_apostrophes {
+        options = options.with_excluded(CharacterClass::Apostrophe);
+    }
+    if args.exclude_uppercase {
+        options = options.with_excluded(CharacterClass::Uppercase);
+    }
+    options
+}
+
+/// prints a warning for each line of the word list file at `wordlist_path` that was skipped
+fn warn_skipped(wordlist_path: &str, report: &LoadReport) {
+    for skipped in report.skipped() {
+        eprintln!("{}: skipped {}", wordlist_path, skipped);
+    }
+}
+
+/// checks that standard input is used for at most one of the word lists and the batch file
+fn check_stdin(args: &CliArgs) -> Result<(), String> {
+    let batch = match &args.action {
+        ActionType::Find { batch, .. } => batch.as_deref(),
+        ActionType::Test { .. } | ActionType::Index { .. } | ActionType::Wordlist { .. } => None
+    };
+    let stdin_count = args
+        .wordlist_paths
+        .iter()
+        .chain(&args.exclude_list)
+        .map(String::as_str)
+        .chain(batch)
+        .filter(|path| *path == STDIN_PATH)
+        .count();
+    if stdin_count > 1 {
+        return Err(String::from(ERR_STDIN_TWICE));
+    }
+    Ok(())
+}
+
+/// the changes made to the word list by options: the words added by more `-w` options, the
+/// words left out by `--exclude-list` options, and the words blocked by `--safe`
+struct WordlistChanges {
+    added: TrieWordList,
+    excluded: TrieWordList,
+    blocklist: Blocklist
+}
+
+impl WordlistChanges {
+    /// loads the word lists given after the first `-w` option and with `--exclude-list`, and
+    /// the blocklist
+    fn load(args: &CliArgs, options: &WordlistLoadOptions) -> Result<Self, String> {
+        let load_all = |wordlist_paths: &[String]| {
+            let mut words = Vec::new();
+            for wordlist_path in wordlist_paths {
+                words.extend(load_words(wordlist_path, options)?);
+            }
+            Ok::<_, String>(TrieWordList::new(words))
+        };
+        Ok(Self {
+            added: load_all(args.wordlist_paths.get(1..).unwrap_or_default())?,
+            excluded: load_all(&args.exclude_list)?,
+            blocklist: load_blocklist(args)?
+        })
+    }
+
+    /// returns true if these don't change the word list
+    fn is_empty(&self) -> bool {
+        self.added.iter().next().is_none()
+            && self.excluded.iter().next().is_none()
+            && self.blocklist.is_empty()
+    }
+
+    /// returns a view of `wordlist` with the added words, and without the excluded and
+    /// blocked words
+    fn apply<W>(&self, wordlist: W) -> ChangedWordlist<'_, W> {
+        self.blocklist.without_blocked(Difference::new(
+            Union::new(wordlist, &self.added),
+            &self.excluded
+        ))
+    }
+}
+
+/// the type of word list view returned by WordlistChanges::apply
+type ChangedWordlist<'c, W> =
+    Filtered<Difference<Union<W, &'c TrieWordList>, &'c TrieWordList>, &'c Blocklist>;
+
+/// loads the blocklist used with `--safe` (which blocks nothing without it)
+fn load_blocklist(args: &CliArgs) -> Result<Blocklist, String> {
+    match &args.blocklist {
+        Some(path) => Blocklist::from_file(Path::new(path))
+            .map_err(|err| format!("Failed to read blocklist {}: {}", path, err)),
+        None if args.safe => Ok(Blocklist::default_blocklist()),
+        None => Ok(Blocklist::new())
+    }
+}
+
+/// leaves out the anagrams that `blocklist` blocks
+pub fn unblocked<'b, T: AsRef<str>>(
+    iter: impl Iterator<Item = T> + 'b,
+    blocklist: &'b Blocklist
+) -> impl Iterator<Item = T> + 'b {
+    iter.filter(move |anagram| !blocklist.blocks_phrase(anagram.as_ref()))
+}
+
+/// loads the words of the word list at `wordlist_path`, whatever its format
+fn load_words(wordlist_path: &str, options: &WordlistLoadOptions) -> Result<Vec<String>, String> {
+    let source = WordlistSource::open(wordlist_path)?;
+    let read_err =
+        |err: io::Error| format!("Failed to read word list file {}: {}", wordlist_path, err);
+    let (words, report) = match wordlist_format(wordlist_path, &source)? {
+        WordlistFormat::Index => {
+            let index = load_index(source)?;
+            let words = options.filter(index.wordlist().words());
+            (words.into_iter().map(String::from).collect(), None)
+        }
+        WordlistFormat::Frequencies => {
+            let (wordlist, report) = source
+                .reader()
+                .and_then(|reader| FrequencyWordList::from_reader_with_report(reader, options))
+                .map_err(read_err)?;
+            (wordlist.iter().map(String::from).collect(), Some(report))
+        }
+        WordlistFormat::Tags => {
+            let (wordlist, report) = source
+                .reader()
+                .and_then(|reader| TaggedWordList::from_reader_with_report(reader, options))
+                .map_err(read_err)?;
+            (wordlist.iter().map(String::from).collect(), Some(report))
+        }
+        WordlistFormat::Hunspell(aff_path) => {
+            let wordlist = load_hunspell(Path::new(wordlist_path), &aff_path, options)?;
+            (wordlist.iter().map(String::from).collect(), None)
+        }
+        WordlistFormat::Plain => {
+            let (words, report) = source
+                .reader()
+                .and_then(|reader| options.read_with_report(reader))
+                .map_err(read_err)?;
+            (words, Some(report))
+        }
+    };
+    if let Some(report) = report {
+        warn_skipped(wordlist_path, &report);
+    }
+    Ok(words)
+}
+
+/// returns true if words must be left out of word lists that aren't loaded from text
+/// (indexes and the default word list)
+fn filters_words(args: &CliArgs) -> bool {
+    args.exclude_apostrophes || args.exclude_uppercase
+}
+
+/// the kinds of word list file anabot can read
+enum WordlistFormat {
+    /// an index file built with `anabot index build`
+    Index,
+    /// one word per line
+    Plain,
+    /// a word, a tab, and the word's frequency on each line
+    Frequencies,
+    /// a word, a tab, and the word's part of speech tags on each line
+    Tags,
+    /// the stems of a Hunspell dictionary, expanded with the affix file at the given path
+    Hunspell(PathBuf)
+}
+
+/// extra information about the words in the word list, if the word list file has it
+///
+/// `F` is the type of word list the frequencies come from
+struct WordInfo<'w, F> {
+    frequencies: Option<&'w F>,
+    tags: Option<&'w TaggedWordList>
+}
+
+impl<F> Default for WordInfo<'_, F> {
+    fn default() -> Self {
+        Self {
+            frequencies: None,
+            tags: None
+        }
+    }
+}
+
+impl WordInfo<'_, FrequencyWordList> {
+    /// returns the information for a word list without frequencies or tags
+    fn plain() -> Self {
+        Self::default()
+    }
+}
+
+/// where the word list given with `-w` is read from
+enum WordlistSource<'p> {
+    /// a file
+    File(&'p Path),
+    /// the content of standard input (`-w -`), which can only be read once
+    Stdin(Vec<u8>)
+}
+
+impl<'p> WordlistSource<'p> {
+    /// opens the word list at `wordlist_path`, reading all of standard input if it is `-`
+    fn open(wordlist_path: &'p str) -> Result<Self, String> {
+        if wordlist_path == STDIN_PATH {
+            let mut content = Vec::new();
+            io::stdin()
+                .lock()
+                .read_to_end(&mut content)
+                .map_err(|err| format!("Failed to read word list from standard input: {}", err))?;
+            Ok(WordlistSource::Stdin(content))
+        } else {
+            Ok(WordlistSource::File(Path::new(wordlist_path)))
+        }
+    }
+
+    /// returns a reader for the content of the word list, from the start
+    fn reader(&self) -> io::Result<Box<dyn BufRead + '_>> {
+        match self {
+            WordlistSource::File(path) => Ok(Box::new(BufReader::new(fs::File::open(path)?))),
+            WordlistSource::Stdin(content) => Ok(Box::new(content.as_slice()))
+        }
+    }
+}
+
+/// determines the format of the word list in `source` (from `wordlist_path`) from its first word
+fn wordlist_format(wordlist_path: &str, source: &WordlistSource) -> Result<WordlistFormat, String> {
+    let read_err =
+        |err: io::Error| format!("Failed to read word list file {}: {}", wordlist_path, err);
+    // Hunspell dictionaries are a .dic file of stems next to a .aff file of affixes
+    if let WordlistSource::File(path) = source {
+        let aff_path = path.with_extension("aff");
+        if path.extension().is_some_and(|extension| extension == "dic") && aff_path.is_file() {
+            return Ok(WordlistFormat::Hunspell(aff_path));
+        }
+    }
+    let mut reader = source.reader().map_err(read_err)?;
+    let start = reader.fill_buf().map_err(read_err)?;
+    if wordlists::is_index(start) {
+        return Ok(WordlistFormat::Index);
+    }
+    // the first line that isn't blank or a comment, looking only at the start of the file
+    let mut reader = wordlists::decompressed(reader).map_err(read_err)?;
+    let start = Encoding::Detect.decode_lossy(reader.fill_buf().map_err(read_err)?);
+    let first_line = start
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .unwrap_or("");
+
+    let format = match first_line.rsplit_once('\t') {
+        None => WordlistFormat::Plain,
+        Some((_, column)) if column.trim().parse::<u64>().is_ok() => WordlistFormat::Frequencies,
+        Some(_) => WordlistFormat::Tags
+    };
+    Ok(format)
+}
+
+/// expands the Hunspell dictionary at `dic_path` (with the affix file at `aff_path`) into the
+/// words that `options` keep
+fn load_hunspell(
+    dic_path: &Path,
+    aff_path: &Path,
+    options: &WordlistLoadOptions
+) -> Result<OwnedWordList, String> {
+    let dictionary = HunspellDictionary::from_files(aff_path, dic_path).map_err(|err| {
+        format!(
+            "Failed to read Hunspell dictionary {}: {}",
+            dic_path.display(),
+            err
+        )
+    })?;
+    let words = dictionary.expand(&HunspellOptions::new());
+    Ok(options
+        .filter(words.iter().map(String::as_str))
+        .into_iter()
+        .map(String::from)
+        .collect())
+}
+
+/// returns the minimum frequency of words to use, if one was given
+fn min_frequency(args: &CliArgs) -> Option<u64> {
+    match &args.action {
+        ActionType::Find { min_frequency, .. } => *min_frequency,
+        ActionType::Test { .. } | ActionType::Index { .. } | ActionType::Wordlist { .. } => None
+    }
+}
+
 /// used to handle actions involving a wordlist in a common manner independant of wordlist type
 ///
 /// called after a wordlist is determined to be needed and has been successfully resolved.
+/// `word_info` holds the frequencies or tags of the words, if the word list has them;
+/// `wordlist` may be a view of it that leaves out rare words.
+///
+/// `changes` are applied to `wordlist`: the words given with more `-w` options are added to it,
+/// and the words of `--exclude-list` files and blocked words are left out of it.
 ///
 ///# Panics
 ///
 /// this function panics if args.anagram_type is `Standard`, as this is meant to be handled
 /// before this function is called (due to the lack of requirement of a wordlist)
-fn do_action<'a>(args: &CliArgs, wordlist: &'a impl Wordlist<'a>) {
+fn do_action(
+    args: &CliArgs,
+    wordlist: &(impl Wordlist + Sync),
+    changes: &WordlistChanges,
+    word_info: WordInfo<'_, impl WeightedWordlist>,
+    stops: &SearchStops
+) -> Result<(), String> {
+    if changes.is_empty() {
+        run_action(args, wordlist, &changes.blocklist, word_info, stops)
+    } else {
+        let wordlist = changes.apply(wordlist);
+        run_action(args, &wordlist, &changes.blocklist, word_info, stops)
+    }
+}
+
+/// performs the action of do_action with the final word list, leaving out phrases that
+/// `blocklist` blocks
+fn run_action(
+    args: &CliArgs,
+    wordlist: &(impl Wordlist + Sync),
+    blocklist: &Blocklist,
+    word_info: WordInfo<'_, impl WeightedWordlist>,
+    stops: &SearchStops
+) -> Result<(), String> {
     const PANIC_MSG: &str = "Logic Error! Used do_action for standard anagram";
 
     let case_sensitive = !args.case_insensitive;
     match &args.action {
         ActionType::Test { word_a, word_b } => {
+            // both words are looked up several times
+            let wordlist = &CaseFoldedWordlist::new(wordlist);
             let (are_anagrams, anagram_name) = match &args.anagram_type {
                 AnagramType::Standard => panic!("{}", PANIC_MSG),
                 AnagramType::Proper => {
@@ -146,8 +716,8 @@ fn do_action<'a>(args: &CliArgs, wordlist: &'a impl Wordlist<'a>) {
                 if word_a == word_b {
                     println!("Reason: {}", REASON_DUPLICATES);
                 } else {
-                    let word_a_real = wordlist.includes_word(word_a);
-                    let word_b_real = wordlist.includes_word(word_b);
+                    let word_a_real = wordlist.includes_word_with_case(word_a, case_sensitive);
+                    let word_b_real = wordlist.includes_word_with_case(word_b, case_sensitive);
                     if !word_a_real {
                         println!("Reason: {}", REASON_FIRST_NOT_WORD);
                     }
@@ -159,57 +729,913 @@ fn do_action<'a>(args: &CliArgs, wordlist: &'a impl Wordlist<'a>) {
                     }
                 }
             }
+            if !args.simple_output && !case_sensitive {
+                for word in [word_a, word_b] {
+                    let spelling = wordlist.canonical_spelling(word);
+                    if let Some(spelling) = spelling.filter(|spelling| spelling != word) {
+                        println!(
+                            "Note: \"{}\" is spelled \"{}\" in the word list",
+                            word, spelling
+                        );
+                    }
+                }
+            }
         }
         ActionType::Find {
             word,
             limit,
-            min_word_length
+            min_word_length,
+            cache_limit,
+            checkpoint,
+            threads,
+            batch,
+            count,
+            unordered,
+            best,
+            score,
+            min_frequency,
+            sort,
+            template
         } => {
-            fn print_fn(
-                args: &CliArgs,
-                iter: impl Iterator<Item = impl std::fmt::Display>,
-                limit: usize
-            ) {
-                let mut index: usize = 0;
-                for word in iter {
-                    if index >= limit {
-                        break;
-                    }
-                    println!("{}", word);
-
-                    index += 1;
-                }
-                if !args.simple_output {
-                    let anagram_type = match args.anagram_type {
-                        AnagramType::Standard => panic!("{}", PANIC_MSG),
-                        AnagramType::Proper => "proper",
-                        AnagramType::Loose => "loose"
-                    };
-                    println!("found {} {} anagrams", index, anagram_type);
-                }
+            let sort_frequencies = match sort {
+                Some(SortType::Frequency) => Some(word_info.frequencies.ok_or(ERR_NO_FREQUENCIES)?),
+                Some(SortType::Wordlist) | None => None
+            };
+            if min_frequency.is_some() && word_info.frequencies.is_none() {
+                return Err(String::from(ERR_NO_FREQUENCIES));
             }
+
             match &args.anagram_type {
                 AnagramType::Standard => panic!("{}", PANIC_MSG),
                 AnagramType::Proper => {
-                    print_fn(
-                        args,
-                        anagram::find_proper_anagrams(word, wordlist, case_sensitive),
-                        *limit
-                    );
+                    if checkpoint.is_some() {
+                        return Err(String::from(ERR_CHECKPOINT_NOT_LOOSE));
+                    }
+                    if *count {
+                        return Err(String::from(ERR_COUNT_NOT_LOOSE));
+                    }
+                    if best.is_some() {
+                        return Err(String::from(ERR_BEST_NOT_LOOSE));
+                    }
+                    if !template.is_empty() {
+                        return Err(String::from(ERR_TEMPLATE_NOT_LOOSE));
+                    }
+                    if threads.is_some() && batch.is_none() {
+                        return Err(String::from(ERR_THREADS_NO_BATCH));
+                    }
+                    if let Some(batch_path) = batch {
+                        if args.timeout.is_some() {
+                            return Err(String::from(ERR_TIMEOUT_BATCH));
+                        }
+                        return print_batch(
+                            args,
+                            batch_path,
+                            wordlist,
+                            sort_frequencies,
+                            *limit,
+                            *threads
+                        );
+                    }
+                    let mut iter =
+                        anagram::find_proper_anagrams(find_word(word), wordlist, case_sensitive)
+                            .with_limits(stops.start());
+                    let count = match sort_frequencies {
+                        Some(frequencies) => {
+                            // every anagram must be found before the most frequent can be printed
+                            let mut anagrams: Vec<&str> = iter.by_ref().collect();
+                            frequencies.sort_by_weight(&mut anagrams);
+                            print_anagrams(&mut anagrams.into_iter(), *limit)
+                        }
+                        None => print_anagrams(&mut iter, *limit)
+                    };
+                    print_summary(args, count, iter.truncation());
                 }
                 AnagramType::Loose => {
-                    print_fn(
-                        args,
-                        anagram::find_loose_anagrams(
+                    if batch.is_some() {
+                        return Err(String::from(ERR_BATCH_NOT_PROPER));
+                    }
+                    let word = find_word(word);
+                    if *count {
+                        let mode = if *unordered {
+                            CountMode::Unordered
+                        } else {
+                            CountMode::Ordered
+                        };
+                        let count = anagram::count_loose_anagrams(
                             word,
                             wordlist,
                             *min_word_length,
-                            case_sensitive
-                        ),
-                        *limit
-                    );
+                            case_sensitive,
+                            mode,
+                            stops.start()
+                        );
+                        print_count(args, count);
+                        return Ok(());
+                    }
+                    if let Some(best) = best {
+                        let iter = anagram::find_best_loose_anagrams(
+                            word,
+                            wordlist,
+                            *min_word_length,
+                            case_sensitive,
+                            *score,
+                            *best
+                        );
+                        let iter = iter.with_limits(stops.start()).without_blocked(blocklist);
+                        print_best(args, iter);
+                        return Ok(());
+                    }
+                    if let Some(frequencies) = sort_frequencies {
+                        let iter = anagram::find_best_loose_anagrams(
+                            word,
+                            wordlist,
+                            *min_word_length,
+                            case_sensitive,
+                            FrequencyScorer::new(frequencies),
+                            *limit
+                        );
+                        let iter = iter.with_limits(stops.start()).without_blocked(blocklist);
+                        print_best(args, iter);
+                        return Ok(());
+                    }
+                    let cache_limit = match cache_limit {
+                        Some(megabytes) => CacheLimit::Bytes(megabytes.saturating_mul(1024 * 1024)),
+                        None => CacheLimit::Unbounded
+                    };
+                    if let Some(threads) = threads {
+                        if checkpoint.is_some() {
+                            return Err(String::from(
+                                "Checkpoints are not supported for multithreaded searches"
+                            ));
+                        }
+                        let (count, truncation) = parallel::print_loose_anagrams(
+                            word,
+                            wordlist,
+                            *min_word_length,
+                            case_sensitive,
+                            cache_limit,
+                            stops.start(),
+                            *threads,
+                            *limit,
+                            blocklist
+                        )?;
+                        print_summary(args, count, truncation);
+                        return Ok(());
+                    }
+
+                    let templates = template
+                        .iter()
+                        .map(|template| template.parse::<PhraseTemplate>())
+                        .collect::<Result<Vec<PhraseTemplate>, TemplateError>>()
+                        .map_err(|err| err.to_string())?;
+                    let tags = if templates.is_empty() {
+                        None
+                    } else {
+                        Some(word_info.tags.ok_or(ERR_NO_TAGS)?)
+                    };
+                    let checkpoint_path = checkpoint.as_deref().map(Path::new);
+                    let iter = match checkpoint_path {
+                        Some(path) if path.exists() => resume_search(
+                            path,
+                            word,
+                            *min_word_length,
+                            case_sensitive,
+                            &templates,
+                            wordlist,
+                            tags
+                        )?,
+                        _ => {
+                            let iter = anagram::find_loose_anagrams(
+                                word,
+                                wordlist,
+                                *min_word_length,
+                                case_sensitive
+                            );
+                            match tags {
+                                Some(tags) => iter.with_templates(&templates, tags),
+                                None => iter
+                            }
+                        }
+                    };
+                    let mut iter = iter
+                        .with_cache_limit(cache_limit)
+                        .with_limits(stops.start());
+                    let count = print_anagrams(&mut unblocked(&mut iter, blocklist), *limit);
+                    print_summary(args, count, iter.truncation());
+
+                    if let Some(path) = checkpoint_path {
+                        let finished = count < *limit && iter.truncation().is_none();
+                        save_search(args, path, finished, &iter, wordlist)?;
+                    }
+                }
+            }
+        }
+        ActionType::Index { .. } | ActionType::Wordlist { .. } => panic!("{}", PANIC_NOT_SEARCH)
+    }
+
+    Ok(())
+}
+
+/// loads the index file in `source`
+fn load_index(source: WordlistSource) -> Result<IndexFile, String> {
+    match source {
+        WordlistSource::File(path) => IndexFile::open(path)
+            .map_err(|err| format!("Failed to load word list index {}: {}", path.display(), err)),
+        WordlistSource::Stdin(content) => IndexFile::from_vec(content).map_err(|err| {
+            format!(
+                "Failed to load word list index from standard input: {}",
+                err
+            )
+        })
+    }
+}
+
+/// writes an index with the words written by its argument, used by build_index
+type WriteIndex<'f> =
+    dyn Fn(&dyn Fn(&mut BufWriter<fs::File>) -> Result<(), IndexError>) -> Result<(), String> + 'f;
+
+/// writes an index of `wordlist` with `changes` applied, returning the number of words written
+fn write_words(
+    write: &WriteIndex,
+    wordlist: &impl Wordlist,
+    changes: &WordlistChanges
+) -> Result<usize, String> {
+    if changes.is_empty() {
+        write(&|writer| wordlists::write_index(wordlist, writer))?;
+        Ok(wordlist.iter().count())
+    } else {
+        let combined = changes.apply(wordlist);
+        write(&|writer| wordlists::write_index(&combined, writer))?;
+        Ok(combined.iter().count())
+    }
+}
+
+/// like write_words, but also writes the weights of the words (added words have a weight of 0)
+fn write_weighted_words(
+    write: &WriteIndex,
+    wordlist: &impl WeightedWordlist,
+    changes: &WordlistChanges
+) -> Result<usize, String> {
+    if changes.is_empty() {
+        write(&|writer| wordlists::write_weighted_index(wordlist, writer))?;
+        Ok(wordlist.iter().count())
+    } else {
+        let combined: FrequencyWordList = changes
+            .apply(wordlist)
+            .iter()
+            .map(|word| (word.to_string(), wordlist.weight(word).unwrap_or(0)))
+            .collect();
+        write(&|writer| wordlists::write_weighted_index(&combined, writer))?;
+        Ok(combined.iter().count())
+    }
+}
+
+/// compiles the word lists given with `-w` (or the default word list) into an index at `output`
+fn build_index(args: &CliArgs, output: &Path) -> Result<(), String> {
+    let write = |write_words: &dyn Fn(&mut BufWriter<fs::File>) -> Result<(), IndexError>| {
+        let write_err =
+            |err: IndexError| format!("Failed to write index {}: {}", output.display(), err);
+        let file = fs::File::create(output).map_err(|err| write_err(err.into()))?;
+        let mut writer = BufWriter::new(file);
+        write_words(&mut writer).map_err(write_err)?;
+        writer.flush().map_err(|err| write_err(err.into()))
+    };
+
+    let options = load_options(args);
+    let changes = WordlistChanges::load(args, &options)?;
+    let word_count = match args.wordlist_paths.first() {
+        Some(wordlist_path) => {
+            let source = WordlistSource::open(wordlist_path)?;
+            let read_err = |err: io::Error| {
+                format!("Failed to read word list file {}: {}", wordlist_path, err)
+            };
+            match wordlist_format(wordlist_path, &source)? {
+                WordlistFormat::Index => {
+                    let index = load_index(source)?;
+                    let wordlist = index.wordlist();
+                    let words = options.filter(wordlist.words());
+                    if wordlist.has_weights() {
+                        let weighted: FrequencyWordList = words
+                            .iter()
+                            .map(|word| (word.to_string(), wordlist.weight(word).unwrap_or(0)))
+                            .collect();
+                        write_weighted_words(&write, &weighted, &changes)?
+                    } else {
+                        let words = BorrowedWordList::new(words.iter().copied());
+                        write_words(&write, &words, &changes)?
+                    }
+                }
+                WordlistFormat::Frequencies => {
+                    let (wordlist, report) = source
+                        .reader()
+                        .and_then(|reader| {
+                            FrequencyWordList::from_reader_with_report(reader, &options)
+                        })
+                        .map_err(read_err)?;
+                    warn_skipped(wordlist_path, &report);
+                    write_weighted_words(&write, &wordlist, &changes)?
+                }
+                WordlistFormat::Tags => {
+                    let (wordlist, report) = source
+                        .reader()
+                        .and_then(|reader| {
+                            TaggedWordList::from_reader_with_report(reader, &options)
+                        })
+                        .map_err(read_err)?;
+                    warn_skipped(wordlist_path, &report);
+                    write_words(&write, &wordlist, &changes)?
                 }
+                WordlistFormat::Hunspell(aff_path) => {
+                    let wordlist = load_hunspell(Path::new(wordlist_path), &aff_path, &options)?;
+                    write_words(&write, &wordlist, &changes)?
+                }
+                WordlistFormat::Plain => {
+                    let (wordlist, report) = source
+                        .reader()
+                        .and_then(|reader| OwnedWordList::from_reader_with_report(reader, &options))
+                        .map_err(read_err)?;
+                    warn_skipped(wordlist_path, &report);
+                    write_words(&write, &wordlist, &changes)?
+                }
+            }
+        }
+        None => {
+            let wordlist =
+                default_wordlist::default_wordlist_with_options(&options).ok_or_else(|| {
+                    String::from("No word list was provided, and no default wordlist is available")
+                })?;
+            write_words(&write, &wordlist, &changes)?
+        }
+    };
+
+    if !args.simple_output {
+        println!(
+            "wrote index of {} words to {}",
+            word_count,
+            output.display()
+        );
+    }
+    Ok(())
+}
+
+/// prints statistics about the word list at `wordlist_path` and the problems found in it,
+/// failing if any word has an error
+fn check_wordlist(wordlist_path: &str, format: ReportFormat, top: usize) -> Result<(), String> {
+    let checked = read_checked_words(wordlist_path)?;
+    let stats = WordlistStats::new(&checked.words[..]);
+    let line_numbers = checked.line_numbers.as_deref();
+    match format {
+        ReportFormat::Text => print_stats(&stats, top, line_numbers, &checked.undecodable),
+        ReportFormat::Json => print_stats_json(&stats, top, line_numbers, &checked.undecodable)
+    }
+
+    if !checked.undecodable.is_empty() {
+        return Err(format!(
+            "{} has {} lines that couldn't be decoded as UTF-8",
+            wordlist_path,
+            checked.undecodable.len()
+        ));
+    }
+    if stats.has_errors() {
+        return Err(format!(
+            "{} has {} words with errors",
+            wordlist_path,
+            stats.error_count()
+        ));
+    }
+    Ok(())
+}
+
+/// the words read from a word list by `wordlist check`
+struct CheckedWords {
+    words: Vec<String>,
+    /// the line each word is on (for text word lists)
+    line_numbers: Option<Vec<usize>>,
+    /// the lines that couldn't be decoded, which are errors
+    undecodable: Vec<SkippedLine>
+}
+
+/// reads the words of the word list at `wordlist_path` without cleaning them up
+///
+/// text word lists must be UTF-8. only `#` comments are skipped, and only the first column of
+/// word lists with frequencies or tags is used
+fn read_checked_words(wordlist_path: &str) -> Result<CheckedWords, String> {
+    let source = WordlistSource::open(wordlist_path)?;
+    let read_err =
+        |err: io::Error| format!("Failed to read word list file {}: {}", wordlist_path, err);
+    let has_columns = match wordlist_format(wordlist_path, &source)? {
+        WordlistFormat::Index => {
+            let index = load_index(source)?;
+            return Ok(CheckedWords {
+                words: index.wordlist().words().map(String::from).collect(),
+                line_numbers: None,
+                undecodable: Vec::new()
+            });
+        }
+        WordlistFormat::Hunspell(aff_path) => {
+            let options = WordlistLoadOptions::new();
+            let wordlist = load_hunspell(Path::new(wordlist_path), &aff_path, &options)?;
+            return Ok(CheckedWords {
+                words: wordlist.iter().map(String::from).collect(),
+                line_numbers: None,
+                undecodable: Vec::new()
+            });
+        }
+        WordlistFormat::Plain => false,
+        WordlistFormat::Frequencies | WordlistFormat::Tags => true
+    };
+
+    // lines that can't be decoded are collected rather than failing the check straight away
+    let options = WordlistLoadOptions::new()
+        .with_encoding(Encoding::Utf8)
+        .with_lenient(true);
+    let (lines, report) = source
+        .reader()
+        .and_then(|reader| options.read_with_report(reader))
+        .map_err(read_err)?;
+    let undecodable = report.skipped().to_vec();
+    // the numbers of the lines that were read, skipping those that couldn't be decoded
+    let mut skipped_numbers = undecodable.iter().map(SkippedLine::line_number).peekable();
+    let line_numbers = (1..).filter(|number| {
+        let skipped = skipped_numbers.peek() == Some(number);
+        if skipped {
+            skipped_numbers.next();
+        }
+        !skipped
+    });
+
+    let mut words = Vec::new();
+    let mut word_line_numbers = Vec::new();
+    for (line_number, line) in line_numbers.zip(lines) {
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+        let word = match line.rsplit_once('\t') {
+            Some((word, _)) if has_columns => String::from(word),
+            _ => line
+        };
+        words.push(word);
+        word_line_numbers.push(line_number);
+    }
+    Ok(CheckedWords {
+        words,
+        line_numbers: Some(word_line_numbers),
+        undecodable
+    })
+}
+
+/// returns where the word at `position` is in the word list, for reports
+fn word_location(position: usize, line_numbers: Option<&[usize]>) -> String {
+    match line_numbers {
+        Some(line_numbers) => format!("line {}", line_numbers[position]),
+        None => format!("word {}", position + 1)
+    }
+}
+
+/// prints `stats` as text, listing at most `top` duplicates, case variants, and anagram groups
+fn print_stats(
+    stats: &WordlistStats,
+    top: usize,
+    line_numbers: Option<&[usize]>,
+    undecodable: &[SkippedLine]
+) {
+    let print_more = |total: usize| {
+        if total > top {
+            println!("  ... and {} more", total - top);
+        }
+    };
+
+    println!(
+        "words: {} ({} distinct)",
+        stats.word_count(),
+        stats.distinct_count()
+    );
+    println!(
+        "fingerprint: {} (unordered: {})",
+        stats.fingerprint(),
+        stats.unordered_fingerprint()
+    );
+    println!("lengths:");
+    for (length, count) in stats.length_counts() {
+        println!("  {}: {}", length, count);
+    }
+    println!("characters: {}", stats.character_counts().len());
+    for (character, count) in stats.character_counts() {
+        println!("  {:?}: {}", character, count);
+    }
+
+    println!("duplicates: {}", stats.duplicates().len());
+    for (word, count) in stats.duplicates().iter().take(top) {
+        println!("  {:?} appears {} times", word, count);
+    }
+    print_more(stats.duplicates().len());
+    println!("case variants: {}", stats.case_variants().len());
+    for spellings in stats.case_variants().iter().take(top) {
+        println!("  {}", spellings.join(", "));
+    }
+    print_more(stats.case_variants().len());
+    println!("anagram groups: {}", stats.anagram_classes().len());
+    for class in stats.anagram_classes().iter().take(top) {
+        println!("  {} words: {}", class.len(), class.join(", "));
+    }
+    print_more(stats.anagram_classes().len());
+
+    println!("undecodable lines: {}", undecodable.len());
+    for line in undecodable {
+        println!("  {}", line);
+    }
+    let (errors, warnings): (Vec<_>, Vec<_>) = stats
+        .flagged()
+        .iter()
+        .partition(|word| word.problem().is_error());
+    for (heading, flagged) in [("errors", errors), ("warnings", warnings)] {
+        println!("{}: {}", heading, flagged.len());
+        for word in flagged {
+            println!(
+                "  {}: {:?}: {}",
+                word_location(word.position(), line_numbers),
+                word.word(),
+                word.problem()
+            );
+        }
+    }
+}
+
+/// prints `stats` as a JSON object on one line, listing at most `top` duplicates, case
+/// variants, and anagram groups
+fn print_stats_json(
+    stats: &WordlistStats,
+    top: usize,
+    line_numbers: Option<&[usize]>,
+    undecodable: &[SkippedLine]
+) {
+    let list = |items: Vec<String>| format!("[{}]", items.join(","));
+    let words = |words: &[String]| list(words.iter().map(|word| json_string(word)).collect());
+
+    let lengths: Vec<String> = stats
+        .length_counts()
+        .iter()
+        .map(|(length, count)| format!("\"{}\":{}", length, count))
+        .collect();
+    let characters: Vec<String> = stats
+        .character_counts()
+        .iter()
+        .map(|(character, count)| format!("{}:{}", json_string(&character.to_string()), count))
+        .collect();
+    let duplicates = stats
+        .duplicates()
+        .iter()
+        .take(top)
+        .map(|(word, count)| format!("{{\"word\":{},\"count\":{}}}", json_string(word), count))
+        .collect();
+    let case_variants = stats
+        .case_variants()
+        .iter()
+        .take(top)
+        .map(|spellings| words(spellings));
+    let anagram_groups = stats
+        .anagram_classes()
+        .iter()
+        .take(top)
+        .map(|class| words(class));
+    let flagged = stats
+        .flagged()
+        .iter()
+        .map(|word| {
+            let line = line_numbers.map_or(String::from("null"), |line_numbers| {
+                line_numbers[word.position()].to_string()
+            });
+            format!(
+                "{{\"position\":{},\"line\":{},\"word\":{},\"problem\":{},\"error\":{}}}",
+                word.position(),
+                line,
+                json_string(word.word()),
+                json_string(&word.problem().to_string()),
+                word.problem().is_error()
+            )
+        })
+        .collect();
+    let undecodable = undecodable
+        .iter()
+        .map(|line| {
+            format!(
+                "{{\"line\":{},\"reason\":{}}}",
+                line.line_number(),
+                json_string(line.reason())
+            )
+        })
+        .collect();
+
+    println!(
+        "{{\"words\":{},\"distinct_words\":{},\"fingerprint\":\"{}\",\
+         \"unordered_fingerprint\":\"{}\",\"lengths\":{{{}}},\"characters\":{{{}}},\
+         \"duplicate_count\":{},\"duplicates\":{},\"case_variant_count\":{},\
+         \"case_variants\":{},\"anagram_group_count\":{},\"anagram_groups\":{},\
+         \"undecodable_lines\":{},\"error_count\":{},\"flagged\":{}}}",
+        stats.word_count(),
+        stats.distinct_count(),
+        stats.fingerprint(),
+        stats.unordered_fingerprint(),
+        lengths.join(","),
+        characters.join(","),
+        stats.duplicates().len(),
+        list(duplicates),
+        stats.case_variants().len(),
+        list(case_variants.collect()),
+        stats.anagram_classes().len(),
+        list(anagram_groups.collect()),
+        list(undecodable),
+        stats.error_count(),
+        list(flagged)
+    );
+}
+
+/// returns `text` as a JSON string literal
+fn json_string(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for character in text.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            character if character.is_control() => {
+                escaped.push_str(&format!("\\u{:04x}", u32::from(character)))
+            }
+            character => escaped.push(character)
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+impl AnagramScorer for ScoreType {
+    fn word_score(&self, word: &str) -> f64 {
+        match self {
+            ScoreType::FewerWords => FewerWords.word_score(word),
+            ScoreType::LongerWords => LongerWords.word_score(word)
+        }
+    }
+
+    fn per_word_adjustment(&self) -> f64 {
+        match self {
+            ScoreType::FewerWords => FewerWords.per_word_adjustment(),
+            ScoreType::LongerWords => LongerWords.per_word_adjustment()
+        }
+    }
+}
+
+/// prints the best loose anagrams found by `iter` with their scores, followed by a summary
+fn print_best(args: &CliArgs, mut iter: BestLooseAnagramsIterator) {
+    let mut count = 0;
+    for (anagram, score) in iter.by_ref() {
+        if args.simple_output {
+            println!("{}\t{}", anagram, score);
+        } else {
+            println!("{} (score {})", anagram, score);
+        }
+        count += 1;
+    }
+    print_summary(args, count, iter.truncation());
+}
+
+/// prints the number of loose anagrams counted, and why the count is a lower bound (if it is)
+///
+/// in simple mode, only the number is printed to stdout
+fn print_count(args: &CliArgs, count: LooseAnagramCount) {
+    let reason = match count {
+        LooseAnagramCount::Exact(_) => None,
+        LooseAnagramCount::AtLeast {
+            truncation: Some(truncation),
+            ..
+        } => Some(truncation.to_string()),
+        LooseAnagramCount::AtLeast {
+            truncation: None, ..
+        } => Some(String::from("too many to count"))
+    };
+
+    if args.simple_output {
+        println!("{}", count.count());
+        if let Some(reason) = reason {
+            eprintln!("count truncated: {}", reason);
+        }
+        return;
+    }
+
+    println!("counted {} loose anagrams", count);
+    if let Some(reason) = reason {
+        println!("count truncated: {}", reason);
+    }
+}
+
+/// returns the word to find anagrams of
+///
+///# Panics
+///
+/// panics if no word was given; clap only allows this when `--batch` is used,
+/// which must be handled before calling this function
+fn find_word(word: &Option<String>) -> &str {
+    word.as_deref()
+        .expect("Logic Error! No word to find anagrams of")
+}
+
+/// finds and prints the proper anagrams of every word in the batch file at `batch_path`
+///
+/// `--limit` applies to each word separately
+fn print_batch(
+    args: &CliArgs,
+    batch_path: &str,
+    wordlist: &(impl Wordlist + Sync),
+    sort_frequencies: Option<&impl WeightedWordlist>,
+    limit: usize,
+    threads: Option<usize>
+) -> Result<(), String> {
+    let targets = read_batch(batch_path)?;
+    let case_sensitive = !args.case_insensitive;
+    let results = match threads {
+        Some(threads) => {
+            parallel::find_proper_anagrams_batch(&targets, wordlist, case_sensitive, threads)?
+        }
+        None => anagram::find_proper_anagrams_batch(
+            targets.iter().map(String::as_str),
+            wordlist,
+            case_sensitive
+        )
+    };
+
+    for (target, mut anagrams) in results {
+        if let Some(frequencies) = sort_frequencies {
+            frequencies.sort_by_weight(&mut anagrams);
+        }
+        let anagrams = &anagrams[..anagrams.len().min(limit)];
+        if args.simple_output {
+            // one line per word: the word, then its anagrams, separated by tabs
+            let line: Vec<&str> = iter::once(target).chain(anagrams.iter().copied()).collect();
+            println!("{}", line.join("\t"));
+        } else {
+            println!("{}:", target);
+            for anagram in anagrams {
+                println!("  {}", anagram);
+            }
+            println!("found {} proper anagrams of \"{}\"", anagrams.len(), target);
+        }
+    }
+    Ok(())
+}
+
+/// reads the words of a batch file (or standard input if `batch_path` is `-`)
+///
+/// surrounding whitespace is removed from each line, and empty lines are skipped
+fn read_batch(batch_path: &str) -> Result<Vec<String>, String> {
+    let read_err = |err: io::Error| format!("Failed to read batch file {}: {}", batch_path, err);
+    let reader: Box<dyn BufRead> = if batch_path == STDIN_PATH {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(BufReader::new(
+            fs::File::open(batch_path).map_err(read_err)?
+        ))
+    };
+
+    let mut targets = Vec::new();
+    for line in reader.lines() {
+        let line = line.map_err(read_err)?;
+        let target = line.trim();
+        if !target.is_empty() {
+            targets.push(target.to_string());
+        }
+    }
+    Ok(targets)
+}
+
+/// resumes the loose anagram search saved at `path`, checking that it is a search for `word`
+/// with the same minimum word length, case sensitivity and templates
+fn resume_search<'a>(
+    path: &Path,
+    word: &str,
+    min_word_length: usize,
+    case_sensitive: bool,
+    templates: &[PhraseTemplate],
+    wordlist: &'a impl Wordlist,
+    tags: Option<&TaggedWordList>
+) -> Result<LooseAnagramsIterator<'a>, String> {
+    let checkpoint_err =
+        |err: CheckpointError| format!("Failed to resume search from {}: {}", path.display(), err);
+
+    let checkpoint = LooseAnagramsCheckpoint::load(path).map_err(checkpoint_err)?;
+    if checkpoint.target_word() != word {
+        return Err(format!(
+            "Checkpoint {} is for a search for \"{}\", not \"{}\"",
+            path.display(),
+            checkpoint.target_word(),
+            word
+        ));
+    }
+    if checkpoint.min_word_length() != min_word_length {
+        return Err(format!(
+            "Checkpoint {} is for a search with a minimum word length of {}, not {}",
+            path.display(),
+            checkpoint.min_word_length(),
+            min_word_length
+        ));
+    }
+    if checkpoint.case_sensitive() != case_sensitive {
+        let (saved, given) = if checkpoint.case_sensitive() {
+            ("case sensitive", "case insensitive")
+        } else {
+            ("case insensitive", "case sensitive")
+        };
+        return Err(format!(
+            "Checkpoint {} is for a {} search, not a {} one",
+            path.display(),
+            saved,
+            given
+        ));
+    }
+    if checkpoint.templates() != templates {
+        let describe = |templates: &[PhraseTemplate]| match templates {
+            [] => String::from("no templates"),
+            _ => {
+                let templates: Vec<String> = templates
+                    .iter()
+                    .map(|template| format!("\"{}\"", template))
+                    .collect();
+                format!("the templates {}", templates.join(", "))
             }
+        };
+        return Err(format!(
+            "Checkpoint {} is for a search with {}, not {}",
+            path.display(),
+            describe(checkpoint.templates()),
+            describe(templates)
+        ));
+    }
+    match tags {
+        Some(tags) => anagram::resume_loose_anagrams_with_templates(&checkpoint, wordlist, tags),
+        None => anagram::resume_loose_anagrams(&checkpoint, wordlist)
+    }
+    .map_err(checkpoint_err)
+}
+
+/// saves the state of a loose anagram search to `path`, or removes `path` if the search finished
+fn save_search(
+    args: &CliArgs,
+    path: &Path,
+    finished: bool,
+    iter: &LooseAnagramsIterator,
+    wordlist: &impl Wordlist
+) -> Result<(), String> {
+    if finished {
+        if path.exists() {
+            fs::remove_file(path).map_err(|err| {
+                format!("Failed to remove checkpoint {}: {}", path.display(), err)
+            })?;
+        }
+        if !args.simple_output {
+            println!("search complete");
+        }
+    } else {
+        iter.checkpoint(wordlist)
+            .save(path)
+            .map_err(|err| format!("Failed to save checkpoint {}: {}", path.display(), err))?;
+        if !args.simple_output {
+            println!("search saved to {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// prints anagrams from `iter` until it is exhausted or `limit` anagrams have been printed
+///
+/// returns the number of anagrams printed
+fn print_anagrams(iter: &mut impl Iterator<Item = impl Display>, limit: usize) -> usize {
+    let mut index: usize = 0;
+    for word in iter.take(limit) {
+        println!("{}", word);
+
+        index += 1;
+    }
+    index
+}
+
+/// prints the number of anagrams found, and why the search stopped early (if it did)
+///
+/// with simple output, only the reason the search stopped early is printed (to stderr)
+fn print_summary(args: &CliArgs, count: usize, truncation: Option<Truncation>) {
+    if args.simple_output {
+        if let Some(truncation) = truncation {
+            eprintln!("search truncated: {}", truncation);
         }
+        return;
+    }
+
+    let anagram_type = match args.anagram_type {
+        AnagramType::Standard => "standard",
+        AnagramType::Proper => "proper",
+        AnagramType::Loose => "loose"
+    };
+    println!("found {} {} anagrams", count, anagram_type);
+    if let Some(truncation) = truncation {
+        println!("search truncated: {}", truncation);
     }
 }
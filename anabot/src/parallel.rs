@@ -0,0 +1,80 @@
+//! multithreaded loose anagram search, only built with the `parallel` feature
+
+use anagrambot::{
+    anagram::{self, CacheLimit, ProperAnagramsBatchResult, SearchLimits, Truncation},
+    wordlist::{Blocklist, Wordlist}
+};
+use rayon::{iter::ParallelIterator, ThreadPool, ThreadPoolBuilder};
+
+use std::{sync::mpsc, thread};
+
+use crate::{print_anagrams, unblocked};
+
+/// the number of anagrams search threads may find before waiting for them to be printed
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// searches for loose anagrams on `threads` threads, printing them until `limit` have been printed
+///
+/// a `threads` value of 0 uses one thread per CPU, and anagrams that `blocklist` blocks are not
+/// printed.
+/// returns the number of anagrams printed and why the search stopped early (if it did)
+#[allow(clippy::too_many_arguments)]
+pub fn print_loose_anagrams(
+    word: &str,
+    wordlist: &impl Wordlist,
+    min_word_length: usize,
+    case_sensitive: bool,
+    cache_limit: CacheLimit,
+    limits: SearchLimits,
+    threads: usize,
+    limit: usize,
+    blocklist: &Blocklist
+) -> Result<(usize, Option<Truncation>), String> {
+    let pool = thread_pool(threads)?;
+    let search = anagram::par_find_loose_anagrams(word, wordlist, min_word_length, case_sensitive)
+        .with_cache_limit(cache_limit)
+        .with_limits(limits);
+    let report = search.truncation_report();
+
+    let (sender, receiver) = mpsc::sync_channel(CHANNEL_CAPACITY);
+    let count = thread::scope(|scope| {
+        scope.spawn(|| {
+            // sending fails once the receiver is dropped, which ends the search
+            pool.install(|| {
+                search.try_for_each_with(sender, |sender, anagram| sender.send(anagram))
+            })
+        });
+        let count = print_anagrams(&mut unblocked(receiver.iter(), blocklist), limit);
+        drop(receiver);
+        count
+    });
+
+    Ok((count, report.truncation()))
+}
+
+/// finds the proper anagrams of every word in `targets`, scanning the wordlist on `threads` threads
+///
+/// a `threads` value of 0 uses one thread per CPU
+pub fn find_proper_anagrams_batch<'a, 'b>(
+    targets: &'b [String],
+    wordlist: &'a (impl Wordlist + Sync),
+    case_sensitive: bool,
+    threads: usize
+) -> Result<Vec<ProperAnagramsBatchResult<'a, 'b>>, String> {
+    let pool = thread_pool(threads)?;
+    Ok(pool.install(|| {
+        anagram::par_find_proper_anagrams_batch(
+            targets.iter().map(String::as_str),
+            wordlist,
+            case_sensitive
+        )
+    }))
+}
+
+/// creates a thread pool with `threads` threads (or one per CPU if `threads` is 0)
+fn thread_pool(threads: usize) -> Result<ThreadPool, String> {
+    ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .map_err(|err| format!("Failed to start search threads: {}", err))
+}
@@ -0,0 +1,67 @@
+//! Compares parallel loose anagram searches using different numbers of threads with a
+//! sequential search
+//!
+//! Run with `cargo bench -p anagrambot --features parallel --bench parallel`. Like the wordlist
+//! benchmarks, each measurement is simply the best of several runs. Speedups are limited by the
+//! number of CPUs available.
+
+use anagrambot::{
+    anagram::{find_loose_anagrams, par_find_loose_anagrams},
+    wordlist::TrieWordList
+};
+use rayon::{iter::ParallelIterator, ThreadPoolBuilder};
+use std::{
+    hint::black_box,
+    time::{Duration, Instant}
+};
+
+const WORDS: &str = include_str!("../words.txt");
+const RUNS: u32 = 3;
+
+const TARGETS: [&str; 3] = ["dormitory", "listen here", "anagram bot"];
+const THREADS: [usize; 4] = [1, 2, 4, 8];
+
+/// Returns the shortest time taken by `f` over [RUNS] runs
+fn best_of<R>(mut f: impl FnMut() -> R) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    println!(
+        "{} CPUs available",
+        std::thread::available_parallelism().map_or(1, usize::from)
+    );
+    let wordlist: TrieWordList = WORDS.lines().map(String::from).collect();
+
+    for target in TARGETS {
+        let sequential = best_of(|| find_loose_anagrams(target, &wordlist, 1, false).count());
+        println!(
+            "{:<40} {:>10.3?}",
+            format!("every loose anagram of {:?}", target),
+            sequential
+        );
+
+        for threads in THREADS {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            let parallel = best_of(|| {
+                pool.install(|| par_find_loose_anagrams(target, &wordlist, 1, false).count())
+            });
+            println!(
+                "{:<40} {:>10.3?}  ({:.2}x)",
+                format!("  with {} threads", threads),
+                parallel,
+                sequential.as_secs_f64() / parallel.as_secs_f64()
+            );
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Compares loose anagram searches using a [TrieWordList] with those using a [BorrowedWordList]
+//!
+//! Run with `cargo bench -p anagrambot`. This doesn't use any benchmarking framework,
+//! so each measurement is simply the best of several runs.
+
+use anagrambot::{
+    anagram::find_loose_anagrams,
+    wordlist::{BorrowedWordList, TrieWordList, Wordlist}
+};
+use std::{
+    hint::black_box,
+    time::{Duration, Instant}
+};
+
+const WORDS: &str = include_str!("../words.txt");
+const RUNS: u32 = 5;
+
+const TARGETS: [&str; 4] = ["racecar", "dormitory", "conversation", "anagram search"];
+// loose anagram searches of long targets return too many results to collect,
+// so only the first results are taken
+const MAX_RESULTS: usize = 10_000;
+
+/// Returns the shortest time taken by `f` over [RUNS] runs
+fn best_of<R>(mut f: impl FnMut() -> R) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn report(name: &str, borrowed: Duration, trie: Duration) {
+    println!(
+        "{:<40} borrowed {:>10.3?}  trie {:>10.3?}  ({:.2}x)",
+        name,
+        borrowed,
+        trie,
+        borrowed.as_secs_f64() / trie.as_secs_f64()
+    );
+}
+
+fn main() {
+    let borrowed: BorrowedWordList = WORDS.lines().collect();
+    let trie: TrieWordList = WORDS.lines().map(String::from).collect();
+
+    report(
+        "build wordlist",
+        best_of(|| WORDS.lines().collect::<BorrowedWordList>()),
+        best_of(|| WORDS.lines().map(String::from).collect::<TrieWordList>())
+    );
+
+    let lookups: Vec<&str> = WORDS
+        .lines()
+        .step_by(100)
+        .chain(["notaword", "qqq"])
+        .collect();
+    report(
+        "includes_word",
+        best_of(|| {
+            lookups
+                .iter()
+                .filter(|word| borrowed.includes_word(word))
+                .count()
+        }),
+        best_of(|| {
+            lookups
+                .iter()
+                .filter(|word| trie.includes_word(word))
+                .count()
+        })
+    );
+
+    for target in TARGETS {
+        // the first result requires finding every word that fits into the target
+        report(
+            &format!("first loose anagram of {:?}", target),
+            best_of(|| find_loose_anagrams(target, &borrowed, 1, false).next()),
+            best_of(|| find_loose_anagrams(target, &trie, 1, false).next())
+        );
+        report(
+            &format!("{} loose anagrams of {:?}", MAX_RESULTS, target),
+            best_of(|| {
+                find_loose_anagrams(target, &borrowed, 1, false)
+                    .take(MAX_RESULTS)
+                    .count()
+            }),
+            best_of(|| {
+                find_loose_anagrams(target, &trie, 1, false)
+                    .take(MAX_RESULTS)
+                    .count()
+            })
+        );
+    }
+}
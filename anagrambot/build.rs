@@ -0,0 +1,50 @@
+//! Precompiles the default wordlist into an index, which is embedded into the library
+//!
+//! The index is written in the same format as [write_index](anagrambot::wordlist::write_index),
+//! using the library's own encoder. With the `compressed-default-wordlist` feature, the index
+//! is gzip compressed.
+
+use std::{env, fs, path::Path};
+
+// only the encoder is used here
+#[allow(dead_code)]
+#[path = "src/wordlist/index/format.rs"]
+mod format;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=words.txt");
+    println!("cargo:rerun-if-changed=src/wordlist/index/format.rs");
+
+    // nothing is embedded without the default wordlist
+    if env::var_os("CARGO_FEATURE_NO_DEFAULT_WORDLIST").is_some() {
+        return;
+    }
+
+    let content = fs::read_to_string("words.txt").expect("failed to read words.txt");
+    let words: Vec<&str> = content.lines().collect();
+    let index = format::encode(&words, None).expect("failed to index words.txt");
+
+    let out_dir = env::var_os("OUT_DIR").expect("OUT_DIR is set by cargo");
+    fs::write(
+        Path::new(&out_dir).join("default_wordlist.idx"),
+        compress(index)
+    )
+    .expect("failed to write the default wordlist index");
+}
+
+#[cfg(feature = "compressed-default-wordlist")]
+fn compress(index: Vec<u8>) -> Vec<u8> {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+    encoder
+        .write_all(&index)
+        .and_then(|_| encoder.finish())
+        .expect("failed to compress the default wordlist index")
+}
+
+#[cfg(not(feature = "compressed-default-wordlist"))]
+fn compress(index: Vec<u8>) -> Vec<u8> {
+    index
+}
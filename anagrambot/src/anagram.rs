@@ -29,7 +29,23 @@ use std::collections::BTreeMap;
 type Charmap = BTreeMap<char, u32>;
 
 pub mod loose_anagram;
-pub use loose_anagram::{are_loose_anagrams, are_loose_anagrams_strict, find_loose_anagrams};
+pub use loose_anagram::{
+    are_loose_anagrams, are_loose_anagrams_strict, count_loose_anagrams, find_best_loose_anagrams,
+    find_loose_anagrams, resume_loose_anagrams, resume_loose_anagrams_with_templates,
+    AnagramScorer, BestLooseAnagramsIterator, CacheLimit, CacheStats, CheckpointError, CountMode,
+    FewerWords, FrequencyScorer, LongerWords, LooseAnagramCount, LooseAnagramsCheckpoint,
+    LooseAnagramsIterator, PhraseTemplate, ScoredLooseAnagram, TemplateError
+};
+#[cfg(feature = "parallel")]
+pub use loose_anagram::{par_find_loose_anagrams, ParLooseAnagrams, TruncationReport};
+
+mod search_limits;
+pub use search_limits::{CancellationToken, SearchLimits, Truncation};
+
+mod proper_batch;
+#[cfg(feature = "parallel")]
+pub use proper_batch::par_find_proper_anagrams_batch;
+pub use proper_batch::{find_proper_anagrams_batch, ProperAnagramsBatchResult};
 
 /// Returns a [Charmap] with the number of times each character appears in `word`
 ///
@@ -84,7 +100,7 @@ impl<'a> WordWithCharmap<'a> {
         self.word
     }
     pub fn get_charmap(&mut self) -> &Charmap {
-        if self.word_charmap == None {
+        if self.word_charmap.is_none() {
             self.word_charmap = Some(get_charcount_map(self.word, false, self.case_sensitive));
         }
 
@@ -165,7 +181,9 @@ fn are_anagrams_internal(word_a: &mut WordWithCharmap, word_b: &mut WordWithChar
 /// Similar to [are_anagrams] but checks that both words are real words
 ///
 /// This function will return false if either `word_a`, `word_b`, or both
-/// are not found in the specified `wordlist`.
+/// are not found in the specified `wordlist`. If `case_sensitive` is false, words are looked
+/// up in `wordlist` ignoring case (see [CaseFoldedWordlist](crate::wordlist::CaseFoldedWordlist)
+/// to make repeated lookups faster).
 ///
 /// `wordlist` must implement the [Wordlist] trait (for example, the
 /// [default wordlist](crate::default_wordlist::default_wordlist) if present)
@@ -192,14 +210,16 @@ fn are_anagrams_internal(word_a: &mut WordWithCharmap, word_b: &mut WordWithChar
 /// //non-anagram due to being identical
 /// assert!(!are_proper_anagrams("race", "race", &wordlist, CASE_SENSITIVE));
 /// ```
-pub fn are_proper_anagrams<'a>(
+pub fn are_proper_anagrams(
     word_a: &str,
     word_b: &str,
-    wordlist: &impl Wordlist<'a>,
+    wordlist: &(impl Wordlist + ?Sized),
     case_sensitive: bool
 ) -> bool {
     //return false if either word is not found in wordlist
-    if !wordlist.includes_word(word_a) || !wordlist.includes_word(word_b) {
+    if !wordlist.includes_word_with_case(word_a, case_sensitive)
+        || !wordlist.includes_word_with_case(word_b, case_sensitive)
+    {
         return false;
     }
 
@@ -213,7 +233,9 @@ pub fn are_proper_anagrams<'a>(
 pub struct AnagramsIter {
     chars: Vec<char>,
     stack_state: Vec<usize>,
-    i: usize
+    i: usize,
+    limits: SearchLimits,
+    truncation: Option<Truncation>
 }
 
 impl AnagramsIter {
@@ -224,9 +246,26 @@ impl AnagramsIter {
         Self {
             chars,
             stack_state,
-            i: 1
+            i: 1,
+            limits: SearchLimits::new(),
+            truncation: None
         }
     }
+
+    /// Stops the search early according to `limits`, returning the modified iterator
+    ///
+    /// See [SearchLimits] for details.
+    pub fn with_limits(mut self, limits: SearchLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Returns the reason this iterator stopped early, if it did
+    ///
+    /// Returns `None` while the search is still running or if it finished normally.
+    pub fn truncation(&self) -> Option<Truncation> {
+        self.truncation
+    }
 }
 
 impl Iterator for AnagramsIter {
@@ -237,7 +276,11 @@ impl Iterator for AnagramsIter {
     // https://en.wikipedia.org/wiki/Heap's_algorithm
     fn next(&mut self) -> Option<Self::Item> {
         let seq_len = self.chars.len();
-        if seq_len <= 1 {
+        if seq_len <= 1 || self.truncation.is_some() {
+            return None;
+        }
+        if let Some(truncation) = self.limits.check() {
+            self.truncation = Some(truncation);
             return None;
         }
         while self.i < seq_len {
@@ -276,7 +319,7 @@ impl Iterator for AnagramsIter {
 /// Factorials get up to extremely high output values for relatively low input values.
 /// Be mindful of this if you plan to fill a vector with standard anagrams:
 /// storing ***all*** standard anagrams of a word may require multiple gigabytes of memory.
-pub fn find_anagrams(word: &str) -> impl Iterator<Item = String> {
+pub fn find_anagrams(word: &str) -> AnagramsIter {
     AnagramsIter::new(word)
 }
 
@@ -289,7 +332,29 @@ where
 {
     word: WordWithCharmap<'b>,
     wordlist_iter: T,
-    case_sensitive: bool
+    case_sensitive: bool,
+    limits: SearchLimits,
+    truncation: Option<Truncation>
+}
+
+impl<'a, 'b, T> ProperAnagramsIter<'a, 'b, T>
+where
+    T: Iterator<Item = &'a str>
+{
+    /// Stops the search early according to `limits`, returning the modified iterator
+    ///
+    /// See [SearchLimits] for details.
+    pub fn with_limits(mut self, limits: SearchLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Returns the reason this iterator stopped early, if it did
+    ///
+    /// Returns `None` while the search is still running or if it finished normally.
+    pub fn truncation(&self) -> Option<Truncation> {
+        self.truncation
+    }
 }
 
 impl<'a, 'b, T> Iterator for ProperAnagramsIter<'a, 'b, T>
@@ -298,7 +363,14 @@ where
 {
     type Item = &'a str;
     fn next(&mut self) -> Option<Self::Item> {
+        if self.truncation.is_some() {
+            return None;
+        }
         for next_word in self.wordlist_iter.by_ref() {
+            if let Some(truncation) = self.limits.check() {
+                self.truncation = Some(truncation);
+                return None;
+            }
             let mut next_word_with_charmap = WordWithCharmap::new(next_word, self.case_sensitive);
             if are_anagrams_internal(&mut self.word, &mut next_word_with_charmap) {
                 return Some(next_word);
@@ -313,6 +385,21 @@ where
 /// Note that this method does not check if `word` is present in `wordlist`;
 /// this is the responsibility of the caller (if desired)
 ///
+/// To find the proper anagrams of many words, [find_proper_anagrams_batch] is much faster
+/// than calling this function for each word.
+///
+/// When `case_sensitive` is true and the wordlist
+/// [has a signature lookup](Wordlist::has_signature_lookup), only the words returned by
+/// [words_with_signature](Wordlist::words_with_signature) are checked, so searching an
+/// [IndexedWordList](crate::wordlist::IndexedWordList) doesn't look at the rest of the wordlist.
+/// Other wordlists are checked one word at a time, as the iterator is advanced.
+///
+/// With a [WeightedWordlist](crate::wordlist::WeightedWordlist) (such as a
+/// [FrequencyWordList](crate::wordlist::FrequencyWordList)), the results can be ordered from most
+/// to least frequent with [sort_by_weight](crate::wordlist::WeightedWordlist::sort_by_weight),
+/// and rare words can be left out by searching the view returned by
+/// [with_min_weight](crate::wordlist::WeightedWordlist::with_min_weight).
+///
 ///# Examples
 /// ```
 /// use anagrambot::anagram::find_proper_anagrams;
@@ -341,12 +428,24 @@ pub fn find_proper_anagrams<'a, 'b, T>(
     case_sensitive: bool
 ) -> ProperAnagramsIter<'a, 'b, impl Iterator<Item = &'a str>>
 where
-    T: Wordlist<'a>
+    T: Wordlist + ?Sized
 {
     let word_with_charmap = WordWithCharmap::new(word, case_sensitive);
+    // when searching case sensitively, only words with the same letters can be anagrams; they
+    // are only looked up all at once if that doesn't mean looking at every word before the
+    // first result (and before any limit is checked)
+    let (signature_words, all_words) = if case_sensitive && wordlist.has_signature_lookup() {
+        (wordlist.words_with_signature(word), None)
+    } else {
+        (Vec::new(), Some(wordlist.iter()))
+    };
     ProperAnagramsIter {
         word: word_with_charmap,
-        wordlist_iter: wordlist.iter(),
-        case_sensitive
+        wordlist_iter: signature_words
+            .into_iter()
+            .chain(all_words.into_iter().flatten()),
+        case_sensitive,
+        limits: SearchLimits::new(),
+        truncation: None
     }
 }
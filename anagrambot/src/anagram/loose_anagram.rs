@@ -3,8 +3,36 @@
 //! A loose anagram of a word is a proper anagram that can have a different
 //! number of spaces (i.e. a different number of words).
 
-use super::{Charmap, Wordlist, get_charcount_map};
-use std::collections::HashMap;
+use super::{get_charcount_map, Charmap, SearchLimits, Truncation, Wordlist};
+use std::{collections::HashSet, sync::Arc};
+
+mod candidate_cache;
+use candidate_cache::CandidateCaches;
+pub use candidate_cache::{CacheLimit, CacheStats};
+
+mod best;
+pub use best::{
+    find_best_loose_anagrams, AnagramScorer, BestLooseAnagramsIterator, FewerWords,
+    FrequencyScorer, LongerWords, ScoredLooseAnagram
+};
+
+mod template;
+use template::TemplateFilter;
+pub use template::{PhraseTemplate, TemplateError};
+
+mod count;
+pub use count::{count_loose_anagrams, CountMode, LooseAnagramCount};
+
+mod checkpoint;
+pub use checkpoint::{
+    resume_loose_anagrams, resume_loose_anagrams_with_templates, CheckpointError,
+    LooseAnagramsCheckpoint
+};
+
+#[cfg(feature = "parallel")]
+mod parallel;
+#[cfg(feature = "parallel")]
+pub use parallel::{par_find_loose_anagrams, ParLooseAnagrams, TruncationReport};
 
 /// Similar to [are_anagrams](super::are_anagrams) but checks for loose anagrams 
 /// 
@@ -18,7 +46,7 @@ use std::collections::HashMap;
 /// - "racecar" and "arc care" are loose anagrams
 /// - "racecar" and "race car" are loose anagrams
 /// - "racecar" and "rac e car" are not strictly loose anagrams because "rac" and "e" aren't
-///    words, but this function will return `true` for "racecar" and "rac e car" despite this
+///   words, but this function will return `true` for "racecar" and "rac e car" despite this
 /// 
 /// If you do want to check that both words are real words, [are_loose_anagrams_strict]
 /// does perform this check.
@@ -58,22 +86,25 @@ pub fn are_loose_anagrams(word_a: &str, word_b: &str, case_sensitive: bool) -> b
 /// This function checks both `word_a` and `word_b` for presence in `wordlist`.
 /// If either of them is not found within `wordlist`, this function will return false.
 /// 
+/// If `case_sensitive` is false, words are looked up in `wordlist` ignoring case.
+///
 /// If both `word_a` and `word_b` are present in `wordlist`, this function's return value
 /// will be identical to that of [are_loose_anagrams] for the given `word_a` and `word_b`.
-pub fn are_loose_anagrams_strict<'a>(
-     word_a: &str, 
-     word_b: &str,
-     wordlist: &impl Wordlist<'a>, 
-     case_sensitive: bool) -> bool
-{
-    if wordlist.includes_word(word_a) && wordlist.includes_word(word_b){
+pub fn are_loose_anagrams_strict(
+    word_a: &str,
+    word_b: &str,
+    wordlist: &(impl Wordlist + ?Sized),
+    case_sensitive: bool
+) -> bool {
+    if wordlist.includes_word_with_case(word_a, case_sensitive)
+        && wordlist.includes_word_with_case(word_b, case_sensitive)
+    {
         are_loose_anagrams(word_a, word_b, case_sensitive)
     } else {
         false
     }
 }
 
-
 /// Returns an Iterator over all loose anagrams of `target_word`
 /// 
 /// A loose anagram of a word is a proper anagram that can have a different
@@ -95,9 +126,27 @@ pub fn are_loose_anagrams_strict<'a>(
 /// For this reason, the [LooseAnagramsIterator] caches partial results to decrease time spent waiting
 /// on the next anagram to be generated. This caching behavior results in massive speed gains, but means 
 /// that [LooseAnagramsIterator] instances may take more memory than you might think, especially for larger words.
+/// If memory is constrained, use [LooseAnagramsIterator::with_cache_limit] to bound the size of the cache;
+/// entries evicted from the cache are recomputed when needed, trading speed for memory.
+///
+/// Searching for every loose anagram of a long word may take hours. To stop a search after
+/// a certain amount of time or from another thread, use [LooseAnagramsIterator::with_limits].
+/// Searches can also be saved with [LooseAnagramsIterator::checkpoint] and continued later
+/// with [resume_loose_anagrams].
+/// With the `parallel` feature enabled, `par_find_loose_anagrams` searches using multiple threads.
 /// 
 /// Loose anagrams are also significantly more numerous than proper anagrams. Be mindful of this if you plan to fill
 /// a vector with loose anagrams: storing ***all*** loose anagrams of a word may require multiple gigabytes of memory.
+/// [count_loose_anagrams] counts them without storing them, and [find_best_loose_anagrams] finds only
+/// the highest scoring ones (for example, those made of the most frequent words, using [FrequencyScorer]).
+/// To leave rare words out of the results entirely, search a view of a
+/// [WeightedWordlist](crate::wordlist::WeightedWordlist) created with
+/// [with_min_weight](crate::wordlist::WeightedWordlist::with_min_weight).
+///
+/// Every search starts by finding the words that fit into `target_word` with
+/// [words_fitting](Wordlist::words_fitting). For large wordlists, this is much faster with a
+/// [TrieWordList](crate::wordlist::TrieWordList), which skips every word starting with a
+/// prefix that doesn't fit.
 /// 
 ///# Examples
 /// ```
@@ -127,67 +176,17 @@ pub fn are_loose_anagrams_strict<'a>(
 /// assert_eq!(loose_anagrams_vec, 
 ///     vec!["acre car", "car acre", "car care", "car race", "care car", "race car"]);
 /// ```
-pub fn find_loose_anagrams<'a, T>(target_word: &str, 
-    wordlist: &'a T, 
+pub fn find_loose_anagrams<'a, T>(
+    target_word: &str,
+    wordlist: &'a T,
     min_word_length: usize,
-    case_sensitive: bool) 
--> LooseAnagramsIterator<'a> where T: Wordlist<'a>
+    case_sensitive: bool
+) -> LooseAnagramsIterator<'a>
+where
+    T: Wordlist + ?Sized
 {
-
-    let min_word_length = if min_word_length == 0 {1} else {min_word_length};
-
-    // get the charcount map of word (ignoring spaces)
-    let target_charmap = get_charcount_map(target_word, true, case_sensitive);
-
-    // find every word in the wordlist that can fit into the base word
-    // and store them in full_candidate_set
-    let full_candidate_set: HashMap<&str, Charmap> = wordlist.iter().filter_map(|word_b|{
-            if word_b.chars().count() >= min_word_length {
-                if let Some(charcount_map) = get_fitting_charmap(
-                    word_b, 
-                    &target_charmap, 
-                    true, 
-                    case_sensitive){
-                    //dont include word if it's the same word
-                    if target_word == word_b{
-                        None
-                    } else {
-                        Some((word_b, charcount_map))
-                    }
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        }
-    ).collect();
-
-    // hashmap containing the wordset that will fit into the specified charmap
-    let candidate_map: HashMap<Charmap, Vec<(&str, Charmap)>> = HashMap::with_capacity(full_candidate_set.len());
-
-    // vector containing the words to test fit into target word
-    // this is where created words will be stored before verification
-    // once verified, they are moved to result_vec
-    let words_to_try: Vec<(Vec<&str>, Charmap)> =
-    //tuple member 1 is the words that combine to make this word
-    //tuple member 2 is the charmap of this word
-
-    // initially fill words_to_try with the candidate set
-    full_candidate_set.iter().map(|item|{
-        (vec![*item.0], item.1.clone())
-    }).collect();
-
-    // create an owned string from the target word
-    let target_word = target_word.to_string();
-    // create a LooseAnagramsIterator from this data
-    LooseAnagramsIterator{
-        target_word,
-        target_charmap,
-        full_candidate_set,
-        candidate_map,
-        words_to_try
-    }
+    LooseAnagramsIterator::without_frontier(target_word, wordlist, min_word_length, case_sensitive)
+        .with_initial_frontier()
 }
 
 /// An iterator over all the loose anagrams of a word
@@ -200,86 +199,290 @@ pub fn find_loose_anagrams<'a, T>(target_word: &str,
 pub struct LooseAnagramsIterator<'a> {
     target_word: String,
     target_charmap: Charmap,
-    full_candidate_set: HashMap<&'a str, Charmap>,
-    candidate_map: HashMap<Charmap, Vec<(&'a str, Charmap)>>,
-    words_to_try: Vec<(Vec<&'a str>, Charmap)>
+    min_word_length: usize,
+    case_sensitive: bool,
+    // shared (along with the caches) by every part of a parallel search
+    candidates: Arc<[(&'a str, Charmap)]>,
+    // maps the charmap of a partial anagram to the candidates that still fit
+    candidate_cache: Arc<CandidateCaches>,
+    // vector containing the words to test fit into target word
+    // this is where created words will be stored before verification
+    //tuple member 1 is the indices (into candidates) of the words that combine to make this word
+    //tuple member 2 is the charmap of this word
+    words_to_try: Vec<(Vec<u32>, Charmap)>,
+    // number of loose anagrams returned so far
+    results_returned: u64,
+    limits: SearchLimits,
+    truncation: Option<Truncation>,
+    // phrase templates that results must match, if any
+    templates: Option<Arc<TemplateFilter>>
+}
+
+impl<'a> LooseAnagramsIterator<'a> {
+    /// Creates an iterator with its candidate words found, but with nothing left to try
+    ///
+    /// The candidates are found with [words_fitting](Wordlist::words_fitting), so wordlists
+    /// that can find them quickly (such as a [TrieWordList](crate::wordlist::TrieWordList))
+    /// speed up every loose anagram search.
+    fn without_frontier<T>(
+        target_word: &str,
+        wordlist: &'a T,
+        min_word_length: usize,
+        case_sensitive: bool
+    ) -> Self
+    where
+        T: Wordlist + ?Sized
+    {
+        let target_charmap = get_charcount_map(target_word, true, case_sensitive);
+        Self::from_words(
+            target_word,
+            wordlist.words_fitting(&target_charmap, case_sensitive),
+            min_word_length,
+            case_sensitive
+        )
+    }
+
+    /// Creates an iterator with no frontier whose candidates are the words in `words` that fit
+    ///
+    /// `words` must include every wordlist word that fits into `target_word`, in wordlist order,
+    /// but may include words that don't fit.
+    fn from_words(
+        target_word: &str,
+        words: impl IntoIterator<Item = &'a str>,
+        min_word_length: usize,
+        case_sensitive: bool
+    ) -> Self {
+        let min_word_length = if min_word_length == 0 {
+            1
+        } else {
+            min_word_length
+        };
+                        
+        // get the charcount map of word (ignoring spaces)
+        let target_charmap = get_charcount_map(target_word, true, case_sensitive);
+
+        // find every word in the wordlist that can fit into the base word
+        // and store them in candidates, in wordlist order and without duplicates
+        let mut seen_words: HashSet<&str> = HashSet::new();
+        let candidates: Arc<[(&str, Charmap)]> = words
+            .into_iter()
+            .filter_map(|word_b| {
+                //dont include word if it's the same word
+                if word_b.chars().count() < min_word_length || target_word == word_b {
+                    return None;
+                }
+                let charcount_map =
+                    get_fitting_charmap(word_b, &target_charmap, true, case_sensitive)?;
+                if seen_words.insert(word_b) {
+                    Some((word_b, charcount_map))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        LooseAnagramsIterator {
+            target_word: target_word.to_string(),
+            target_charmap,
+            min_word_length,
+            case_sensitive,
+            candidates,
+            candidate_cache: Arc::new(CandidateCaches::new(CacheLimit::Unbounded)),
+            words_to_try: Vec::new(),
+            results_returned: 0,
+            limits: SearchLimits::new(),
+            truncation: None,
+            templates: None
+        }
+    }
+
+    /// Fills words_to_try with the candidate set
+    /// (in reverse, so that the first candidate is tried first)
+    fn with_initial_frontier(mut self) -> Self {
+        self.words_to_try = self
+            .candidates
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(index, (_, charmap))| (vec![index as u32], charmap.clone()))
+            .collect();
+        self
+    }
+
+    /// Limits the amount of memory used to cache partial results, returning the modified iterator
+    ///
+    /// See [CacheLimit] for the available limits. By default, the cache is unbounded.
+    ///
+    ///# Examples
+    /// ```
+    /// use anagrambot::anagram::{find_loose_anagrams, CacheLimit};
+    /// use anagrambot::wordlist::BorrowedWordList;
+    ///
+    /// const TEST_WORD_SET: [&str; 5] = ["race", "car", "care", "racecar", "acre"];
+    /// let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+    ///
+    /// let mut loose_anagrams_iter = find_loose_anagrams("racecar", &wordlist, 3, true)
+    ///     .with_cache_limit(CacheLimit::Entries(1));
+    ///
+    /// assert_eq!(loose_anagrams_iter.by_ref().count(), 6);
+    /// assert!(loose_anagrams_iter.cache_stats().entries <= 1);
+    /// ```
+    pub fn with_cache_limit(mut self, limit: CacheLimit) -> Self {
+        self.set_cache_limit(limit);
+        self
+    }
+
+    /// Changes the limit on the amount of memory used to cache partial results
+    ///
+    /// If the cache currently exceeds the new limit, entries are evicted immediately.
+    pub fn set_cache_limit(&mut self, limit: CacheLimit) {
+        self.candidate_cache.set_limit(limit);
+    }
+
+    /// Stops the search early according to `limits`, returning the modified iterator
+    ///
+    /// Limits are checked each time a partial anagram is expanded. See [SearchLimits] for details.
+    ///
+    ///# Examples
+    /// ```
+    /// use anagrambot::anagram::{find_loose_anagrams, SearchLimits, Truncation};
+    /// use anagrambot::wordlist::BorrowedWordList;
+    /// use std::time::Instant;
+    ///
+    /// const TEST_WORD_SET: [&str; 5] = ["race", "car", "care", "racecar", "acre"];
+    /// let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+    ///
+    /// // a deadline that has already passed stops the search right away
+    /// let limits = SearchLimits::new().with_deadline(Instant::now());
+    /// let mut loose_anagrams_iter = find_loose_anagrams("racecar", &wordlist, 3, true)
+    ///     .with_limits(limits);
+    ///
+    /// assert_eq!(loose_anagrams_iter.next(), None);
+    /// assert_eq!(loose_anagrams_iter.truncation(), Some(Truncation::TimedOut));
+    /// ```
+    pub fn with_limits(mut self, limits: SearchLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Returns the reason this iterator stopped early, if it did
+    ///
+    /// Returns `None` while the search is still running or if it finished normally.
+    pub fn truncation(&self) -> Option<Truncation> {
+        self.truncation
+    }
+
+    /// Returns the current size of the partial result cache and statistics on its use
+    pub fn cache_stats(&self) -> CacheStats {
+        self.candidate_cache.stats()
+    }
+
+    /// Returns the indices of all candidates that fit into the letters left over
+    /// after the words in `word_vec` (with combined charmap `word_charmap`) are used
+    ///
+    /// Results are cached; on a cache miss they are derived from the candidates
+    /// allowed for the parent (i.e. `word_vec` without its last word), or from the
+    /// full candidate list if the parent isn't cached either.
+    fn allowed_words(&self, word_vec: &[u32], word_charmap: &Charmap) -> Arc<[u32]> {
+        // the cache is only used by this thread, so it can stay locked
+        let mut cache = self.candidate_cache.lock();
+        if let Some(allowed_words) = cache.get(word_charmap) {
+            return allowed_words;
+        }
+
+        // this word hasn't had allowed words generated yet (or they were evicted)
+        // create allowed words as a subset of parent's allowed words
+        let last_word = *word_vec.last().unwrap() as usize;
+        let last_word_charmap = &self.candidates[last_word].1;
+        let parent_charmap = unsafe { sub_charmaps(word_charmap, last_word_charmap) };
+
+        let cached_parent_words = cache.get(&parent_charmap);
+        let parent_words = match cached_parent_words {
+            Some(parent_words) => parent_words,
+            None => {
+                let reduced_map =
+                    // it is safe to use sub_charmaps here because the word charmap will always fit
+                    // within the target charmap; if it didn't, it wouldn't be in words_to_try
+                    unsafe { sub_charmaps(&self.target_charmap, &parent_charmap) };
+                let allowed_words: Arc<[u32]> = self
+                    .candidates
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (_, charmap))| word_fits(&reduced_map, charmap))
+                    .map(|(index, _)| index as u32)
+                    .collect();
+                cache.insert(parent_charmap, Arc::clone(&allowed_words));
+                allowed_words
+            }
+        };
+                        
+        //find reduced map; the map that words must fit into to still fit into
+        //the target word after 'word' has been included
+        let reduced_map =
+            // it is safe to use sub_charmaps here because the word charmap will always fit
+            // within the target charmap; if it didn't, it wouldn't be in words_to_try
+            unsafe { sub_charmaps(&self.target_charmap, word_charmap) };
+
+        let allowed_words: Arc<[u32]> = parent_words
+            .iter()
+            .copied()
+            .filter(|index| word_fits(&reduced_map, &self.candidates[*index as usize].1))
+            .collect();
+        //store allowed words in the cache
+        cache.insert(word_charmap.clone(), Arc::clone(&allowed_words));
+        allowed_words
+    }
 }
 
 impl<'a> Iterator for LooseAnagramsIterator<'a> {
     type Item = String;
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some((word_vec, word_charmap)) 
-        = self.words_to_try.pop() {
-            if word_charmap == self.target_charmap{
-                let loose_anagram = word_vec.join(" ");
+        if self.truncation.is_some() {
+            return None;
+        }
+        while let Some((word_vec, word_charmap)) = self.words_to_try.pop() {
+            if let Some(truncation) = self.limits.check() {
+                // put the partial anagram back so that no results are lost
+                self.words_to_try.push((word_vec, word_charmap));
+                self.truncation = Some(truncation);
+                return None;
+            }
+            if word_charmap == self.target_charmap {
+                let loose_anagram = word_vec
+                    .iter()
+                    .map(|index| self.candidates[*index as usize].0)
+                    .collect::<Vec<&str>>()
+                    .join(" ");
                 // only return if this generated anagram doesn't match
                 // the target exactly (this can happen with multi-word targets)
-                if loose_anagram != self.target_word{
+                let matches_template = match &self.templates {
+                    Some(templates) => templates.allows_phrase(&word_vec),
+                    None => true
+                };
+                if loose_anagram != self.target_word && matches_template {
+                    self.results_returned += 1;
                     return Some(loose_anagram);
                 }
             } else {
-                let allowed_words = match self.candidate_map.get(&word_charmap){
-                    Some(map) => map,
-                    None => {
-                        // this word hasn't had allowed words generated yet
-                        // create allowed words as a subset of parent's allowed words
-
-                        let last_word = word_vec.last().unwrap();
-                        let last_word_charmap = self.full_candidate_set.get(last_word).unwrap();
-                        let parent_charmap = 
-                            unsafe{
-                                sub_charmaps(&word_charmap, last_word_charmap)
-                            };
-                        
-                        let parent_words = 
-                            match self.candidate_map.get(&parent_charmap){
-                                Some(val) => val,
-                                None => {
-                                    let reduced_map =
-                                    // it is safe to use sub_charmaps here because the word charmap will always fit
-                                    // within the target charmap; if it didn't, it wouldn't be in words_to_try
-                                    unsafe {sub_charmaps(&self.target_charmap, &parent_charmap)};
-
-
-                                    let allowed_words = self.full_candidate_set.iter()
-                                                .filter_map(|item|{
-                                                    if word_fits(&reduced_map, item.1){
-                                                        Some((*item.0, item.1.clone()))
-                                                    } else {
-                                                        None
-                                                    }
-                                                }).collect();
-                                    self.candidate_map.entry(parent_charmap).or_insert(allowed_words)
-                                }
-                            };
-                        
-                        //find reduced map; the map that words must fit into to still fit into
-                        //the target word after 'word' has been included
-                        let reduced_map = 
-                        // it is safe to use sub_charmaps here because the word charmap will always fit
-                        // within the target charmap; if it didn't, it wouldn't be in words_to_try
-                        unsafe {sub_charmaps(&self.target_charmap, &word_charmap)};
-
-                        let allowed_words = parent_words.iter()
-                        .filter_map(|item|{
-                            if word_fits(&reduced_map, &item.1){
-                                Some(item.clone())
-                            } else {
-                                None
-                            }
-                        }).collect();
-                        //store allowed words in candidate_map and return ref to newly stored words
-                        self.candidate_map.entry(word_charmap.clone()).or_insert(allowed_words)
+                if let Some(templates) = &self.templates {
+                    if !templates.allows_more_words(&word_vec) {
+                        continue;
                     }
-                };
+                }
+                let allowed_words = self.allowed_words(&word_vec, &word_charmap);
 
-                for allowed_word in allowed_words.iter() 
-                {
-                    let (subword, submap) = allowed_word;
+                // push in reverse so that allowed words are tried in candidate order
+                for subword in allowed_words.iter().rev() {
+                    let submap = &self.candidates[*subword as usize].1;
                     
-                    let mut subword_vec:Vec<&str> = Vec::with_capacity(word_vec.len() + 1);
+                    let mut subword_vec: Vec<u32> = Vec::with_capacity(word_vec.len() + 1);
                     subword_vec.clone_from(&word_vec);
-                    subword_vec.push(subword);
+                    subword_vec.push(*subword);
+                    if let Some(templates) = &self.templates {
+                        if !templates.allows_prefix(&subword_vec) {
+                            continue;
+                        }
+                    }
 
                     let summed_map = 
                         add_charmaps(&word_charmap, submap);
@@ -320,7 +523,6 @@ fn word_fits(word_map_a: &Charmap, word_map_b: &Charmap) -> bool
                     return false;
                 }
             }
-
         }
     }
     // if all keys in word b exist in word a,
@@ -359,7 +561,9 @@ unsafe fn sub_charmaps(big_charmap: &Charmap, small_charmap: &Charmap) -> Charma
     let mut new_charmap = Charmap::new();
     for (key, bigvalue) in big_charmap{
         match small_charmap.get(key){
-            None => {new_charmap.insert(*key, *bigvalue);},
+            None => {
+                new_charmap.insert(*key, *bigvalue);
+            }
             Some(smallvalue) => {
                 //using word_fits earlier already ensured smallvalue is
                 //less than or equal to bigvalue, so if they are not equal
@@ -381,20 +585,29 @@ unsafe fn sub_charmaps(big_charmap: &Charmap, small_charmap: &Charmap) -> Charma
 /// 
 /// If you intend to immediately use a generated Charmap with [word_fits],
 /// this is a more efficient way of doing both at once.
-fn get_fitting_charmap(word: &str, bigger_charmap: &Charmap,
-    ignore_spaces: bool, case_sensitive: bool) -> Option<Charmap>
-{
+fn get_fitting_charmap(
+    word: &str,
+    bigger_charmap: &Charmap,
+    ignore_spaces: bool,
+    case_sensitive: bool
+) -> Option<Charmap> {
     let mut lettercount_map = Charmap::new();
 
     let mut insert_closure = |letter|{
         // if bigger charmap doesn't contain this letter, fail right away
-        if bigger_charmap.get(&letter) == None {
+        if !bigger_charmap.contains_key(&letter) {
             return Err(());
         }
 
         let count = match lettercount_map.get_mut(&letter) {
-            None => {lettercount_map.insert(letter, 1); 1},
-            Some(count) => {*count+=1; *count}
+            None => {
+                lettercount_map.insert(letter, 1);
+                1
+            }
+            Some(count) => {
+                *count += 1;
+                *count
+            }
         };
         
         //check count against bigger charmap
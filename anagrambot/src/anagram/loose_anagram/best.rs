@@ -0,0 +1,585 @@
+//! Finding the best loose anagrams of a word
+//!
+//! A long word can have millions of loose anagrams, most of which are uninteresting.
+//! [find_best_loose_anagrams] ranks loose anagrams using an [AnagramScorer] and returns them
+//! best first, without finding the loose anagrams that cannot be among the best.
+
+use super::{
+    sub_charmaps, word_fits, Charmap, LooseAnagramsIterator, SearchLimits, Truncation, Wordlist
+};
+
+use crate::wordlist::{Blocklist, WeightedWordlist};
+
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    sync::Arc
+};
+
+/// Scores loose anagrams for [find_best_loose_anagrams]
+///
+/// The score of a loose anagram is the sum, over each of its words, of
+/// [word_score](AnagramScorer::word_score) plus [per_word_adjustment](AnagramScorer::per_word_adjustment).
+/// Higher scores are better. Scores should be finite.
+///
+/// Any `Fn(&str) -> f64` closure can be used as a scorer; it is used as the
+/// [word_score](AnagramScorer::word_score) function.
+pub trait AnagramScorer {
+    /// Returns the score of a single word in a loose anagram
+    fn word_score(&self, word: &str) -> f64;
+
+    /// Returns the score added for every word in a loose anagram
+    ///
+    /// A negative adjustment favours loose anagrams with fewer words. Defaults to 0.
+    fn per_word_adjustment(&self) -> f64 {
+        0.0
+    }
+}
+
+impl<F> AnagramScorer for F
+where
+    F: Fn(&str) -> f64
+{
+    fn word_score(&self, word: &str) -> f64 {
+        self(word)
+    }
+}
+
+/// Scores loose anagrams with fewer words higher
+///
+/// The score of a loose anagram is the negative of its number of words.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FewerWords;
+
+impl AnagramScorer for FewerWords {
+    fn word_score(&self, _word: &str) -> f64 {
+        0.0
+    }
+
+    fn per_word_adjustment(&self) -> f64 {
+        -1.0
+    }
+}
+
+/// Scores loose anagrams made of longer words higher
+///
+/// The score of a loose anagram is the sum of the squares of the lengths of its words
+/// (ignoring spaces), so one long word scores higher than two short words with the same letters.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LongerWords;
+
+impl AnagramScorer for LongerWords {
+    fn word_score(&self, word: &str) -> f64 {
+        let length = word.chars().filter(|letter| *letter != ' ').count() as f64;
+        length * length
+    }
+}
+
+/// Scores loose anagrams made of more frequent words higher
+///
+/// Each word scores the logarithm of its share of the total weight of a [WeightedWordlist]
+/// (with 1 added to every weight, so that words with no weight still get a finite score).
+/// Every word scores less than zero, so loose anagrams with fewer words are also favoured.
+///
+///# Examples
+/// ```
+/// use anagrambot::anagram::{find_best_loose_anagrams, FrequencyScorer};
+/// use anagrambot::wordlist::FrequencyWordList;
+///
+/// let wordlist: FrequencyWordList =
+///     [("race", 300), ("car", 900), ("care", 500), ("acre", 10), ("arc", 20)]
+///         .into_iter()
+///         .map(|(word, count)| (String::from(word), count))
+///         .collect();
+///
+/// let mut best = find_best_loose_anagrams("racecar", &wordlist, 1, true,
+///     FrequencyScorer::new(&wordlist), 2)
+///     .map(|(anagram, _)| anagram);
+/// assert_eq!(best.next(), Some(String::from("car care")));
+/// assert_eq!(best.next(), Some(String::from("race car")));
+/// ```
+pub struct FrequencyScorer<'w, W> {
+    wordlist: &'w W,
+    log_total_weight: f64
+}
+
+impl<'w, W> FrequencyScorer<'w, W>
+where
+    W: WeightedWordlist
+{
+    /// Creates a scorer using the weights in `wordlist`
+    pub fn new(wordlist: &'w W) -> Self {
+        Self {
+            wordlist,
+            log_total_weight: (wordlist.total_weight() as f64 + 1.0).ln()
+        }
+    }
+}
+
+impl<'w, W> AnagramScorer for FrequencyScorer<'w, W>
+where
+    W: WeightedWordlist
+{
+    fn word_score(&self, word: &str) -> f64 {
+        let weight = self.wordlist.weight(word).unwrap_or(0);
+        (weight as f64 + 1.0).ln() - self.log_total_weight
+    }
+}
+
+/// A loose anagram and its score, as returned by [BestLooseAnagramsIterator]
+pub type ScoredLooseAnagram = (String, f64);
+
+/// A partial (or complete) loose anagram waiting to be expanded
+struct Node {
+    // the highest score any loose anagram starting with these words could have
+    bound: f64,
+    // the order nodes were created in, used to break ties between equal bounds
+    sequence: u64,
+    score: f64,
+    // indices (into candidates) of the words used so far, in candidate order
+    words: Vec<u32>,
+    // the letters left to use
+    remaining: Charmap,
+    remaining_letters: u32,
+    // the words allowed for this node's parent; this node may use the words starting at
+    // position first_allowed that fit into its remaining letters
+    parent_allowed: Arc<[u32]>,
+    first_allowed: usize
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Node {}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // higher bounds first, then older nodes first
+        self.bound
+            .total_cmp(&other.bound)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+/// A score, ordered so that it can be kept in a [BinaryHeap]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Score(f64);
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// An iterator over the best loose anagrams of a word, best first
+///
+/// The return value of [find_best_loose_anagrams]
+///
+///# Technical Notes
+///
+/// See the Technical Notes section of [find_best_loose_anagrams]
+pub struct BestLooseAnagramsIterator<'a> {
+    target_word: String,
+    candidates: Arc<[(&'a str, Charmap)]>,
+    // score of each candidate, including the per word adjustment
+    word_scores: Vec<f64>,
+    // number of letters in each candidate
+    letter_counts: Vec<u32>,
+    // the partial loose anagrams to expand, highest bound first
+    frontier: BinaryHeap<Node>,
+    // the scores of the best complete loose anagrams found so far (at most max_results of them)
+    best_scores: BinaryHeap<Reverse<Score>>,
+    next_sequence: u64,
+    max_results: usize,
+    results_returned: usize,
+    limits: SearchLimits,
+    truncation: Option<Truncation>,
+    blocklist: Option<&'a Blocklist>
+}
+
+impl<'a> BestLooseAnagramsIterator<'a> {
+    /// Leaves out the loose anagrams that `blocklist` blocks, returning the modified iterator
+    ///
+    /// Blocked loose anagrams don't take up any of the `max_results` places, so up to
+    /// `max_results` unblocked loose anagrams are still returned. See
+    /// [Blocklist::blocks_phrase] for which loose anagrams are blocked.
+    pub fn without_blocked(mut self, blocklist: &'a Blocklist) -> Self {
+        self.blocklist = Some(blocklist);
+        self
+    }
+
+    /// Stops the search early according to `limits`, returning the modified iterator
+    ///
+    /// Limits are checked each time a partial anagram is expanded. See [SearchLimits] for details.
+    pub fn with_limits(mut self, limits: SearchLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Returns the reason this iterator stopped early, if it did
+    ///
+    /// Returns `None` while the search is still running or if it finished normally.
+    pub fn truncation(&self) -> Option<Truncation> {
+        self.truncation
+    }
+
+    /// Returns the lowest score a loose anagram can have and still be returned,
+    /// or `None` if fewer than `max_results` loose anagrams have been found
+    fn score_threshold(&self) -> Option<f64> {
+        if self.best_scores.len() < self.max_results {
+            return None;
+        }
+        self.best_scores.peek().map(|Reverse(Score(score))| *score)
+    }
+
+    /// Adds a node to the frontier, unless it cannot beat the loose anagrams already found
+    fn push(&mut self, mut node: Node) {
+        if let Some(threshold) = self.score_threshold() {
+            // allow for rounding error in the bound
+            if node.bound < threshold - BOUND_TOLERANCE * (1.0 + threshold.abs()) {
+                return;
+            }
+        }
+        if node.remaining_letters == 0 {
+            self.best_scores.push(Reverse(Score(node.score)));
+            if self.best_scores.len() > self.max_results {
+                self.best_scores.pop();
+            }
+        }
+        node.sequence = self.next_sequence;
+        self.next_sequence += 1;
+        self.frontier.push(node);
+    }
+
+    /// Pushes every node that can be made by adding one more word to `node`
+    fn expand(&mut self, node: Node) {
+        let allowed: Arc<[u32]> = node.parent_allowed[node.first_allowed..]
+            .iter()
+            .copied()
+            .filter(|index| word_fits(&node.remaining, &self.candidates[*index as usize].1))
+            .collect();
+
+        // the best score per letter of any word at or after each position in allowed;
+        // the letters left after adding a word can score no more than this per letter
+        let mut best_density = vec![f64::NEG_INFINITY; allowed.len()];
+        let mut max_density = f64::NEG_INFINITY;
+        for (position, index) in allowed.iter().enumerate().rev() {
+            let index = *index as usize;
+            let density = self.word_scores[index] / self.letter_counts[index] as f64;
+            max_density = max_density.max(density);
+            best_density[position] = max_density;
+        }
+
+        for (position, index) in allowed.iter().copied().enumerate() {
+            let word_charmap = &self.candidates[index as usize].1;
+            let remaining_letters = node.remaining_letters - self.letter_counts[index as usize];
+            let score = node.score + self.word_scores[index as usize];
+            let bound = if remaining_letters == 0 {
+                score
+            } else {
+                score + remaining_letters as f64 * best_density[position]
+            };
+
+            let mut words = node.words.clone();
+            words.push(index);
+            if remaining_letters == 0
+                && (!self.set_arrangement(&mut words) || self.is_blocked(&words))
+            {
+                continue;
+            }
+
+            self.push(Node {
+                bound,
+                sequence: 0,
+                score,
+                words,
+                // it is safe to use sub_charmaps here because every allowed word fits
+                remaining: unsafe { sub_charmaps(&node.remaining, word_charmap) },
+                remaining_letters,
+                parent_allowed: Arc::clone(&allowed),
+                first_allowed: position
+            });
+        }
+    }
+
+    /// Arranges the words of a complete loose anagram so that it isn't identical to the target
+    /// word (which can happen with multi-word targets)
+    ///
+    /// returns false if every arrangement of the words is identical to the target word
+    fn set_arrangement(&self, words: &mut [u32]) -> bool {
+        if self.join_words(words) != self.target_word {
+            return true;
+        }
+        // rotating the words gives a different arrangement unless every word is the same
+        words.rotate_left(1);
+        self.join_words(words) != self.target_word
+    }
+
+    /// Returns true if the loose anagram made of `words` is blocked
+    fn is_blocked(&self, words: &[u32]) -> bool {
+        self.blocklist
+            .is_some_and(|blocklist| blocklist.blocks_phrase(&self.join_words(words)))
+    }
+
+    fn join_words(&self, words: &[u32]) -> String {
+        words
+            .iter()
+            .map(|index| self.candidates[*index as usize].0)
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+}
+
+/// relative tolerance used when comparing bounds against scores
+const BOUND_TOLERANCE: f64 = 1e-9;
+
+impl<'a> Iterator for BestLooseAnagramsIterator<'a> {
+    type Item = ScoredLooseAnagram;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.truncation.is_some() || self.results_returned >= self.max_results {
+            return None;
+        }
+        while let Some(node) = self.frontier.pop() {
+            if node.remaining_letters == 0 {
+                // nodes are expanded highest bound first, and the bound of a complete loose
+                // anagram is its score, so no loose anagram found later can score higher
+                self.results_returned += 1;
+                return Some((self.join_words(&node.words), node.score));
+            }
+            if let Some(truncation) = self.limits.check() {
+                // put the partial anagram back so that no results are lost
+                self.frontier.push(node);
+                self.truncation = Some(truncation);
+                return None;
+            }
+            self.expand(node);
+        }
+        None
+    }
+}
+
+/// Returns an iterator over the `max_results` highest scoring loose anagrams of `target_word`
+///
+/// Loose anagrams are scored by `scorer` (see [AnagramScorer]) and returned best first,
+/// along with their scores. Loose anagrams with equal scores are returned in an unspecified
+/// (but consistent) order.
+///
+/// Unlike [find_loose_anagrams](super::find_loose_anagrams), only one arrangement of each set
+/// of words is returned: since the score of a loose anagram doesn't depend on the order of its
+/// words, other arrangements would only take up places in the results.
+///
+///# Examples
+/// ```
+/// use anagrambot::anagram::{find_best_loose_anagrams, FewerWords, LongerWords};
+/// use anagrambot::wordlist::BorrowedWordList;
+///
+/// const CASE_SENSITIVE: bool = true;
+/// const MIN_WORD_LENGTH: usize = 1;
+///
+/// const TEST_WORD_SET: [&str; 9] = ["race", "car", "care", "racecar", "acre", "a", "r", "c", "e"];
+/// let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+///
+/// // "racecar" has several two word loose anagrams, but none with one word
+/// let best: Vec<(String, f64)> = find_best_loose_anagrams(
+///     "racecar", &wordlist, MIN_WORD_LENGTH, CASE_SENSITIVE, FewerWords, 3).collect();
+/// assert_eq!(best.len(), 3);
+/// assert!(best.iter().all(|(_, score)| *score == -2.0));
+///
+/// let (_, score) = find_best_loose_anagrams(
+///     "racecar", &wordlist, MIN_WORD_LENGTH, CASE_SENSITIVE, LongerWords, 1).next().unwrap();
+/// assert_eq!(score, 25.0);
+///
+/// // any Fn(&str) -> f64 can be used to score words
+/// let single_letters = |word: &str| if word.len() == 1 { 1.0 } else { 0.0 };
+/// let best: Vec<(String, f64)> = find_best_loose_anagrams(
+///     "racecar", &wordlist, MIN_WORD_LENGTH, CASE_SENSITIVE, single_letters, 1).collect();
+/// assert_eq!(best, vec![(String::from("a a r r c c e"), 7.0)]);
+/// ```
+///
+///# Technical Notes
+///
+/// The search keeps partial loose anagrams in a priority queue, ordered by the highest score
+/// any loose anagram starting with them could have. This bound is the score of the words used
+/// so far plus, for each letter left over, the best score per letter of any word that fits.
+/// Because the partial anagram with the highest bound is always expanded first, loose anagrams
+/// are found in order of score, and the search stops as soon as `max_results` have been
+/// returned. Once `max_results` loose anagrams have been found, partial anagrams that cannot
+/// beat the worst of them are discarded instead of being queued.
+///
+/// Scorers that give every word a similar score per letter make the bound less useful, and
+/// the search may have to queue a large number of partial anagrams.
+pub fn find_best_loose_anagrams<'a, T, S>(
+    target_word: &str,
+    wordlist: &'a T,
+    min_word_length: usize,
+    case_sensitive: bool,
+    scorer: S,
+    max_results: usize
+) -> BestLooseAnagramsIterator<'a>
+where
+    T: Wordlist + ?Sized,
+    S: AnagramScorer
+{
+    let search = LooseAnagramsIterator::without_frontier(
+        target_word,
+        wordlist,
+        min_word_length,
+        case_sensitive
+    );
+    let word_scores = search
+        .candidates
+        .iter()
+        .map(|(word, _)| scorer.word_score(word) + scorer.per_word_adjustment())
+        .collect();
+    let letter_counts = search
+        .candidates
+        .iter()
+        .map(|(_, charmap)| charmap.values().sum())
+        .collect();
+
+    let mut best_iter = BestLooseAnagramsIterator {
+        target_word: search.target_word,
+        candidates: search.candidates,
+        word_scores,
+        letter_counts,
+        frontier: BinaryHeap::new(),
+        best_scores: BinaryHeap::new(),
+        next_sequence: 0,
+        max_results,
+        results_returned: 0,
+        limits: SearchLimits::new(),
+        truncation: None,
+        blocklist: None
+    };
+
+    let target_letters = search.target_charmap.values().sum();
+    // an empty target has no loose anagrams
+    if target_letters > 0 {
+        let all_candidates: Arc<[u32]> = (0..best_iter.candidates.len() as u32).collect();
+        best_iter.push(Node {
+            bound: f64::INFINITY,
+            sequence: 0,
+            score: 0.0,
+            words: Vec::new(),
+            remaining: search.target_charmap,
+            remaining_letters: target_letters,
+            parent_allowed: all_candidates,
+            first_allowed: 0
+        });
+    }
+    best_iter
+}
+
+#[cfg(test)]
+mod best_tests {
+    use super::{find_best_loose_anagrams, AnagramScorer, FewerWords, LongerWords};
+    use crate::{
+        anagram::{find_loose_anagrams, SearchLimits, Truncation},
+        wordlist::{Blocklist, BorrowedWordList}
+    };
+    use std::{collections::HashMap, time::Instant};
+
+    const TEST_WORD_SET: [&str; 12] = [
+        "race", "car", "care", "racecar", "acre", "arc", "a", "err", "cream", "ace", "aa", "rc"
+    ];
+    const TARGETS: [&str; 4] = ["racecar", "race car", "arc cream", "aa aa"];
+
+    /// Returns the scores of every distinct set of words found by find_loose_anagrams, best first
+    fn expected_scores(
+        target: &str,
+        wordlist: &BorrowedWordList,
+        scorer: &impl AnagramScorer
+    ) -> Vec<f64> {
+        let mut word_sets: HashMap<Vec<String>, f64> = HashMap::new();
+        for anagram in find_loose_anagrams(target, wordlist, 1, true) {
+            let mut words: Vec<String> = anagram.split(' ').map(String::from).collect();
+            words.sort_unstable();
+            let score = words
+                .iter()
+                .map(|word| scorer.word_score(word) + scorer.per_word_adjustment())
+                .sum();
+            word_sets.insert(words, score);
+        }
+        let mut scores: Vec<f64> = word_sets.into_values().collect();
+        scores.sort_by(|a, b| b.total_cmp(a));
+        scores
+    }
+
+    fn check_scorer(scorer: impl AnagramScorer + Copy) {
+        let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+        for target in TARGETS {
+            let expected = expected_scores(target, &wordlist, &scorer);
+            for max_results in [1, 3, expected.len() + 1] {
+                let best: Vec<(String, f64)> =
+                    find_best_loose_anagrams(target, &wordlist, 1, true, scorer, max_results)
+                        .collect();
+                let scores: Vec<f64> = best.iter().map(|(_, score)| *score).collect();
+                assert_eq!(scores, expected[..expected.len().min(max_results)]);
+                for (anagram, _) in best {
+                    assert_ne!(anagram, target);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_scores_match_find_loose_anagrams() {
+        check_scorer(FewerWords);
+        check_scorer(LongerWords);
+        check_scorer(|word: &str| if word.contains('e') { 2.5 } else { -1.0 });
+    }
+
+    #[test]
+    fn test_blocked_anagrams_leave_room() {
+        let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+        let all: Vec<(String, f64)> =
+            find_best_loose_anagrams("racecar", &wordlist, 1, true, LongerWords, 100).collect();
+        let (first, _) = &all[0];
+
+        // blocks the best loose anagram, and every other one spelling the same pattern
+        let blocklist = Blocklist::new().with_pattern(&first.replace(' ', ""));
+        let unblocked: Vec<(String, f64)> = all
+            .iter()
+            .filter(|(anagram, _)| !blocklist.blocks_phrase(anagram))
+            .cloned()
+            .collect();
+        assert!(unblocked.len() >= 3 && unblocked.len() < all.len());
+
+        let best: Vec<(String, f64)> =
+            find_best_loose_anagrams("racecar", &wordlist, 1, true, LongerWords, 3)
+                .without_blocked(&blocklist)
+                .collect();
+        let scores: Vec<f64> = best.iter().map(|(_, score)| *score).collect();
+        let expected: Vec<f64> = unblocked[..3].iter().map(|(_, score)| *score).collect();
+        assert_eq!(scores, expected);
+        assert!(best
+            .iter()
+            .all(|(anagram, _)| !blocklist.blocks_phrase(anagram)));
+    }
+
+    #[test]
+    fn test_time_limit() {
+        let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+        let mut best = find_best_loose_anagrams("racecar", &wordlist, 1, true, FewerWords, 10)
+            .with_limits(SearchLimits::new().with_deadline(Instant::now()));
+        assert_eq!(best.next(), None);
+        assert_eq!(best.truncation(), Some(Truncation::TimedOut));
+    }
+}
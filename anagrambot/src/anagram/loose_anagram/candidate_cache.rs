@@ -0,0 +1,384 @@
+//! Bounded cache of candidate words used by [LooseAnagramsIterator](super::LooseAnagramsIterator)
+//!
+//! While searching for loose anagrams, the set of words that still fit into the
+//! remaining letters is computed for every partial anagram. Those sets are cached
+//! (keyed by the [Charmap] of the partial anagram) because the same letters are reached
+//! through many different word orderings. Without a limit this cache can grow very large,
+//! so the cache evicts its least recently used entries once a [CacheLimit] is exceeded.
+//! Evicted entries are simply recomputed if they are needed again.
+//!
+//! A parallel search keeps one cache per thread (see [CandidateCaches]), so that threads never
+//! wait for each other to use the cache.
+
+use crate::anagram::Charmap;
+
+use std::{
+    collections::HashMap,
+    mem,
+    sync::{Arc, Mutex, MutexGuard}
+};
+
+/// Marker for "no node" in the intrusive recency list
+const NIL: usize = usize::MAX;
+
+/// The maximum amount of data a [LooseAnagramsIterator](super::LooseAnagramsIterator) may cache
+///
+/// When the limit is exceeded, the least recently used cache entries are evicted.
+/// Lower limits use less memory but may cause more time to be spent recomputing
+/// evicted entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheLimit {
+    /// Never evict cache entries (the default)
+    #[default]
+    Unbounded,
+    /// Keep at most this many cache entries
+    Entries(usize),
+    /// Keep the estimated memory used by the cache at or below this many bytes
+    ///
+    /// The estimate accounts for the cached data itself but not for allocator overhead,
+    /// so actual memory usage may be somewhat higher.
+    Bytes(usize)
+}
+
+/// A snapshot of the size and effectiveness of a loose anagram cache
+///
+/// Returned by [LooseAnagramsIterator::cache_stats](super::LooseAnagramsIterator::cache_stats)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CacheStats {
+    /// The number of entries currently in the cache
+    pub entries: usize,
+    /// The estimated memory currently used by the cache, in bytes
+    pub estimated_bytes: usize,
+    /// The number of lookups that were answered by the cache
+    pub hits: u64,
+    /// The number of lookups that had to be computed (or recomputed)
+    pub misses: u64,
+    /// The number of entries evicted to stay within the [CacheLimit]
+    pub evictions: u64
+}
+
+impl CacheLimit {
+    /// Returns the limit of each of `parts` caches sharing this limit
+    ///
+    /// Rounds up, so that a small limit shared by many caches doesn't disable them.
+    fn split(self, parts: usize) -> Self {
+        match self {
+            CacheLimit::Unbounded => CacheLimit::Unbounded,
+            CacheLimit::Entries(max_entries) => CacheLimit::Entries(max_entries.div_ceil(parts)),
+            CacheLimit::Bytes(max_bytes) => CacheLimit::Bytes(max_bytes.div_ceil(parts))
+        }
+    }
+}
+
+impl CacheStats {
+    /// Adds the size and statistics of `other` to these
+    fn add(mut self, other: CacheStats) -> Self {
+        self.entries += other.entries;
+        self.estimated_bytes += other.estimated_bytes;
+        self.hits += other.hits;
+        self.misses += other.misses;
+        self.evictions += other.evictions;
+        self
+    }
+}
+
+/// The candidate caches of a search: one for a sequential search, or one per thread for a
+/// parallel search
+///
+/// Each thread only uses its own cache, so its lock is never contended. The [CacheLimit] is
+/// split evenly between the caches.
+pub(crate) struct CandidateCaches {
+    caches: Box<[Mutex<CandidateCache>]>
+}
+
+impl CandidateCaches {
+    pub fn new(limit: CacheLimit) -> Self {
+        Self::with_threads(limit, 1)
+    }
+
+    /// Creates `threads` empty caches, which share `limit`
+    pub fn with_threads(limit: CacheLimit, threads: usize) -> Self {
+        let threads = threads.max(1);
+        let caches = (0..threads)
+            .map(|_| Mutex::new(CandidateCache::new(limit.split(threads))))
+            .collect();
+        Self { caches }
+    }
+
+    pub fn set_limit(&self, limit: CacheLimit) {
+        let parts = self.caches.len();
+        for cache in self.caches.iter() {
+            lock(cache).set_limit(limit.split(parts));
+        }
+    }
+
+    /// Returns the combined size and statistics of every cache
+    pub fn stats(&self) -> CacheStats {
+        self.caches
+            .iter()
+            .map(|cache| lock(cache).stats())
+            .fold(CacheStats::default(), CacheStats::add)
+    }
+
+    /// Locks the cache of the current thread
+    pub fn lock(&self) -> MutexGuard<'_, CandidateCache> {
+        #[cfg(feature = "parallel")]
+        let index = rayon::current_thread_index().unwrap_or(0) % self.caches.len();
+        #[cfg(not(feature = "parallel"))]
+        let index = 0;
+        lock(&self.caches[index])
+    }
+}
+
+fn lock(cache: &Mutex<CandidateCache>) -> MutexGuard<'_, CandidateCache> {
+    // the cache is only modified through complete operations, so it remains
+    // consistent even if another thread panicked while holding the lock
+    cache
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// A single cache entry, linked into the recency list
+struct Node {
+    key: Charmap,
+    candidates: Arc<[u32]>,
+    bytes: usize,
+    prev: usize,
+    next: usize
+}
+
+/// Least recently used cache mapping charmaps to candidate indices
+///
+/// Candidate indices refer to positions in the candidate list of the owning iterator.
+pub(crate) struct CandidateCache {
+    limit: CacheLimit,
+    map: HashMap<Charmap, usize>,
+    nodes: Vec<Node>,
+    free_slots: Vec<usize>,
+    // most recently used node
+    head: usize,
+    // least recently used node
+    tail: usize,
+    stats: CacheStats
+}
+
+impl CandidateCache {
+    pub fn new(limit: CacheLimit) -> Self {
+        Self {
+            limit,
+            map: HashMap::new(),
+            nodes: Vec::new(),
+            free_slots: Vec::new(),
+            head: NIL,
+            tail: NIL,
+            stats: CacheStats::default()
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    pub fn set_limit(&mut self, limit: CacheLimit) {
+        self.limit = limit;
+        self.evict_to_limit();
+    }
+
+    /// Returns the cached candidates for `key`, marking the entry as recently used
+    pub fn get(&mut self, key: &Charmap) -> Option<Arc<[u32]>> {
+        match self.map.get(key) {
+            Some(&slot) => {
+                self.stats.hits += 1;
+                self.unlink(slot);
+                self.push_front(slot);
+                Some(Arc::clone(&self.nodes[slot].candidates))
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Inserts candidates for `key`, then evicts entries until the cache is within its limit
+    ///
+    /// Note that the newly inserted entry may itself be evicted if the limit is very low.
+    pub fn insert(&mut self, key: Charmap, candidates: Arc<[u32]>) {
+        if let Some(&slot) = self.map.get(&key) {
+            // replace the existing entry's data and mark it as recently used
+            let bytes = entry_bytes(&key, &candidates);
+            let node = &mut self.nodes[slot];
+            self.stats.estimated_bytes = self.stats.estimated_bytes - node.bytes + bytes;
+            node.bytes = bytes;
+            node.candidates = candidates;
+            self.unlink(slot);
+            self.push_front(slot);
+        } else {
+            let bytes = entry_bytes(&key, &candidates);
+            let node = Node {
+                key: key.clone(),
+                candidates,
+                bytes,
+                prev: NIL,
+                next: NIL
+            };
+            let slot = match self.free_slots.pop() {
+                Some(slot) => {
+                    self.nodes[slot] = node;
+                    slot
+                }
+                None => {
+                    self.nodes.push(node);
+                    self.nodes.len() - 1
+                }
+            };
+            self.map.insert(key, slot);
+            self.push_front(slot);
+            self.stats.entries += 1;
+            self.stats.estimated_bytes += bytes;
+        }
+        self.evict_to_limit();
+    }
+
+    fn over_limit(&self) -> bool {
+        match self.limit {
+            CacheLimit::Unbounded => false,
+            CacheLimit::Entries(max_entries) => self.stats.entries > max_entries,
+            CacheLimit::Bytes(max_bytes) => self.stats.estimated_bytes > max_bytes
+        }
+    }
+
+    fn evict_to_limit(&mut self) {
+        while self.over_limit() && self.tail != NIL {
+            let slot = self.tail;
+            self.unlink(slot);
+            let node = &mut self.nodes[slot];
+            // release the candidate data right away; the slot itself is reused later
+            let key = mem::take(&mut node.key);
+            node.candidates = Arc::new([]);
+            self.map.remove(&key);
+            self.free_slots.push(slot);
+
+            self.stats.entries -= 1;
+            self.stats.estimated_bytes -= node.bytes;
+            self.stats.evictions += 1;
+        }
+    }
+
+    fn unlink(&mut self, slot: usize) {
+        let (prev, next) = (self.nodes[slot].prev, self.nodes[slot].next);
+        if prev == NIL {
+            self.head = next;
+        } else {
+            self.nodes[prev].next = next;
+        }
+        if next == NIL {
+            self.tail = prev;
+        } else {
+            self.nodes[next].prev = prev;
+        }
+        self.nodes[slot].prev = NIL;
+        self.nodes[slot].next = NIL;
+    }
+
+    fn push_front(&mut self, slot: usize) {
+        self.nodes[slot].next = self.head;
+        if self.head != NIL {
+            self.nodes[self.head].prev = slot;
+        }
+        self.head = slot;
+        if self.tail == NIL {
+            self.tail = slot;
+        }
+    }
+}
+
+/// Estimates the number of bytes used by a single cache entry
+fn entry_bytes(key: &Charmap, candidates: &[u32]) -> usize {
+    // the key is stored twice: once in the lookup map and once in the node
+    let key_bytes = mem::size_of::<Charmap>() + key.len() * mem::size_of::<(char, u32)>();
+    2 * key_bytes
+        + mem::size_of::<Node>()
+        + mem::size_of::<usize>()
+        + mem::size_of_val(candidates)
+        // reference counts stored alongside the candidates
+        + 2 * mem::size_of::<usize>()
+}
+
+#[cfg(test)]
+mod candidate_cache_tests {
+    use super::{CacheLimit, CandidateCache, CandidateCaches};
+    use crate::anagram::get_charcount_map;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_entry_limit_evicts_least_recently_used() {
+        let mut cache = CandidateCache::new(CacheLimit::Entries(2));
+        let (a, b, c) = (
+            get_charcount_map("a", true, true),
+            get_charcount_map("b", true, true),
+            get_charcount_map("c", true, true)
+        );
+
+        cache.insert(a.clone(), Arc::new([0]));
+        cache.insert(b.clone(), Arc::new([1]));
+        // touch a so that b becomes the least recently used entry
+        assert_eq!(cache.get(&a).as_deref(), Some(&[0][..]));
+        cache.insert(c.clone(), Arc::new([2]));
+
+        assert!(cache.get(&b).is_none());
+        assert!(cache.get(&a).is_some());
+        assert!(cache.get(&c).is_some());
+
+        let stats = cache.stats();
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.evictions, 1);
+        assert_eq!(stats.hits, 3);
+        assert_eq!(stats.misses, 1);
+    }
+
+    #[test]
+    fn test_byte_limit() {
+        let mut cache = CandidateCache::new(CacheLimit::Bytes(0));
+        cache.insert(get_charcount_map("abc", true, true), Arc::new([0, 1, 2]));
+        assert_eq!(cache.stats().entries, 0);
+        assert_eq!(cache.stats().estimated_bytes, 0);
+
+        cache.set_limit(CacheLimit::Unbounded);
+        cache.insert(get_charcount_map("abc", true, true), Arc::new([0, 1, 2]));
+        assert_eq!(cache.stats().entries, 1);
+        assert!(cache.stats().estimated_bytes > 0);
+
+        cache.set_limit(CacheLimit::Entries(0));
+        assert_eq!(cache.stats().entries, 0);
+        assert_eq!(cache.stats().estimated_bytes, 0);
+    }
+
+    #[test]
+    fn test_split_limit() {
+        let caches = CandidateCaches::with_threads(CacheLimit::Entries(5), 2);
+        caches
+            .lock()
+            .insert(get_charcount_map("a", true, true), Arc::new([0]));
+        caches
+            .lock()
+            .insert(get_charcount_map("b", true, true), Arc::new([1]));
+        caches
+            .lock()
+            .insert(get_charcount_map("c", true, true), Arc::new([2]));
+        caches
+            .lock()
+            .insert(get_charcount_map("d", true, true), Arc::new([3]));
+        // each of the two caches keeps at most 3 of the 5 entries
+        assert_eq!(caches.stats().entries, 3);
+        assert_eq!(caches.stats().evictions, 1);
+
+        caches.set_limit(CacheLimit::Entries(2));
+        assert_eq!(caches.stats().entries, 1);
+
+        // a limit smaller than the number of caches still leaves room in each of them
+        assert_eq!(CacheLimit::Entries(3).split(8), CacheLimit::Entries(1));
+        assert_eq!(CacheLimit::Bytes(100).split(8), CacheLimit::Bytes(13));
+        assert_eq!(CacheLimit::Entries(0).split(8), CacheLimit::Entries(0));
+    }
+}
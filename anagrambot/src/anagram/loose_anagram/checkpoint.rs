@@ -0,0 +1,609 @@
+//! Saving and resuming loose anagram searches
+//!
+//! Finding every loose anagram of a long phrase can take hours. A [LooseAnagramsIterator]
+//! can be saved to a [LooseAnagramsCheckpoint] between calls to `next`, written to a file,
+//! and later resumed with [resume_loose_anagrams] (or [resume_loose_anagrams_with_templates]
+//! if it was restricted to templates), even in a different process.
+//! A resumed search continues exactly where the original stopped: no loose anagram is
+//! returned twice and none are skipped.
+//!
+//! Checkpoints refer to the wordlist they were created with by its
+//! [fingerprint](crate::wordlist::Wordlist::fingerprint), so a search can be resumed with any
+//! wordlist that has the same words in the same order (such as an index of the original one).
+//! Resuming a search with a different wordlist fails with [CheckpointError::WordlistMismatch].
+
+use super::{add_charmaps, word_fits, Charmap, LooseAnagramsIterator, PhraseTemplate};
+use crate::wordlist::{Fingerprint, TaggedWordlist, Wordlist};
+
+use std::{
+    error::Error,
+    fmt, fs,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    str::FromStr
+};
+
+/// The first word of every checkpoint
+const FORMAT_HEADER: &str = "anagrambot-loose-checkpoint";
+
+/// The version of the checkpoint format written by this version of anagrambot
+const FORMAT_VERSION: u32 = 2;
+
+/// The saved state of a [LooseAnagramsIterator]
+///
+/// Created by [LooseAnagramsIterator::checkpoint] and resumed by [resume_loose_anagrams].
+/// The partial result cache is not saved; it is rebuilt as the resumed search runs.
+///
+///# Examples
+/// ```
+/// use anagrambot::anagram::{find_loose_anagrams, resume_loose_anagrams, LooseAnagramsCheckpoint};
+/// use anagrambot::wordlist::BorrowedWordList;
+///
+/// const TEST_WORD_SET: [&str; 5] = ["race", "car", "care", "racecar", "acre"];
+/// let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+///
+/// let mut loose_anagrams_iter = find_loose_anagrams("racecar", &wordlist, 3, true);
+/// let mut found: Vec<String> = loose_anagrams_iter.by_ref().take(2).collect();
+///
+/// // save the search and load it again (this could be a file instead)
+/// let mut saved = Vec::new();
+/// loose_anagrams_iter.checkpoint(&wordlist).write_to(&mut saved).unwrap();
+/// let checkpoint = LooseAnagramsCheckpoint::read_from(saved.as_slice()).unwrap();
+///
+/// let resumed_iter = resume_loose_anagrams(&checkpoint, &wordlist).unwrap();
+/// found.extend(resumed_iter);
+/// found.sort();
+///
+/// assert_eq!(found,
+///     vec!["acre car", "car acre", "car care", "car race", "care car", "race car"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LooseAnagramsCheckpoint {
+    target_word: String,
+    min_word_length: usize,
+    case_sensitive: bool,
+    wordlist_fingerprint: Fingerprint,
+    templates: Vec<PhraseTemplate>,
+    candidate_count: usize,
+    results_returned: u64,
+    words_to_try: Vec<Vec<u32>>
+}
+
+impl LooseAnagramsCheckpoint {
+    /// Returns the word the saved search is finding loose anagrams of
+    pub fn target_word(&self) -> &str {
+        &self.target_word
+    }
+
+    /// Returns the minimum subword length of the saved search
+    pub fn min_word_length(&self) -> usize {
+        self.min_word_length
+    }
+
+    /// Returns true if the saved search is case sensitive
+    pub fn case_sensitive(&self) -> bool {
+        self.case_sensitive
+    }
+
+    /// Returns the number of loose anagrams the search had returned when it was saved
+    pub fn results_returned(&self) -> u64 {
+        self.results_returned
+    }
+
+    /// Returns the [fingerprint](Wordlist::fingerprint) of the wordlist the saved search uses
+    pub fn wordlist_fingerprint(&self) -> Fingerprint {
+        self.wordlist_fingerprint
+    }
+
+    /// Returns the [templates](LooseAnagramsIterator::with_templates) the saved search is
+    /// restricted to, or an empty slice if it isn't
+    ///
+    /// A search with templates can only be resumed by [resume_loose_anagrams_with_templates],
+    /// which restores them.
+    pub fn templates(&self) -> &[PhraseTemplate] {
+        &self.templates
+    }
+
+    /// Returns true if the saved search has no loose anagrams left to find
+    pub fn is_finished(&self) -> bool {
+        self.words_to_try.is_empty()
+    }
+
+    /// Writes this checkpoint to `writer`
+    ///
+    /// The format is line based text, starting with a version header.
+    pub fn write_to(&self, writer: impl Write) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        writeln!(writer, "{} {}", FORMAT_HEADER, FORMAT_VERSION)?;
+        writeln!(writer, "target {}", escape(&self.target_word))?;
+        writeln!(writer, "min_word_length {}", self.min_word_length)?;
+        writeln!(writer, "case_sensitive {}", self.case_sensitive)?;
+        writeln!(writer, "wordlist {}", self.wordlist_fingerprint)?;
+        writeln!(writer, "templates {}", self.templates.len())?;
+        for template in &self.templates {
+            writeln!(writer, "template {}", template)?;
+        }
+        writeln!(writer, "candidates {}", self.candidate_count)?;
+        writeln!(writer, "results {}", self.results_returned)?;
+        writeln!(writer, "stack {}", self.words_to_try.len())?;
+        for word_vec in &self.words_to_try {
+            let mut indices = word_vec.iter();
+            if let Some(first) = indices.next() {
+                write!(writer, "{}", first)?;
+            }
+            for index in indices {
+                write!(writer, " {}", index)?;
+            }
+            writeln!(writer)?;
+        }
+        writer.flush()
+    }
+
+    /// Reads a checkpoint previously written by [write_to](LooseAnagramsCheckpoint::write_to)
+    pub fn read_from(reader: impl BufRead) -> Result<Self, CheckpointError> {
+        let mut lines = reader
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line));
+        let mut next_line = || match lines.next() {
+            Some((line_number, Ok(line))) => Ok((line_number, line)),
+            Some((_, Err(err))) => Err(CheckpointError::Io(err)),
+            None => Err(CheckpointError::Malformed {
+                line: 0,
+                reason: String::from("unexpected end of checkpoint")
+            })
+        };
+
+        let (_, header) = next_line()?;
+        let version = match header.split_once(' ') {
+            Some((FORMAT_HEADER, version)) => version
+                .parse()
+                .map_err(|_| malformed(1, "invalid version"))?,
+            _ => return Err(malformed(1, "not a loose anagram checkpoint"))
+        };
+        if version != FORMAT_VERSION {
+            return Err(CheckpointError::UnsupportedVersion(version));
+        }
+
+        // reads a line of the form "<key> <value>"
+        let mut field = |key: &str| -> Result<(usize, String), CheckpointError> {
+            let (line_number, line) = next_line()?;
+            match line.split_once(' ') {
+                Some((found_key, value)) if found_key == key => {
+                    Ok((line_number, value.to_string()))
+                }
+                _ => Err(malformed(line_number, &format!("expected `{}`", key)))
+            }
+        };
+        fn parse<T: std::str::FromStr>(
+            (line, value): (usize, String),
+            key: &str
+        ) -> Result<T, CheckpointError> {
+            value
+                .parse()
+                .map_err(|_| malformed(line, &format!("invalid value for `{}`", key)))
+        }
+
+        let (target_line, target_word) = field("target")?;
+        let target_word = unescape(&target_word)
+            .ok_or_else(|| malformed(target_line, "invalid escape in target"))?;
+        let min_word_length = parse(field("min_word_length")?, "min_word_length")?;
+        let case_sensitive = parse(field("case_sensitive")?, "case_sensitive")?;
+        let (fingerprint_line, fingerprint) = field("wordlist")?;
+        let wordlist_fingerprint = Fingerprint::from_str(&fingerprint)
+            .map_err(|_| malformed(fingerprint_line, "invalid wordlist fingerprint"))?;
+        let template_count: usize = parse(field("templates")?, "templates")?;
+        let mut templates = Vec::with_capacity(template_count.min(1024));
+        for _ in 0..template_count {
+            templates.push(parse(field("template")?, "template")?);
+        }
+        let candidate_count = parse(field("candidates")?, "candidates")?;
+        let results_returned = parse(field("results")?, "results")?;
+        let stack_len: usize = parse(field("stack")?, "stack")?;
+
+        // the capacity is capped since stack_len comes from untrusted data
+        let mut words_to_try = Vec::with_capacity(stack_len.min(1024));
+        for _ in 0..stack_len {
+            let (line_number, line) = next_line()?;
+            let word_vec = line
+                .split(' ')
+                .map(|index| index.parse())
+                .collect::<Result<Vec<u32>, _>>()
+                .map_err(|_| malformed(line_number, "invalid candidate index"))?;
+            words_to_try.push(word_vec);
+        }
+
+        Ok(Self {
+            target_word,
+            min_word_length,
+            case_sensitive,
+            wordlist_fingerprint,
+            templates,
+            candidate_count,
+            results_returned,
+            words_to_try
+        })
+    }
+
+    /// Writes this checkpoint to the file at `path`, replacing it if it exists
+    ///
+    /// The checkpoint is first written to a temporary file next to `path` and then renamed,
+    /// so an existing checkpoint is never left partially overwritten.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut temp_path = path.as_os_str().to_owned();
+        temp_path.push(".tmp");
+
+        self.write_to(fs::File::create(&temp_path)?)?;
+        fs::rename(&temp_path, path)
+    }
+
+    /// Reads a checkpoint from the file at `path`
+    pub fn load(path: &Path) -> Result<Self, CheckpointError> {
+        let file = fs::File::open(path).map_err(CheckpointError::Io)?;
+        Self::read_from(BufReader::new(file))
+    }
+}
+
+/// An error encountered while reading or resuming a [LooseAnagramsCheckpoint]
+#[derive(Debug)]
+pub enum CheckpointError {
+    /// The checkpoint could not be read
+    Io(io::Error),
+    /// The data is not a checkpoint, or is corrupted
+    ///
+    /// `line` is the (1-based) line the problem was found on, or 0 if the data ended early.
+    Malformed { line: usize, reason: String },
+    /// The checkpoint was written in a format version this version of anagrambot can't read
+    UnsupportedVersion(u32),
+    /// The checkpoint was created with a different wordlist than the one provided
+    ///
+    /// Contains the fingerprints of the wordlist the checkpoint expects and the one provided.
+    WordlistMismatch {
+        expected: Fingerprint,
+        found: Fingerprint
+    },
+    /// The saved search state is inconsistent with the wordlist and target word
+    InvalidState(String)
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckpointError::Io(err) => write!(f, "failed to read checkpoint: {}", err),
+            CheckpointError::Malformed { line: 0, reason } => write!(f, "malformed checkpoint: {}", reason),
+            CheckpointError::Malformed { line, reason } => {
+                write!(f, "malformed checkpoint (line {}): {}", line, reason)
+            }
+            CheckpointError::UnsupportedVersion(version) => {
+                write!(f, "unsupported checkpoint version {} (expected {})", version, FORMAT_VERSION)
+            }
+            CheckpointError::WordlistMismatch { expected, found } => write!(
+                f,
+                "checkpoint was created with a different wordlist (expected fingerprint {}, found {})",
+                expected, found
+            ),
+            CheckpointError::InvalidState(reason) => write!(f, "invalid checkpoint state: {}", reason)
+        }
+    }
+}
+
+impl Error for CheckpointError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CheckpointError::Io(err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+fn malformed(line: usize, reason: &str) -> CheckpointError {
+    CheckpointError::Malformed {
+        line,
+        reason: reason.to_string()
+    }
+}
+
+impl<'a> LooseAnagramsIterator<'a> {
+    /// Saves the current state of this search
+    ///
+    /// `wordlist` must be the wordlist this search was started with; its fingerprint is
+    /// stored in the checkpoint so that the search can only be resumed with the same wordlist.
+    /// See [LooseAnagramsCheckpoint] for an example.
+    pub fn checkpoint<T>(&self, wordlist: &T) -> LooseAnagramsCheckpoint
+    where
+        T: Wordlist + ?Sized
+    {
+        LooseAnagramsCheckpoint {
+            target_word: self.target_word.clone(),
+            min_word_length: self.min_word_length,
+            case_sensitive: self.case_sensitive,
+            wordlist_fingerprint: wordlist.fingerprint(),
+            templates: self
+                .templates
+                .as_ref()
+                .map(|templates| templates.templates().to_vec())
+                .unwrap_or_default(),
+            candidate_count: self.candidates.len(),
+            results_returned: self.results_returned,
+            words_to_try: self
+                .words_to_try
+                .iter()
+                .map(|(word_vec, _)| word_vec.clone())
+                .collect()
+        }
+    }
+}
+
+/// Resumes a loose anagram search saved with [LooseAnagramsIterator::checkpoint]
+///
+/// `wordlist` must have the same content as the wordlist the search was started with.
+///
+/// The returned iterator has the default cache and search limits; use
+/// [with_cache_limit](LooseAnagramsIterator::with_cache_limit) and
+/// [with_limits](LooseAnagramsIterator::with_limits) to set them again if needed.
+///
+/// Fails with [CheckpointError::InvalidState] if the search was restricted to
+/// [templates](LooseAnagramsCheckpoint::templates), since they can't be restored without the
+/// tags of the words; use [resume_loose_anagrams_with_templates] instead.
+pub fn resume_loose_anagrams<'a, T>(
+    checkpoint: &LooseAnagramsCheckpoint,
+    wordlist: &'a T
+) -> Result<LooseAnagramsIterator<'a>, CheckpointError>
+where
+    T: Wordlist + ?Sized
+{
+    if !checkpoint.templates.is_empty() {
+        return Err(CheckpointError::InvalidState(String::from(
+            "the search has templates, which need a tagged wordlist to resume"
+        )));
+    }
+    resume(checkpoint, wordlist)
+}
+
+/// Resumes a loose anagram search saved with [LooseAnagramsIterator::checkpoint], restoring
+/// its [templates](LooseAnagramsCheckpoint::templates)
+///
+/// `wordlist` must have the same content as the wordlist the search was started with, and
+/// `tags` gives the parts of speech of its words (as for
+/// [with_templates](LooseAnagramsIterator::with_templates); they are usually the same
+/// wordlist). Searches without templates are resumed just like [resume_loose_anagrams].
+pub fn resume_loose_anagrams_with_templates<'a, W, T>(
+    checkpoint: &LooseAnagramsCheckpoint,
+    wordlist: &'a W,
+    tags: &T
+) -> Result<LooseAnagramsIterator<'a>, CheckpointError>
+where
+    W: Wordlist + ?Sized,
+    T: TaggedWordlist + ?Sized
+{
+    let loose_anagrams_iter = resume(checkpoint, wordlist)?;
+    if checkpoint.templates.is_empty() {
+        return Ok(loose_anagrams_iter);
+    }
+    Ok(loose_anagrams_iter.with_templates(&checkpoint.templates, tags))
+}
+
+/// Resumes the search saved in `checkpoint` without its templates
+fn resume<'a, T>(
+    checkpoint: &LooseAnagramsCheckpoint,
+    wordlist: &'a T
+) -> Result<LooseAnagramsIterator<'a>, CheckpointError>
+where
+    T: Wordlist + ?Sized
+{
+    let fingerprint = wordlist.fingerprint();
+    if fingerprint != checkpoint.wordlist_fingerprint {
+        return Err(CheckpointError::WordlistMismatch {
+            expected: checkpoint.wordlist_fingerprint,
+            found: fingerprint
+        });
+    }
+
+    let mut loose_anagrams_iter = LooseAnagramsIterator::without_frontier(
+        &checkpoint.target_word,
+        wordlist,
+        checkpoint.min_word_length,
+        checkpoint.case_sensitive
+    );
+    if loose_anagrams_iter.candidates.len() != checkpoint.candidate_count {
+        return Err(CheckpointError::InvalidState(format!(
+            "expected {} candidate words, found {}",
+            checkpoint.candidate_count,
+            loose_anagrams_iter.candidates.len()
+        )));
+    }
+
+    let mut words_to_try = Vec::with_capacity(checkpoint.words_to_try.len());
+    for word_vec in &checkpoint.words_to_try {
+        let mut word_charmap = Charmap::new();
+        for index in word_vec {
+            let (_, charmap) = loose_anagrams_iter
+                .candidates
+                .get(*index as usize)
+                .ok_or_else(|| {
+                    CheckpointError::InvalidState(format!("no candidate word {}", index))
+                })?;
+            word_charmap = add_charmaps(&word_charmap, charmap);
+        }
+        // the search relies on every partial anagram fitting into the target
+        if word_vec.is_empty() || !word_fits(&loose_anagrams_iter.target_charmap, &word_charmap) {
+            return Err(CheckpointError::InvalidState(String::from(
+                "partial anagram does not fit into the target word"
+            )));
+        }
+        words_to_try.push((word_vec.clone(), word_charmap));
+    }
+
+    loose_anagrams_iter.words_to_try = words_to_try;
+    loose_anagrams_iter.results_returned = checkpoint.results_returned;
+    Ok(loose_anagrams_iter)
+}
+
+/// Escapes backslashes and line breaks so that `text` fits on a single line
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(character)
+        }
+    }
+    escaped
+}
+
+/// Reverses [escape], returning `None` if `text` contains an invalid escape sequence
+fn unescape(text: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut characters = text.chars();
+    while let Some(character) = characters.next() {
+        if character == '\\' {
+            match characters.next()? {
+                '\\' => unescaped.push('\\'),
+                'n' => unescaped.push('\n'),
+                'r' => unescaped.push('\r'),
+                _ => return None
+            }
+        } else {
+            unescaped.push(character);
+        }
+    }
+    Some(unescaped)
+}
+
+#[cfg(test)]
+mod checkpoint_tests {
+    use super::{
+        escape, resume_loose_anagrams, resume_loose_anagrams_with_templates, unescape,
+        CheckpointError, LooseAnagramsCheckpoint
+    };
+    use crate::{
+        anagram::{find_loose_anagrams, PhraseTemplate},
+        wordlist::{write_index, BorrowedWordList, IndexedWordList, TaggedWordList, Wordlist}
+    };
+
+    const TEST_WORD_SET: [&str; 8] = ["race", "car", "care", "racecar", "acre", "arc", "a", "err"];
+
+    #[test]
+    fn test_resume_at_every_position() {
+        let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+        let all_results: Vec<String> =
+            find_loose_anagrams("race car", &wordlist, 1, true).collect();
+        assert!(!all_results.is_empty());
+
+        for split in 0..=all_results.len() {
+            let mut iter = find_loose_anagrams("race car", &wordlist, 1, true);
+            let mut results: Vec<String> = iter.by_ref().take(split).collect();
+
+            let mut saved = Vec::new();
+            iter.checkpoint(&wordlist).write_to(&mut saved).unwrap();
+            let checkpoint = LooseAnagramsCheckpoint::read_from(saved.as_slice()).unwrap();
+            assert_eq!(checkpoint.results_returned(), split as u64);
+
+            results.extend(resume_loose_anagrams(&checkpoint, &wordlist).unwrap());
+            assert_eq!(results, all_results);
+        }
+    }
+
+    #[test]
+    fn test_templates_are_saved() {
+        let wordlist: TaggedWordList = [("race", "NOUN,VERB"), ("car", "NOUN"), ("arc", "PUNCT")]
+            .into_iter()
+            .map(|(word, tags)| (String::from(word), tags.parse().unwrap()))
+            .collect();
+        let templates: Vec<PhraseTemplate> =
+            vec!["VERB NOUN".parse().unwrap(), "NOUN|SYM".parse().unwrap()];
+
+        let iter = find_loose_anagrams("racecar", &wordlist, 1, true);
+        assert!(iter.checkpoint(&wordlist).templates().is_empty());
+
+        let iter = iter.with_templates(&templates, &wordlist);
+        let mut saved = Vec::new();
+        iter.checkpoint(&wordlist).write_to(&mut saved).unwrap();
+        let checkpoint = LooseAnagramsCheckpoint::read_from(saved.as_slice()).unwrap();
+        assert_eq!(checkpoint.templates(), templates);
+    }
+
+    #[test]
+    fn test_resume_with_templates() {
+        let wordlist: TaggedWordList = [("race", "NOUN,VERB"), ("car", "NOUN"), ("arc", "VERB")]
+            .into_iter()
+            .map(|(word, tags)| (String::from(word), tags.parse().unwrap()))
+            .collect();
+        let templates: Vec<PhraseTemplate> = vec!["VERB NOUN".parse().unwrap()];
+
+        let mut iter = find_loose_anagrams("racecar", &wordlist, 1, true)
+            .with_templates(&templates, &wordlist);
+        let mut results: Vec<String> = iter.by_ref().take(1).collect();
+        let checkpoint = iter.checkpoint(&wordlist);
+
+        // the templates would be lost
+        assert!(matches!(
+            resume_loose_anagrams(&checkpoint, &wordlist),
+            Err(CheckpointError::InvalidState(_))
+        ));
+        results.extend(
+            resume_loose_anagrams_with_templates(&checkpoint, &wordlist, &wordlist).unwrap()
+        );
+        results.sort();
+        assert_eq!(results, vec!["arc race", "race car"]);
+    }
+
+    #[test]
+    fn test_wordlist_mismatch() {
+        let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+        let other_wordlist: BorrowedWordList = TEST_WORD_SET[..5].iter().copied().collect();
+
+        let checkpoint = find_loose_anagrams("racecar", &wordlist, 1, true).checkpoint(&wordlist);
+        assert_eq!(checkpoint.wordlist_fingerprint(), wordlist.fingerprint());
+        assert!(matches!(
+            resume_loose_anagrams(&checkpoint, &other_wordlist),
+            Err(CheckpointError::WordlistMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_resume_with_index() {
+        // an index of the wordlist has the same fingerprint, so it can resume the search
+        let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+        let mut index = Vec::new();
+        write_index(&wordlist, &mut index).unwrap();
+        let indexed = IndexedWordList::from_bytes(&index).unwrap();
+
+        let mut iter = find_loose_anagrams("racecar", &wordlist, 1, true);
+        let mut results: Vec<String> = iter.by_ref().take(3).collect();
+        let checkpoint = iter.checkpoint(&wordlist);
+        results.extend(resume_loose_anagrams(&checkpoint, &indexed).unwrap());
+        assert!(results
+            .into_iter()
+            .eq(find_loose_anagrams("racecar", &wordlist, 1, true)));
+    }
+
+    #[test]
+    fn test_malformed() {
+        assert!(matches!(
+            LooseAnagramsCheckpoint::read_from("not a checkpoint".as_bytes()),
+            Err(CheckpointError::Malformed { line: 1, .. })
+        ));
+        assert!(matches!(
+            LooseAnagramsCheckpoint::read_from("anagrambot-loose-checkpoint 99\n".as_bytes()),
+            Err(CheckpointError::UnsupportedVersion(99))
+        ));
+        assert!(matches!(
+            LooseAnagramsCheckpoint::read_from(
+                "anagrambot-loose-checkpoint 2\ntarget a\n".as_bytes()
+            ),
+            Err(CheckpointError::Malformed { line: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_escape() {
+        for text in ["race car", "back\\slash", "two\nlines\r\n", ""] {
+            assert!(!escape(text).contains('\n'));
+            assert_eq!(unescape(&escape(text)).as_deref(), Some(text));
+        }
+        assert_eq!(unescape("\\x"), None);
+    }
+}
@@ -0,0 +1,307 @@
+//! Counting loose anagrams without finding them
+//!
+//! The number of loose anagrams of a word grows extremely quickly with its length, but many
+//! partial anagrams leave the same letters over. [count_loose_anagrams] counts the ways to
+//! use up each distinct set of leftover letters only once, so it can count far more
+//! loose anagrams than could ever be found one at a time.
+
+use super::{
+    sub_charmaps, word_fits, Charmap, LooseAnagramsIterator, SearchLimits, Truncation, Wordlist
+};
+
+use std::{collections::HashMap, fmt};
+
+/// Whether loose anagrams that contain the same words in a different order are counted separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountMode {
+    /// Count every order of words separately, like [find_loose_anagrams](super::find_loose_anagrams)
+    ///
+    /// For example, "race car" and "car race" are counted as two loose anagrams.
+    Ordered,
+    /// Count each set of words once, no matter how many orders it can be arranged in
+    ///
+    /// For example, "race car" and "car race" are counted as one loose anagram.
+    Unordered
+}
+
+/// The result of [count_loose_anagrams]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LooseAnagramCount {
+    /// The exact number of loose anagrams
+    Exact(u64),
+    /// A lower bound on the number of loose anagrams
+    ///
+    /// `truncation` is the reason counting stopped early, or `None` if counting finished
+    /// but the number of loose anagrams is too large to fit in a `u64`
+    /// (in which case `count` is [u64::MAX]).
+    AtLeast {
+        count: u64,
+        truncation: Option<Truncation>
+    }
+}
+
+impl LooseAnagramCount {
+    /// Returns the number of loose anagrams counted (a lower bound if the count isn't exact)
+    pub fn count(&self) -> u64 {
+        match self {
+            Self::Exact(count) | Self::AtLeast { count, .. } => *count
+        }
+    }
+
+    /// Returns true if the count is exact
+    pub fn is_exact(&self) -> bool {
+        matches!(self, Self::Exact(_))
+    }
+}
+
+impl fmt::Display for LooseAnagramCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exact(count) => write!(f, "{}", count),
+            Self::AtLeast { count, .. } => write!(f, "at least {}", count)
+        }
+    }
+}
+
+/// Counts loose anagrams with memoization over the letters left to use
+struct Counter<'c, 'a> {
+    candidates: &'c [(&'a str, Charmap)],
+    mode: CountMode,
+    limits: SearchLimits,
+    // maps (letters left, smallest candidate index allowed) to the number of ways to use them up;
+    // the index is always 0 when counting ordered loose anagrams
+    memo: HashMap<(Charmap, u32), u64>,
+    truncation: Option<Truncation>,
+    overflowed: bool
+}
+
+impl<'c, 'a> Counter<'c, 'a> {
+    /// Returns the number of ways to use up exactly the letters in `remaining`
+    ///
+    /// `allowed` must contain the indices of every candidate that fits into `remaining`
+    /// (and, when counting unordered loose anagrams, whose index is at least `min_index`).
+    /// If counting stops early, the partial (lower bound) count is returned and not memoized.
+    fn count(&mut self, remaining: &Charmap, allowed: &[u32], min_index: u32) -> u64 {
+        if remaining.is_empty() {
+            return 1;
+        }
+        let key = (remaining.clone(), min_index);
+        if let Some(count) = self.memo.get(&key) {
+            return *count;
+        }
+        if let Some(truncation) = self.limits.check() {
+            self.truncation = Some(truncation);
+            return 0;
+        }
+
+        let mut total: u64 = 0;
+        for (position, index) in allowed.iter().copied().enumerate() {
+            let word_charmap = &self.candidates[index as usize].1;
+            // it is safe to use sub_charmaps here because every allowed word fits into remaining
+            let reduced_map = unsafe { sub_charmaps(remaining, word_charmap) };
+
+            // unordered loose anagrams are only counted with their words in candidate order,
+            // so the following words may not come before this one
+            let (parent_words, next_min_index) = match self.mode {
+                CountMode::Ordered => (allowed, 0),
+                CountMode::Unordered => (&allowed[position..], index)
+            };
+            let sub_allowed: Vec<u32> = parent_words
+                .iter()
+                .copied()
+                .filter(|sub_index| {
+                    word_fits(&reduced_map, &self.candidates[*sub_index as usize].1)
+                })
+                .collect();
+
+            let sub_count = self.count(&reduced_map, &sub_allowed, next_min_index);
+            total = total.checked_add(sub_count).unwrap_or_else(|| {
+                self.overflowed = true;
+                u64::MAX
+            });
+            if self.truncation.is_some() {
+                return total;
+            }
+        }
+
+        self.memo.insert(key, total);
+        total
+    }
+}
+
+/// Counts the loose anagrams of `target_word` without finding them
+///
+/// When `mode` is [CountMode::Ordered], the count is the number of results that
+/// [find_loose_anagrams](super::find_loose_anagrams) would return with the same arguments.
+/// When `mode` is [CountMode::Unordered], loose anagrams made of the same words in a different
+/// order are only counted once.
+///
+/// Counting is usually much faster than finding every loose anagram, but it can still take a
+/// long time for long words. If `limits` stop counting early, or the count doesn't fit in a
+/// `u64`, a lower bound is returned instead of the exact count (see [LooseAnagramCount]).
+///
+///# Examples
+/// ```
+/// use anagrambot::anagram::{count_loose_anagrams, CountMode, LooseAnagramCount, SearchLimits};
+/// use anagrambot::wordlist::BorrowedWordList;
+///
+/// const CASE_SENSITIVE: bool = true;
+/// const MIN_WORD_LENGTH: usize = 3;
+///
+/// const TEST_WORD_SET: [&str; 5] = ["race", "car", "care", "racecar", "acre"];
+/// let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+///
+/// // "acre car", "car acre", "car care", "car race", "care car", "race car"
+/// let ordered = count_loose_anagrams("racecar", &wordlist, MIN_WORD_LENGTH, CASE_SENSITIVE,
+///     CountMode::Ordered, SearchLimits::new());
+/// assert_eq!(ordered, LooseAnagramCount::Exact(6));
+///
+/// // "acre car", "care car", "race car"
+/// let unordered = count_loose_anagrams("racecar", &wordlist, MIN_WORD_LENGTH, CASE_SENSITIVE,
+///     CountMode::Unordered, SearchLimits::new());
+/// assert_eq!(unordered, LooseAnagramCount::Exact(3));
+/// ```
+///
+///# Technical Notes
+///
+/// Like [find_loose_anagrams](super::find_loose_anagrams), this first finds every word in
+/// `wordlist` that fits into `target_word`. The number of ways to use up a set of letters is
+/// then the sum, over each word that fits into those letters, of the number of ways to use up
+/// the letters left over after that word. Each distinct set of leftover letters is only
+/// counted once, and the counts are kept until counting is finished; for long words with
+/// large wordlists, this may use a lot of memory.
+pub fn count_loose_anagrams<T>(
+    target_word: &str,
+    wordlist: &T,
+    min_word_length: usize,
+    case_sensitive: bool,
+    mode: CountMode,
+    limits: SearchLimits
+) -> LooseAnagramCount
+where
+    T: Wordlist + ?Sized
+{
+    let search = LooseAnagramsIterator::without_frontier(
+        target_word,
+        wordlist,
+        min_word_length,
+        case_sensitive
+    );
+    let mut counter = Counter {
+        candidates: &search.candidates,
+        mode,
+        limits,
+        memo: HashMap::new(),
+        truncation: None,
+        overflowed: false
+    };
+
+    // every candidate fits into the target by definition
+    let all_candidates: Vec<u32> = (0..counter.candidates.len() as u32).collect();
+    let count = counter.count(&search.target_charmap, &all_candidates, 0);
+    let count = count.saturating_sub(excluded_arrangements(target_word, &search, mode));
+
+    match (counter.truncation, counter.overflowed) {
+        (None, false) => LooseAnagramCount::Exact(count),
+        (truncation, _) => LooseAnagramCount::AtLeast { count, truncation }
+    }
+}
+
+/// Returns the number of counted arrangements that loose anagram searches leave out
+/// because they are identical to the target word (which can happen with multi-word targets)
+fn excluded_arrangements(
+    target_word: &str,
+    search: &LooseAnagramsIterator,
+    mode: CountMode
+) -> u64 {
+    let target_words: Vec<&str> = target_word.split(' ').collect();
+    let all_candidates = target_words.iter().all(|target_word| {
+        search
+            .candidates
+            .iter()
+            .any(|(candidate, _)| candidate == target_word)
+    });
+    if target_words.len() < 2 || !all_candidates {
+        return 0;
+    }
+
+    match mode {
+        CountMode::Ordered => 1,
+        // the target's set of words is still a valid loose anagram in another order,
+        // unless every word is the same
+        CountMode::Unordered => u64::from(target_words.iter().all(|word| *word == target_words[0]))
+    }
+}
+
+#[cfg(test)]
+mod count_tests {
+    use super::{count_loose_anagrams, CountMode, LooseAnagramCount};
+    use crate::{
+        anagram::{find_loose_anagrams, SearchLimits, Truncation},
+        wordlist::BorrowedWordList
+    };
+    use std::{collections::HashSet, time::Instant};
+
+    const TEST_WORD_SET: [&str; 12] = [
+        "race", "car", "care", "racecar", "acre", "arc", "a", "err", "cream", "ace", "aa", "Race"
+    ];
+    const TARGETS: [&str; 5] = ["racecar", "race car", "arc cream", "aa aa", "Racecar"];
+
+    #[test]
+    fn test_matches_find_loose_anagrams() {
+        let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+        for case_sensitive in [true, false] {
+            for target in TARGETS {
+                let found: Vec<String> =
+                    find_loose_anagrams(target, &wordlist, 1, case_sensitive).collect();
+                let word_sets: HashSet<Vec<&str>> = found
+                    .iter()
+                    .map(|anagram| {
+                        let mut words: Vec<&str> = anagram.split(' ').collect();
+                        words.sort_unstable();
+                        words
+                    })
+                    .collect();
+
+                let ordered = count_loose_anagrams(
+                    target,
+                    &wordlist,
+                    1,
+                    case_sensitive,
+                    CountMode::Ordered,
+                    SearchLimits::new()
+                );
+                let unordered = count_loose_anagrams(
+                    target,
+                    &wordlist,
+                    1,
+                    case_sensitive,
+                    CountMode::Unordered,
+                    SearchLimits::new()
+                );
+                assert_eq!(ordered, LooseAnagramCount::Exact(found.len() as u64));
+                assert_eq!(unordered, LooseAnagramCount::Exact(word_sets.len() as u64));
+            }
+        }
+    }
+
+    #[test]
+    fn test_time_limit() {
+        let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+        let count = count_loose_anagrams(
+            "racecar",
+            &wordlist,
+            1,
+            true,
+            CountMode::Ordered,
+            SearchLimits::new().with_deadline(Instant::now())
+        );
+        assert_eq!(
+            count,
+            LooseAnagramCount::AtLeast {
+                count: 0,
+                truncation: Some(Truncation::TimedOut)
+            }
+        );
+    }
+}
@@ -0,0 +1,262 @@
+//! Parallel loose anagram search (requires the `parallel` feature)
+//!
+//! [par_find_loose_anagrams] splits a loose anagram search by the first word of each
+//! loose anagram. Each part is an ordinary [LooseAnagramsIterator] search, and the parts are
+//! spread across threads by [rayon], whose work stealing keeps every thread busy even though
+//! some parts take much longer than others. Each thread has its own candidate cache, which
+//! the parts it runs share, so threads never wait for each other to use a cache.
+
+use super::{
+    CacheLimit, CacheStats, CandidateCaches, LooseAnagramsIterator, PhraseTemplate, SearchLimits,
+    Truncation, Wordlist
+};
+use crate::wordlist::TaggedWordlist;
+
+use rayon::iter::{plumbing::UnindexedConsumer, IntoParallelIterator, ParallelIterator};
+use std::sync::{Arc, Mutex};
+
+/// A parallel iterator over all the loose anagrams of a word
+///
+/// The return value of [par_find_loose_anagrams]
+pub struct ParLooseAnagrams<'a> {
+    // iterator holding the shared search state; its own frontier and cache are never used
+    base: LooseAnagramsIterator<'a>,
+    cache_limit: CacheLimit,
+    report: TruncationReport
+}
+
+/// Reports whether a [ParLooseAnagrams] search stopped early
+///
+/// A parallel iterator is consumed when it is used, so the reason it stopped early (if it did)
+/// is made available through this separate handle. Obtain one with
+/// [ParLooseAnagrams::truncation_report] before starting the search.
+#[derive(Debug, Clone, Default)]
+pub struct TruncationReport {
+    truncation: Arc<Mutex<Option<Truncation>>>
+}
+
+impl TruncationReport {
+    /// Returns the reason the search stopped early, or `None` if it hasn't
+    pub fn truncation(&self) -> Option<Truncation> {
+        *self
+            .truncation
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn record(&self, truncation: Truncation) {
+        self.truncation
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get_or_insert(truncation);
+    }
+}
+
+impl<'a> ParLooseAnagrams<'a> {
+    /// Limits the amount of memory used to cache partial results, returning the modified iterator
+    ///
+    /// The limit is split evenly between the caches of the threads the search runs on.
+    /// See [LooseAnagramsIterator::with_cache_limit].
+    pub fn with_cache_limit(mut self, limit: CacheLimit) -> Self {
+        self.cache_limit = limit;
+        self
+    }
+
+    /// Stops the search early according to `limits`, returning the modified iterator
+    ///
+    /// See [LooseAnagramsIterator::with_limits].
+    pub fn with_limits(mut self, limits: SearchLimits) -> Self {
+        self.base.limits = limits;
+        self
+    }
+
+    /// Only returns loose anagrams matching one of `templates`, returning the modified iterator
+    ///
+    /// See [LooseAnagramsIterator::with_templates].
+    pub fn with_templates<T>(mut self, templates: &[PhraseTemplate], wordlist: &T) -> Self
+    where
+        T: TaggedWordlist + ?Sized
+    {
+        self.base = self.base.with_templates(templates, wordlist);
+        self
+    }
+
+    /// Returns a handle that reports whether the search stopped early
+    pub fn truncation_report(&self) -> TruncationReport {
+        self.report.clone()
+    }
+
+    /// Returns the current size of the partial result caches and statistics on their use
+    ///
+    /// The caches are created when the search starts, so this is always empty.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.base.cache_stats()
+    }
+}
+
+impl<'a> ParallelIterator for ParLooseAnagrams<'a> {
+    type Item = String;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>
+    {
+        let ParLooseAnagrams {
+            base,
+            cache_limit,
+            report
+        } = self;
+        // this runs on the thread pool the search is for, so it has the right number of threads
+        let caches = Arc::new(CandidateCaches::with_threads(
+            cache_limit,
+            rayon::current_num_threads()
+        ));
+        (0..base.candidates.len() as u32)
+            .into_par_iter()
+            .flat_map_iter(move |first_word| SubSearch {
+                iter: base.sub_search(first_word, &caches),
+                report: report.clone()
+            })
+            .drive_unindexed(consumer)
+    }
+}
+
+impl<'a> LooseAnagramsIterator<'a> {
+    /// Creates an iterator over the loose anagrams starting with the candidate `first_word`
+    ///
+    /// The new iterator shares its candidates and limits with this one, and uses `caches`.
+    fn sub_search(
+        &self,
+        first_word: u32,
+        caches: &Arc<CandidateCaches>
+    ) -> LooseAnagramsIterator<'a> {
+        let first_charmap = self.candidates[first_word as usize].1.clone();
+        let allowed_first_word = match &self.templates {
+            Some(templates) => templates.allows_prefix(&[first_word]),
+            None => true
+        };
+        let words_to_try = if allowed_first_word {
+            vec![(vec![first_word], first_charmap)]
+        } else {
+            Vec::new()
+        };
+        LooseAnagramsIterator {
+            target_word: self.target_word.clone(),
+            target_charmap: self.target_charmap.clone(),
+            min_word_length: self.min_word_length,
+            case_sensitive: self.case_sensitive,
+            candidates: Arc::clone(&self.candidates),
+            candidate_cache: Arc::clone(caches),
+            words_to_try,
+            results_returned: 0,
+            limits: self.limits.clone(),
+            truncation: None,
+            templates: self.templates.clone()
+        }
+    }
+}
+
+/// One part of a parallel search; records truncation in the shared report when it ends
+struct SubSearch<'a> {
+    iter: LooseAnagramsIterator<'a>,
+    report: TruncationReport
+}
+
+impl<'a> Iterator for SubSearch<'a> {
+    type Item = String;
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.iter.next();
+        if next.is_none() {
+            if let Some(truncation) = self.iter.truncation() {
+                self.report.record(truncation);
+            }
+        }
+        next
+    }
+}
+
+/// Like [find_loose_anagrams](super::find_loose_anagrams), but searches using multiple threads
+///
+/// Returns a [rayon] [ParallelIterator] over all loose anagrams of `target_word`.
+/// The search runs on the current rayon thread pool; use [rayon::ThreadPool::install]
+/// to control the number of threads. Results are returned in an unpredictable order.
+///
+/// Requires the `parallel` feature.
+///
+///# Examples
+/// ```
+/// use anagrambot::anagram::par_find_loose_anagrams;
+/// use anagrambot::wordlist::BorrowedWordList;
+/// use rayon::iter::ParallelIterator;
+///
+/// const TEST_WORD_SET: [&str; 5] = ["race", "car", "care", "racecar", "acre"];
+/// let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+///
+/// let mut loose_anagrams_vec: Vec<String> =
+///     par_find_loose_anagrams("racecar", &wordlist, 3, true).collect();
+/// loose_anagrams_vec.sort();
+///
+/// assert_eq!(loose_anagrams_vec,
+///     vec!["acre car", "car acre", "car care", "car race", "care car", "race car"]);
+/// ```
+pub fn par_find_loose_anagrams<'a, T>(
+    target_word: &str,
+    wordlist: &'a T,
+    min_word_length: usize,
+    case_sensitive: bool
+) -> ParLooseAnagrams<'a>
+where
+    T: Wordlist + ?Sized
+{
+    ParLooseAnagrams {
+        base: LooseAnagramsIterator::without_frontier(
+            target_word,
+            wordlist,
+            min_word_length,
+            case_sensitive
+        ),
+        cache_limit: CacheLimit::Unbounded,
+        report: TruncationReport::default()
+    }
+}
+
+#[cfg(test)]
+mod parallel_tests {
+    use super::par_find_loose_anagrams;
+    use crate::{
+        anagram::{find_loose_anagrams, CancellationToken, SearchLimits, Truncation},
+        wordlist::BorrowedWordList
+    };
+    use rayon::iter::ParallelIterator;
+
+    const TEST_WORD_SET: [&str; 10] = [
+        "race", "car", "care", "racecar", "acre", "arc", "a", "err", "cream", "ace"
+    ];
+
+    #[test]
+    fn test_matches_sequential() {
+        let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+        for target in ["racecar", "race car", "arc cream"] {
+            let mut sequential: Vec<String> =
+                find_loose_anagrams(target, &wordlist, 1, true).collect();
+            let mut parallel: Vec<String> =
+                par_find_loose_anagrams(target, &wordlist, 1, true).collect();
+            sequential.sort();
+            parallel.sort();
+            assert_eq!(sequential, parallel);
+        }
+    }
+
+    #[test]
+    fn test_truncation_report() {
+        let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+        let token = CancellationToken::new();
+        token.cancel();
+
+        let search = par_find_loose_anagrams("racecar", &wordlist, 1, true)
+            .with_limits(SearchLimits::new().with_cancellation(token));
+        let report = search.truncation_report();
+        assert_eq!(search.count(), 0);
+        assert_eq!(report.truncation(), Some(Truncation::Cancelled));
+    }
+}
@@ -0,0 +1,283 @@
+//! Restricting loose anagrams to grammatical phrases
+//!
+//! A [PhraseTemplate] is a sequence of parts of speech, such as `ADJ NOUN`. With templates
+//! set (see [LooseAnagramsIterator::with_templates]), a loose anagram search only returns
+//! phrases whose words have the parts of speech of one of the templates, in order.
+
+use super::LooseAnagramsIterator;
+use crate::wordlist::{PartOfSpeech, PosTags, TaggedWordlist, UnknownTagError};
+
+use std::{error::Error, fmt, str::FromStr, sync::Arc};
+
+/// A sequence of parts of speech that a loose anagram's words must match
+///
+/// Each slot of the template matches one word. A slot can allow several parts of speech,
+/// separated by `|` when parsing.
+///
+///# Examples
+/// ```
+/// use anagrambot::anagram::PhraseTemplate;
+///
+/// let template: PhraseTemplate = "ADJ|NUM NOUN".parse().unwrap();
+/// assert_eq!(template.len(), 2);
+/// assert_eq!(template.to_string(), "ADJ|NUM NOUN");
+///
+/// assert!("".parse::<PhraseTemplate>().is_err());
+/// assert!("ADJ THING".parse::<PhraseTemplate>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhraseTemplate {
+    slots: Vec<PosTags>
+}
+
+impl PhraseTemplate {
+    /// Creates a template from the parts of speech allowed for each word
+    ///
+    /// Returns `None` if `slots` is empty or any slot allows no parts of speech.
+    pub fn new(slots: impl IntoIterator<Item = PosTags>) -> Option<Self> {
+        let slots: Vec<PosTags> = slots.into_iter().collect();
+        if slots.is_empty() || slots.iter().any(PosTags::is_empty) {
+            None
+        } else {
+            Some(Self { slots })
+        }
+    }
+
+    /// Returns the number of words in phrases matching this template
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Always returns false; templates have at least one slot
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Returns true if words with the tags in `word_tags` could begin a phrase matching this template
+    fn matches_prefix(&self, word_tags: impl ExactSizeIterator<Item = PosTags>) -> bool {
+        word_tags.len() <= self.slots.len()
+            && self
+                .slots
+                .iter()
+                .zip(word_tags)
+                .all(|(slot, tags)| slot.intersects(tags))
+    }
+}
+
+impl fmt::Display for PhraseTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let slots: Vec<String> = self
+            .slots
+            .iter()
+            .map(|slot| {
+                slot.iter()
+                    .map(|part_of_speech| part_of_speech.tag())
+                    .collect::<Vec<&str>>()
+                    .join("|")
+            })
+            .collect();
+        f.write_str(&slots.join(" "))
+    }
+}
+
+/// The error returned when parsing an invalid [PhraseTemplate]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    /// The template has no slots
+    Empty,
+    /// A slot of the template contains an unknown tag
+    UnknownTag(UnknownTagError)
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::Empty => write!(f, "phrase template is empty"),
+            TemplateError::UnknownTag(err) => write!(f, "invalid phrase template: {}", err)
+        }
+    }
+}
+
+impl Error for TemplateError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TemplateError::UnknownTag(err) => Some(err),
+            TemplateError::Empty => None
+        }
+    }
+}
+
+impl FromStr for PhraseTemplate {
+    type Err = TemplateError;
+
+    /// Parses a template from whitespace separated slots, each containing one or more
+    /// Universal Dependencies tags separated by `|` (such as `DET ADJ|NUM NOUN`)
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        let slots = template
+            .split_whitespace()
+            .map(|slot| {
+                slot.split('|')
+                    .map(PartOfSpeech::from_str)
+                    .collect::<Result<PosTags, UnknownTagError>>()
+            })
+            .collect::<Result<Vec<PosTags>, UnknownTagError>>()
+            .map_err(TemplateError::UnknownTag)?;
+        PhraseTemplate::new(slots).ok_or(TemplateError::Empty)
+    }
+}
+
+/// The templates a loose anagram search is restricted to, with the tags of each candidate
+pub(super) struct TemplateFilter {
+    templates: Vec<PhraseTemplate>,
+    // tags of each candidate, in candidate order
+    candidate_tags: Vec<PosTags>,
+    // number of words in the longest template
+    max_len: usize
+}
+
+impl TemplateFilter {
+    fn tags_of<'t>(&'t self, words: &'t [u32]) -> impl ExactSizeIterator<Item = PosTags> + 't {
+        words
+            .iter()
+            .map(|index| self.candidate_tags[*index as usize])
+    }
+
+    /// Returns the templates, in the order they were given
+    pub(super) fn templates(&self) -> &[PhraseTemplate] {
+        &self.templates
+    }
+
+    /// Returns true if `words` could begin a phrase matching one of the templates
+    pub(super) fn allows_prefix(&self, words: &[u32]) -> bool {
+        self.templates
+            .iter()
+            .any(|template| template.matches_prefix(self.tags_of(words)))
+    }
+
+    /// Returns true if `words` form a phrase matching one of the templates
+    pub(super) fn allows_phrase(&self, words: &[u32]) -> bool {
+        self.templates.iter().any(|template| {
+            template.len() == words.len() && template.matches_prefix(self.tags_of(words))
+        })
+    }
+
+    /// Returns true if phrases matching the templates can have more words than `words`
+    pub(super) fn allows_more_words(&self, words: &[u32]) -> bool {
+        words.len() < self.max_len
+    }
+}
+
+impl<'a> LooseAnagramsIterator<'a> {
+    /// Only returns loose anagrams matching one of `templates`, returning the modified iterator
+    ///
+    /// A loose anagram matches a template if it has as many words as the template has slots,
+    /// and each word has a part of speech (according to `wordlist`) allowed by its slot.
+    /// `wordlist` should be the wordlist this search was started with (or a [TaggedWordlist]
+    /// with the same words); words it has no tags for never match.
+    ///
+    /// Templates are saved in checkpoints (see
+    /// [LooseAnagramsCheckpoint::templates](super::LooseAnagramsCheckpoint::templates)), but
+    /// the tags aren't, so searches with templates are resumed with
+    /// [resume_loose_anagrams_with_templates](super::resume_loose_anagrams_with_templates).
+    ///
+    ///# Examples
+    /// ```
+    /// use anagrambot::anagram::{find_loose_anagrams, PhraseTemplate};
+    /// use anagrambot::wordlist::TaggedWordList;
+    ///
+    /// let wordlist: TaggedWordList = [
+    ///     ("race", "NOUN,VERB"), ("car", "NOUN"), ("care", "NOUN,VERB"),
+    ///     ("acre", "NOUN"), ("arc", "NOUN,VERB")
+    /// ]
+    ///     .into_iter()
+    ///     .map(|(word, tags)| (String::from(word), tags.parse().unwrap()))
+    ///     .collect();
+    ///
+    /// let templates: Vec<PhraseTemplate> = vec!["VERB NOUN".parse().unwrap()];
+    /// let mut loose_anagrams: Vec<String> = find_loose_anagrams("racecar", &wordlist, 1, true)
+    ///     .with_templates(&templates, &wordlist)
+    ///     .collect();
+    /// loose_anagrams.sort();
+    ///
+    /// assert_eq!(loose_anagrams, vec![
+    ///     "arc acre", "arc care", "arc race", "care arc", "care car", "race arc", "race car"
+    /// ]);
+    /// ```
+    pub fn with_templates<T>(mut self, templates: &[PhraseTemplate], wordlist: &T) -> Self
+    where
+        T: TaggedWordlist + ?Sized
+    {
+        let filter = TemplateFilter {
+            templates: templates.to_vec(),
+            candidate_tags: self
+                .candidates
+                .iter()
+                .map(|(word, _)| wordlist.tags(word))
+                .collect(),
+            max_len: templates.iter().map(PhraseTemplate::len).max().unwrap_or(0)
+        };
+        self.words_to_try
+            .retain(|(word_vec, _)| filter.allows_prefix(word_vec));
+        self.templates = Some(Arc::new(filter));
+        self
+    }
+}
+
+#[cfg(test)]
+mod template_tests {
+    use super::PhraseTemplate;
+    use crate::{anagram::find_loose_anagrams, wordlist::TaggedWordList};
+
+    #[test]
+    fn test_templates_filter_results() {
+        let wordlist: TaggedWordList = [
+            ("race", "NOUN,VERB"),
+            ("car", "NOUN"),
+            ("care", "NOUN,VERB"),
+            ("acre", "NOUN"),
+            ("arc", "NOUN,VERB"),
+            ("a", "DET"),
+            ("err", "VERB"),
+            ("cc", "")
+        ]
+        .into_iter()
+        .map(|(word, tags)| (String::from(word), tags.parse().unwrap()))
+        .collect();
+        let templates: Vec<PhraseTemplate> = ["NOUN NOUN", "VERB|DET NOUN VERB"]
+            .into_iter()
+            .map(|template| template.parse().unwrap())
+            .collect();
+
+        for target in ["racecar", "race car", "racecarerr"] {
+            let mut expected: Vec<String> = find_loose_anagrams(target, &wordlist, 1, true)
+                .filter(|anagram| {
+                    let tags: Vec<&str> = anagram
+                        .split(' ')
+                        .map(|word| match word {
+                            "car" | "acre" => "N",
+                            "race" | "care" | "arc" => "NV",
+                            "a" => "D",
+                            "err" => "V",
+                            _ => ""
+                        })
+                        .collect();
+                    match tags.as_slice() {
+                        [first, second] => first.contains('N') && second.contains('N'),
+                        [first, second, third] => {
+                            (first.contains('V') || first.contains('D'))
+                                && second.contains('N')
+                                && third.contains('V')
+                        }
+                        _ => false
+                    }
+                })
+                .collect();
+            let mut found: Vec<String> = find_loose_anagrams(target, &wordlist, 1, true)
+                .with_templates(&templates, &wordlist)
+                .collect();
+            expected.sort();
+            found.sort();
+            assert_eq!(found, expected);
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! Finding proper anagrams of many words at once
+//!
+//! [find_proper_anagrams](super::find_proper_anagrams) scans the whole wordlist for every
+//! word it is called with. When proper anagrams of many words are needed, the functions in
+//! this module scan the wordlist only once, checking each word in the wordlist against all
+//! target words at the same time.
+
+use super::{get_charcount_map, Charmap, Wordlist};
+
+use std::collections::{HashMap, HashSet};
+
+/// The proper anagrams of a single target word, as returned by [find_proper_anagrams_batch]
+///
+/// The first member is the target word, the second is its proper anagrams in wordlist order.
+pub type ProperAnagramsBatchResult<'a, 'b> = (&'b str, Vec<&'a str>);
+
+/// Target words grouped by the characters they contain
+struct BatchTargets<'b> {
+    targets: Vec<&'b str>,
+    // indices into targets for each distinct charmap
+    targets_by_charmap: HashMap<Charmap, Vec<usize>>,
+    // byte lengths of the targets, used to skip words quickly when case sensitive
+    target_lengths: HashSet<usize>,
+    case_sensitive: bool
+}
+
+impl<'b> BatchTargets<'b> {
+    fn new(targets: impl IntoIterator<Item = &'b str>, case_sensitive: bool) -> Self {
+        let targets: Vec<&str> = targets.into_iter().collect();
+        let mut targets_by_charmap: HashMap<Charmap, Vec<usize>> = HashMap::new();
+        for (index, target) in targets.iter().enumerate() {
+            targets_by_charmap
+                .entry(get_charcount_map(target, false, case_sensitive))
+                .or_default()
+                .push(index);
+        }
+        let target_lengths = targets.iter().map(|target| target.len()).collect();
+
+        Self {
+            targets,
+            targets_by_charmap,
+            target_lengths,
+            case_sensitive
+        }
+    }
+
+    /// Returns the indices of the targets that `word` is a proper anagram of
+    fn matches<'t>(&'t self, word: &'t str) -> impl Iterator<Item = usize> + 't {
+        // when case sensitive, words can only be anagrams if their byte lengths match
+        // (see are_anagrams_internal)
+        let could_match = !self.case_sensitive || self.target_lengths.contains(&word.len());
+        could_match
+            .then(|| {
+                self.targets_by_charmap
+                    .get(&get_charcount_map(word, false, self.case_sensitive))
+            })
+            .flatten()
+            .into_iter()
+            .flatten()
+            .copied()
+            // two identical words are not anagrams
+            .filter(move |index| self.targets[*index] != word)
+    }
+
+    /// Creates an empty result for every target
+    fn empty_results<'a>(&self) -> Vec<ProperAnagramsBatchResult<'a, 'b>> {
+        self.targets
+            .iter()
+            .map(|target| (*target, Vec::new()))
+            .collect()
+    }
+}
+
+/// Returns the proper anagrams of every word in `targets`, scanning `wordlist` only once
+///
+/// The result contains one entry for each target word, in the same order as `targets`.
+/// Each entry contains the proper anagrams of its target in the order they appear in `wordlist`;
+/// that is, the same anagrams that [find_proper_anagrams](super::find_proper_anagrams)
+/// would return for that target.
+///
+/// Like [find_proper_anagrams](super::find_proper_anagrams), this does not check if
+/// the target words are present in `wordlist`.
+///
+///# Examples
+/// ```
+/// use anagrambot::anagram::find_proper_anagrams_batch;
+/// use anagrambot::wordlist::BorrowedWordList;
+///
+/// const CASE_SENSITIVE: bool = true;
+///
+/// const TEST_WORD_SET: [&str; 6] = ["aster", "taser", "tears", "race", "care", "cow"];
+/// let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+///
+/// let results = find_proper_anagrams_batch(["tears", "race", "cow"], &wordlist, CASE_SENSITIVE);
+///
+/// assert_eq!(results, vec![
+///     ("tears", vec!["aster", "taser"]),
+///     ("race", vec!["care"]),
+///     ("cow", vec![])
+/// ]);
+/// ```
+pub fn find_proper_anagrams_batch<'a, 'b, T>(
+    targets: impl IntoIterator<Item = &'b str>,
+    wordlist: &'a T,
+    case_sensitive: bool
+) -> Vec<ProperAnagramsBatchResult<'a, 'b>>
+where
+    T: Wordlist + ?Sized
+{
+    let targets = BatchTargets::new(targets, case_sensitive);
+    let mut results = targets.empty_results();
+
+    for word in wordlist.iter() {
+        for index in targets.matches(word) {
+            results[index].1.push(word);
+        }
+    }
+
+    results
+}
+
+/// Like [find_proper_anagrams_batch], but scans the wordlist using multiple threads
+///
+/// The results are identical to those of [find_proper_anagrams_batch].
+/// The scan runs on the current [rayon] thread pool.
+///
+/// Requires the `parallel` feature.
+#[cfg(feature = "parallel")]
+pub fn par_find_proper_anagrams_batch<'a, 'b, T>(
+    targets: impl IntoIterator<Item = &'b str>,
+    wordlist: &'a T,
+    case_sensitive: bool
+) -> Vec<ProperAnagramsBatchResult<'a, 'b>>
+where
+    T: Wordlist + ?Sized
+{
+    use rayon::prelude::*;
+
+    let targets = BatchTargets::new(targets, case_sensitive);
+    let mut results = targets.empty_results();
+
+    let words: Vec<&str> = wordlist.iter().collect();
+    // collecting an indexed parallel iterator keeps the matches in wordlist order
+    let matches: Vec<(&str, Vec<usize>)> = words
+        .par_iter()
+        .filter_map(|word| {
+            let matching_targets: Vec<usize> = targets.matches(word).collect();
+            (!matching_targets.is_empty()).then_some((*word, matching_targets))
+        })
+        .collect();
+
+    for (word, matching_targets) in matches {
+        for index in matching_targets {
+            results[index].1.push(word);
+        }
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod proper_batch_tests {
+    use super::find_proper_anagrams_batch;
+    use crate::{anagram::find_proper_anagrams, wordlist::BorrowedWordList};
+
+    const TEST_WORD_SET: [&str; 9] = [
+        "aster", "taser", "tears", "Tears", "race", "care", "acre", "cow", "Race"
+    ];
+    const TARGETS: [&str; 5] = ["tears", "race", "race", "RACE", "nothing"];
+
+    #[test]
+    fn test_matches_find_proper_anagrams() {
+        let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+        for case_sensitive in [true, false] {
+            let results = find_proper_anagrams_batch(TARGETS, &wordlist, case_sensitive);
+            assert_eq!(results.len(), TARGETS.len());
+            for (target, (result_target, anagrams)) in TARGETS.iter().zip(results) {
+                let expected: Vec<&str> =
+                    find_proper_anagrams(target, &wordlist, case_sensitive).collect();
+                assert_eq!(*target, result_target);
+                assert_eq!(anagrams, expected);
+            }
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_matches_sequential() {
+        use super::par_find_proper_anagrams_batch;
+
+        let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+        for case_sensitive in [true, false] {
+            assert_eq!(
+                find_proper_anagrams_batch(TARGETS, &wordlist, case_sensitive),
+                par_find_proper_anagrams_batch(TARGETS, &wordlist, case_sensitive)
+            );
+        }
+    }
+}
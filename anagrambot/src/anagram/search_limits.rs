@@ -0,0 +1,157 @@
+//! Time limits and cancellation for anagram searches
+//!
+//! Finding standard or loose anagrams of a long word can take an extremely long time.
+//! The anagram iterators in this crate accept [SearchLimits] (via their `with_limits`
+//! methods) that are checked between steps of the search. Once a limit is reached, the
+//! iterator stops returning anagrams and reports why through its `truncation` method.
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc
+    },
+    time::{Duration, Instant}
+};
+
+/// A handle used to cancel a running anagram search
+///
+/// Clones of a `CancellationToken` share the same state, so a clone can be moved to
+/// another thread (or a signal handler) and used to cancel a search in progress.
+///
+///# Examples
+/// ```
+/// use anagrambot::anagram::{find_anagrams, CancellationToken, SearchLimits, Truncation};
+///
+/// let token = CancellationToken::new();
+/// let mut anagrams = find_anagrams("anagram")
+///     .with_limits(SearchLimits::new().with_cancellation(token.clone()));
+///
+/// assert!(anagrams.next().is_some());
+/// token.cancel();
+/// assert_eq!(anagrams.next(), None);
+/// assert_eq!(anagrams.truncation(), Some(Truncation::Cancelled));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>
+}
+
+impl CancellationToken {
+    /// Creates a new token that has not been cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels every search using this token (or any of its clones)
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if [cancel](CancellationToken::cancel) has been called
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Conditions under which an anagram search stops before it is exhausted
+///
+/// The default `SearchLimits` never stops a search early.
+#[derive(Debug, Clone, Default)]
+pub struct SearchLimits {
+    deadline: Option<Instant>,
+    cancellation: Option<CancellationToken>
+}
+
+impl SearchLimits {
+    /// Creates limits that never stop a search early
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stops the search once `deadline` has passed
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Stops the search once `timeout` has elapsed, measured from when this method is called
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.with_deadline(deadline),
+            // a timeout too large to represent will never be reached
+            None => self
+        }
+    }
+
+    /// Stops the search once `token` is cancelled
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Returns the reason the search should stop, or `None` if it may continue
+    ///
+    /// Cancellation takes priority over the deadline if both apply.
+    pub fn check(&self) -> Option<Truncation> {
+        if let Some(token) = &self.cancellation {
+            if token.is_cancelled() {
+                return Some(Truncation::Cancelled);
+            }
+        }
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => Some(Truncation::TimedOut),
+            _ => None
+        }
+    }
+}
+
+/// The reason an anagram search stopped before finding every anagram
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Truncation {
+    /// The deadline set through [SearchLimits] passed
+    TimedOut,
+    /// The [CancellationToken] set through [SearchLimits] was cancelled
+    Cancelled
+}
+
+impl fmt::Display for Truncation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Truncation::TimedOut => write!(f, "timed out"),
+            Truncation::Cancelled => write!(f, "cancelled")
+        }
+    }
+}
+
+#[cfg(test)]
+mod search_limits_tests {
+    use super::{CancellationToken, SearchLimits, Truncation};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_default_never_truncates() {
+        assert_eq!(SearchLimits::new().check(), None);
+        assert_eq!(
+            SearchLimits::new().with_timeout(Duration::MAX).check(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_deadline() {
+        let limits = SearchLimits::new().with_deadline(Instant::now());
+        assert_eq!(limits.check(), Some(Truncation::TimedOut));
+    }
+
+    #[test]
+    fn test_cancellation_takes_priority() {
+        let token = CancellationToken::new();
+        let limits = SearchLimits::new()
+            .with_deadline(Instant::now())
+            .with_cancellation(token.clone());
+        assert_eq!(limits.check(), Some(Truncation::TimedOut));
+        token.cancel();
+        assert_eq!(limits.check(), Some(Truncation::Cancelled));
+    }
+}
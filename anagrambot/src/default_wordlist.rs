@@ -10,21 +10,35 @@
 //! in source distributions of the anagrambot project as `WORDLIST-LICENSE` or can be viewed
 //! [online](http://changelogs.ubuntu.com/changelogs/pool/main/s/scowl/scowl_2020.12.07-2/copyright).
 
-use crate::wordlist::BorrowedWordList;
+use crate::wordlist::{BorrowedWordList, IndexedWordList, WordlistLoadOptions};
 
-/// Returns the default wordlist content as a string literal, if present
+#[cfg(not(feature = "no-default-wordlist"))]
+use std::sync::OnceLock;
+
+/// Returns the default wordlist content (one word per line), if present
 ///
 /// If the project was built normally (i.e. without the `no-default-wordlist` feature),
 /// this function will return `Some` containing the wordlist content.
 ///
 /// If the project was built with the `no-default-wordlist` feature,
 /// this function will return `None`.
-pub const fn default_wordlist_content() -> Option<&'static str> {
+///
+/// Only the [index](default_indexed_wordlist) of the default wordlist is built into the
+/// library, so the content is rebuilt from it the first time this is called.
+pub fn default_wordlist_content() -> Option<&'static str> {
     #[cfg(feature = "no-default-wordlist")]
     return None;
 
     #[cfg(not(feature = "no-default-wordlist"))]
-    return Some(include_str!("../words.txt"));
+    {
+        static CONTENT: OnceLock<String> = OnceLock::new();
+
+        default_indexed_wordlist().map(|wordlist| {
+            CONTENT
+                .get_or_init(|| wordlist.words().collect::<Vec<&str>>().join("\n"))
+                .as_str()
+        })
+    }
 }
 
 /// Returns the default wordlist as a [BorrowedWordList], if present.
@@ -34,6 +48,92 @@ pub const fn default_wordlist_content() -> Option<&'static str> {
 ///
 /// If the project was built with the `no-default-wordlist` feature,
 /// this function will return `None`.
+///
+/// The words are borrowed from the [default index](default_indexed_wordlist), which is
+/// faster to search and doesn't need to collect the words into a new list.
 pub fn default_wordlist() -> Option<BorrowedWordList<'static>> {
-    default_wordlist_content().map(|wordlist_content| wordlist_content.lines().collect())
+    default_indexed_wordlist().map(|wordlist| wordlist.words().collect())
+}
+
+/// Returns the default wordlist as an [IndexedWordList], if present.
+///
+/// If the project was built normally (i.e. without the `no-default-wordlist` feature),
+/// this function will return `Some` containing the wordlist.
+///
+/// If the project was built with the `no-default-wordlist` feature,
+/// this function will return `None`.
+///
+/// The default wordlist is indexed when the library is built, so no words are parsed or
+/// copied at runtime. The index is checked (and decompressed, with the
+/// `compressed-default-wordlist` feature) the first time this is called; every other call
+/// is free. Checking if a word is in the default wordlist is a binary search, and its
+/// proper anagrams can be found directly with
+/// [words_with_signature](IndexedWordList::words_with_signature).
+pub fn default_indexed_wordlist() -> Option<IndexedWordList<'static>> {
+    #[cfg(feature = "no-default-wordlist")]
+    return None;
+
+    #[cfg(not(feature = "no-default-wordlist"))]
+    {
+        static WORDLIST: OnceLock<IndexedWordList<'static>> = OnceLock::new();
+
+        let wordlist = WORDLIST.get_or_init(|| {
+            IndexedWordList::from_bytes(default_index())
+                .expect("the default wordlist index is built with the library")
+        });
+        Some(*wordlist)
+    }
+}
+
+#[cfg(not(feature = "no-default-wordlist"))]
+static INDEX: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/default_wordlist.idx"));
+
+/// Returns the bytes of the default wordlist index
+#[cfg(all(
+    not(feature = "no-default-wordlist"),
+    not(feature = "compressed-default-wordlist")
+))]
+fn default_index() -> &'static [u8] {
+    INDEX
+}
+
+/// Returns the bytes of the default wordlist index, decompressing it the first time
+#[cfg(all(
+    not(feature = "no-default-wordlist"),
+    feature = "compressed-default-wordlist"
+))]
+fn default_index() -> &'static [u8] {
+    use std::io::Read;
+
+    static DECOMPRESSED: OnceLock<Vec<u8>> = OnceLock::new();
+    DECOMPRESSED.get_or_init(|| {
+        let mut index = Vec::new();
+        flate2::read::GzDecoder::new(INDEX)
+            .read_to_end(&mut index)
+            .expect("the default wordlist index is compressed with the library");
+        index
+    })
+}
+
+/// Returns the words of the default wordlist that `options` keep, if present
+///
+/// Returns `None` if the project was built with the `no-default-wordlist` feature.
+/// See [WordlistLoadOptions] for the available options.
+///
+///# Examples
+/// ```
+/// use anagrambot::default_wordlist::default_wordlist_with_options;
+/// use anagrambot::wordlist::{CharacterClass, WordlistLoadOptions, Wordlist};
+///
+/// let options = WordlistLoadOptions::new().with_excluded(CharacterClass::Apostrophe);
+/// if let Some(wordlist) = default_wordlist_with_options(&options) {
+///     assert!(wordlist.includes_word("cat"));
+///     assert!(!wordlist.includes_word("cat's"));
+/// }
+/// ```
+pub fn default_wordlist_with_options(
+    options: &WordlistLoadOptions
+) -> Option<BorrowedWordList<'static>> {
+    default_indexed_wordlist()
+        .map(|wordlist| BorrowedWordList::new(options.filter(wordlist.words())))
 }
@@ -1,27 +1,450 @@
 //! The `Wordlist` trait and some implementations
 
 use std::{
+    collections::{BTreeMap, HashMap, HashSet},
     fs,
     io::{self, BufRead, BufReader},
-    path::Path
+    path::Path,
+    sync::Arc
 };
 
+mod tagged;
+pub use tagged::{PartOfSpeech, PosTags, TaggedWordList, TaggedWordlist, UnknownTagError};
+
+mod load;
+pub use load::{
+    decompressed, open_decompressed, CharacterClass, Compression, Dedupe, Encoding, LoadReport,
+    SkippedLine, WordlistLoadOptions
+};
+
+mod index;
+use index::signature;
+pub use index::{
+    is_index, write_index, write_weighted_index, Fingerprint, IndexError, IndexFile,
+    IndexedWordList, IndexedWords
+};
+
+mod trie;
+pub use trie::TrieWordList;
+
+mod hunspell;
+pub use hunspell::{HunspellDictionary, HunspellOptions};
+
+mod combine;
+pub use combine::{
+    Difference, DifferenceIter, Filtered, FilteredIter, Intersection, IntersectionIter, Union,
+    UnionIter, WordFilter, WordPredicate
+};
+
+mod blocklist;
+pub use blocklist::Blocklist;
+
+mod case;
+pub use case::CaseFoldedWordlist;
+
+mod collections;
+
+mod stats;
+pub use stats::{FlaggedWord, WordProblem, WordlistStats};
+
+mod legacy;
+#[allow(deprecated)]
+pub use legacy::{Legacy, LegacyWordlist};
+
 /// A list of words
 ///
 /// A `Wordlist` is a list of words (each word being a `&str`).
-pub trait Wordlist<'a> {
+///
+/// Besides the wordlists in this module, `Wordlist` is implemented for `&[String]`, `&[&str]`,
+/// and for `HashSet`s and `BTreeSet`s of `String` or `&str`, so existing collections can be
+/// searched without copying them (a `Vec` is searched as a slice, with `&words[..]`). Sets use
+/// their own lookups, but a `HashSet` returns its words in an arbitrary order.
+///
+/// The iterator type is generic over the lifetime of the borrow of the wordlist, so generic code
+/// can take a plain `&impl Wordlist` and a wordlist can be shared as an `Arc<W>`, which also
+/// implements `Wordlist`. Code written for the old `Wordlist<'a>` trait can use
+/// [LegacyWordlist] while it is being ported.
+///
+///# Examples
+/// ```
+/// use anagrambot::anagram::find_proper_anagrams;
+/// use anagrambot::wordlist::Wordlist;
+/// use std::collections::HashSet;
+///
+/// let words: HashSet<String> = ["race", "care", "cow"].into_iter().map(String::from).collect();
+/// assert!(words.includes_word("care"));
+/// assert_eq!(find_proper_anagrams("acre", &words, true).count(), 2);
+///
+/// let slice: &[&str] = &["race", "care", "cow"];
+/// assert_eq!(find_proper_anagrams("acre", &slice, true).count(), 2);
+///
+/// let words: Vec<String> = vec![String::from("race"), String::from("care")];
+/// assert_eq!(find_proper_anagrams("acre", &words[..], true).count(), 2);
+/// ```
+pub trait Wordlist {
     /// The type of iterator that the `iter` method returns
     ///
-    /// Must be an [Iterator] yielding `&str`
-    type IterType: Iterator<Item = &'a str>;
+    /// Must be an [Iterator] yielding `&str`s that live as long as the borrow of the `Wordlist`
+    type IterType<'a>: Iterator<Item = &'a str>
+    where
+        Self: 'a;
 
     /// Returns an an iterator that returns all words
     ///
     /// Unlike the IntoIterator trait, does not consume the `Wordlist`
-    fn iter(&'a self) -> Self::IterType;
+    fn iter(&self) -> Self::IterType<'_>;
 
     /// Given a word, returns true if the word is contained within this `Wordlist`
     fn includes_word(&self, word: &str) -> bool;
+
+    /// Returns the word of this `Wordlist` that is equal to `word` ignoring case, if there is
+    /// one
+    ///
+    /// An exact match is preferred; otherwise the first matching word is returned. This finds
+    /// how a word is spelled in the wordlist, such as "Paris" for "paris".
+    ///
+    /// The default implementation looks at every word, so use a [CaseFoldedWordlist] to look up
+    /// many words.
+    ///
+    ///# Examples
+    /// ```
+    /// use anagrambot::wordlist::{BorrowedWordList, Wordlist};
+    ///
+    /// let wordlist: BorrowedWordList = ["Race", "care", "Care"].into_iter().collect();
+    /// assert_eq!(wordlist.canonical_spelling("RACE"), Some("Race"));
+    /// assert_eq!(wordlist.canonical_spelling("Care"), Some("Care"));
+    /// assert_eq!(wordlist.canonical_spelling("acre"), None);
+    /// ```
+    fn canonical_spelling(&self, word: &str) -> Option<&str> {
+        if self.includes_word(word) {
+            return self.iter().find(|candidate| *candidate == word);
+        }
+        let folded = fold_case(word);
+        self.iter().find(|candidate| fold_case(candidate) == folded)
+    }
+
+    /// Given a word, returns true if a word equal to it ignoring case is contained within this
+    /// `Wordlist`
+    fn includes_word_ignoring_case(&self, word: &str) -> bool {
+        self.includes_word(word) || self.canonical_spelling(word).is_some()
+    }
+
+    /// Like [includes_word](Wordlist::includes_word) if `case_sensitive` is true, and like
+    /// [includes_word_ignoring_case](Wordlist::includes_word_ignoring_case) otherwise
+    fn includes_word_with_case(&self, word: &str, case_sensitive: bool) -> bool {
+        if case_sensitive {
+            self.includes_word(word)
+        } else {
+            self.includes_word_ignoring_case(word)
+        }
+    }
+
+    /// Returns the number of words in this `Wordlist`
+    ///
+    /// This is the number of words [iter](Wordlist::iter) returns, including any duplicates.
+    /// The default implementation counts them.
+    fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Returns true if this `Wordlist` has no words
+    fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Returns every word with the same letters as `word` (ignoring spaces), in wordlist order
+    ///
+    /// Letters are compared case sensitively, and duplicates are kept. The proper anagrams of
+    /// `word` are among these words, so
+    /// [find_proper_anagrams](crate::anagram::find_proper_anagrams) only checks them when
+    /// searching case sensitively.
+    ///
+    /// The default implementation looks at every word; an [IndexedWordList] finds them with a
+    /// binary search instead (see [has_signature_lookup](Wordlist::has_signature_lookup)).
+    ///
+    ///# Examples
+    /// ```
+    /// use anagrambot::wordlist::{BorrowedWordList, Wordlist};
+    ///
+    /// let wordlist: BorrowedWordList = ["race", "car", "Care", "ac re"].into_iter().collect();
+    /// assert_eq!(wordlist.words_with_signature("acer"), vec!["race", "ac re"]);
+    /// ```
+    fn words_with_signature(&self, word: &str) -> Vec<&str> {
+        let target = signature(word);
+        self.iter()
+            .filter(|candidate| {
+                candidate.chars().filter(|letter| *letter != ' ').count() == target.len()
+                    && signature(candidate) == target
+            })
+            .collect()
+    }
+
+    /// Returns true if [words_with_signature](Wordlist::words_with_signature) finds the words
+    /// without looking at every word
+    ///
+    /// When this is false, [find_proper_anagrams](crate::anagram::find_proper_anagrams) checks
+    /// the words one at a time instead, so that it can stop as soon as enough anagrams are found.
+    /// Defaults to false; wordlists that override `words_with_signature` with a faster lookup
+    /// should return true.
+    ///
+    ///# Examples
+    /// ```
+    /// use anagrambot::wordlist::{write_index, BorrowedWordList, IndexedWordList, Wordlist};
+    ///
+    /// let wordlist: BorrowedWordList = ["race", "care"].into_iter().collect();
+    /// assert!(!wordlist.has_signature_lookup());
+    ///
+    /// let mut index = Vec::new();
+    /// write_index(&wordlist, &mut index).unwrap();
+    /// let indexed = IndexedWordList::from_bytes(&index).unwrap();
+    /// assert!(indexed.has_signature_lookup());
+    /// ```
+    fn has_signature_lookup(&self) -> bool {
+        false
+    }
+
+    /// Returns every distinct word that can be made from (some of) the letters in `letters`,
+    /// in wordlist order
+    ///
+    /// `letters` maps each available letter to the number of times it is available. Spaces in
+    /// words are ignored. If `case_sensitive` is false, the letters of each word are converted
+    /// to lowercase before being compared, so `letters` should only contain lowercase letters.
+    ///
+    /// Every loose anagram search starts by finding these words. The default implementation
+    /// looks at every word; a [TrieWordList] skips every word starting with a prefix that
+    /// doesn't fit instead.
+    ///
+    ///# Examples
+    /// ```
+    /// use anagrambot::wordlist::{BorrowedWordList, Wordlist};
+    /// use std::collections::BTreeMap;
+    ///
+    /// let wordlist: BorrowedWordList = ["race", "car", "Car", "cow", "car"].into_iter().collect();
+    /// let letters = BTreeMap::from([('a', 1), ('c', 1), ('e', 1), ('r', 1)]);
+    /// assert_eq!(wordlist.words_fitting(&letters, true), vec!["race", "car"]);
+    /// assert_eq!(wordlist.words_fitting(&letters, false), vec!["race", "car", "Car"]);
+    /// ```
+    fn words_fitting(&self, letters: &BTreeMap<char, u32>, case_sensitive: bool) -> Vec<&str> {
+        distinct(
+            self.iter()
+                .filter(|word| fits_into(word, letters, case_sensitive))
+        )
+    }
+
+    /// Returns every distinct word starting with `prefix`, in wordlist order
+    ///
+    /// The default implementation looks at every word; a [TrieWordList] only visits the words
+    /// below `prefix` in the trie.
+    ///
+    ///# Examples
+    /// ```
+    /// use anagrambot::wordlist::{BorrowedWordList, Wordlist};
+    ///
+    /// let wordlist: BorrowedWordList = ["race", "car", "racecar", "Race"].into_iter().collect();
+    /// assert_eq!(wordlist.words_with_prefix("rac"), vec!["race", "racecar"]);
+    /// ```
+    fn words_with_prefix(&self, prefix: &str) -> Vec<&str> {
+        distinct(self.iter().filter(|word| word.starts_with(prefix)))
+    }
+
+    /// Returns the [Fingerprint] of the words of this `Wordlist`, in order
+    ///
+    /// Two wordlists have the same fingerprint if they return the same words in the same order,
+    /// whatever their types. Search checkpoints record this fingerprint, since they depend on
+    /// the order of the words.
+    ///
+    /// The default implementation looks at every word; an [IndexedWordList] stores its
+    /// fingerprint instead. Implementations must return the same value as the default one.
+    ///
+    ///# Examples
+    /// ```
+    /// use anagrambot::wordlist::{BorrowedWordList, Wordlist};
+    ///
+    /// let wordlist: BorrowedWordList = ["race", "care"].into_iter().collect();
+    /// let words = vec![String::from("race"), String::from("care")];
+    /// assert_eq!(wordlist.fingerprint(), words.as_slice().fingerprint());
+    ///
+    /// let reversed: BorrowedWordList = ["care", "race"].into_iter().collect();
+    /// assert_ne!(wordlist.fingerprint(), reversed.fingerprint());
+    /// ```
+    fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::of_words(self.iter())
+    }
+
+    /// Returns a [Fingerprint] of the words of this `Wordlist` that doesn't depend on their order
+    ///
+    /// Two wordlists have the same unordered fingerprint if they have the same words, each
+    /// appearing the same number of times. Useful for data that only depends on which words a
+    /// wordlist has, or to compare a `HashSet` (whose order is arbitrary) with another wordlist.
+    ///
+    /// The default implementation looks at every word; an [IndexedWordList] stores its
+    /// fingerprint instead. Implementations must return the same value as the default one.
+    ///
+    ///# Examples
+    /// ```
+    /// use anagrambot::wordlist::{BorrowedWordList, Wordlist};
+    /// use std::collections::HashSet;
+    ///
+    /// let wordlist: BorrowedWordList = ["race", "care"].into_iter().collect();
+    /// let set: HashSet<&str> = ["care", "race"].into_iter().collect();
+    /// assert_eq!(wordlist.unordered_fingerprint(), set.unordered_fingerprint());
+    /// ```
+    fn unordered_fingerprint(&self) -> Fingerprint {
+        Fingerprint::of_words_unordered(self.iter())
+    }
+}
+
+/// Returns `word` in the form used to compare words ignoring case
+fn fold_case(word: &str) -> String {
+    word.to_lowercase()
+}
+
+/// Returns true if the letters of `word` (ignoring spaces) are all available in `letters`
+fn fits_into(word: &str, letters: &BTreeMap<char, u32>, case_sensitive: bool) -> bool {
+    let mut used: BTreeMap<char, u32> = BTreeMap::new();
+    let mut take = |letter| {
+        let count = used.entry(letter).or_insert(0);
+        *count += 1;
+        letters
+            .get(&letter)
+            .is_some_and(|available| *count <= *available)
+    };
+    word.chars().filter(|letter| *letter != ' ').all(|letter| {
+        if case_sensitive {
+            take(letter)
+        } else {
+            letter.to_lowercase().all(&mut take)
+        }
+    })
+}
+
+/// Returns the words of `words` without duplicates, keeping the first appearance of each
+fn distinct<'a>(words: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut seen: HashSet<&str> = HashSet::new();
+    words.filter(|word| seen.insert(word)).collect()
+}
+
+/// A reference to a `Wordlist` is a `Wordlist` too
+///
+/// This lets views such as [Union] and [Difference] combine borrowed wordlists.
+impl<W> Wordlist for &W
+where
+    W: Wordlist + ?Sized
+{
+    type IterType<'a>
+        = W::IterType<'a>
+    where
+        Self: 'a;
+
+    fn includes_word(&self, word: &str) -> bool {
+        (**self).includes_word(word)
+    }
+
+    fn iter(&self) -> Self::IterType<'_> {
+        (**self).iter()
+    }
+
+    fn canonical_spelling(&self, word: &str) -> Option<&str> {
+        (**self).canonical_spelling(word)
+    }
+
+    fn includes_word_ignoring_case(&self, word: &str) -> bool {
+        (**self).includes_word_ignoring_case(word)
+    }
+
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+
+    fn is_empty(&self) -> bool {
+        (**self).is_empty()
+    }
+
+    fn words_with_signature(&self, word: &str) -> Vec<&str> {
+        (**self).words_with_signature(word)
+    }
+
+    fn has_signature_lookup(&self) -> bool {
+        (**self).has_signature_lookup()
+    }
+
+    fn words_fitting(&self, letters: &BTreeMap<char, u32>, case_sensitive: bool) -> Vec<&str> {
+        (**self).words_fitting(letters, case_sensitive)
+    }
+
+    fn words_with_prefix(&self, prefix: &str) -> Vec<&str> {
+        (**self).words_with_prefix(prefix)
+    }
+
+    fn fingerprint(&self) -> Fingerprint {
+        (**self).fingerprint()
+    }
+
+    fn unordered_fingerprint(&self) -> Fingerprint {
+        (**self).unordered_fingerprint()
+    }
+}
+
+/// A `Wordlist` shared with an [Arc] is a `Wordlist` too
+///
+/// This lets a long-lived service (such as a bot answering requests on many threads) keep a
+/// single wordlist and search it from anywhere.
+impl<W> Wordlist for Arc<W>
+where
+    W: Wordlist + ?Sized
+{
+    type IterType<'a>
+        = W::IterType<'a>
+    where
+        Self: 'a;
+
+    fn includes_word(&self, word: &str) -> bool {
+        (**self).includes_word(word)
+    }
+
+    fn iter(&self) -> Self::IterType<'_> {
+        (**self).iter()
+    }
+
+    fn canonical_spelling(&self, word: &str) -> Option<&str> {
+        (**self).canonical_spelling(word)
+    }
+
+    fn includes_word_ignoring_case(&self, word: &str) -> bool {
+        (**self).includes_word_ignoring_case(word)
+    }
+
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+
+    fn is_empty(&self) -> bool {
+        (**self).is_empty()
+    }
+
+    fn words_with_signature(&self, word: &str) -> Vec<&str> {
+        (**self).words_with_signature(word)
+    }
+
+    fn has_signature_lookup(&self) -> bool {
+        (**self).has_signature_lookup()
+    }
+
+    fn words_fitting(&self, letters: &BTreeMap<char, u32>, case_sensitive: bool) -> Vec<&str> {
+        (**self).words_fitting(letters, case_sensitive)
+    }
+
+    fn words_with_prefix(&self, prefix: &str) -> Vec<&str> {
+        (**self).words_with_prefix(prefix)
+    }
+
+    fn fingerprint(&self) -> Fingerprint {
+        (**self).fingerprint()
+    }
+
+    fn unordered_fingerprint(&self) -> Fingerprint {
+        (**self).unordered_fingerprint()
+    }
 }
 
 /// A [Wordlist] implementor that borrows its words
@@ -41,27 +464,62 @@ impl<'a> BorrowedWordList<'a> {
     }
 }
 
+impl<'a> BorrowedWordList<'a> {
+    /// Construct a new `BorrowedWordList` from the lines of `text` that `options` keep
+    ///
+    /// See [WordlistLoadOptions] for the available options.
+    pub fn from_text_with_options(text: &'a str, options: &WordlistLoadOptions) -> Self {
+        Self::new(options.filter(text.lines()))
+    }
+}
+
 impl<'a> FromIterator<&'a str> for BorrowedWordList<'a> {
     fn from_iter<T: IntoIterator<Item = &'a str>>(iter: T) -> Self {
         Self::new(iter)
     }
 }
 
-impl<'a> Wordlist<'a> for BorrowedWordList<'a> {
-    type IterType = std::iter::Copied<std::slice::Iter<'a, &'a str>>;
+impl<'w> Wordlist for BorrowedWordList<'w> {
+    type IterType<'a>
+        = std::iter::Copied<std::slice::Iter<'a, &'a str>>
+    where
+        Self: 'a;
 
     fn includes_word(&self, word: &str) -> bool {
         self.word_vec.contains(&word)
     }
 
-    fn iter(&'a self) -> Self::IterType {
+    fn iter(&self) -> Self::IterType<'_> {
         self.word_vec.iter().copied()
     }
+
+    fn len(&self) -> usize {
+        self.word_vec.len()
+    }
 }
 
 #[cfg(test)]
 mod borrowedwordlist_tests {
-    use super::{BorrowedWordList, Wordlist};
+    use super::{BorrowedWordList, Fingerprint, Wordlist};
+
+    #[test]
+    fn test_fingerprints_are_stable() {
+        // these values must never change, since fingerprints are stored in files
+        let wordlist: BorrowedWordList = ["race", "care", "été"].into_iter().collect();
+        assert_eq!(wordlist.fingerprint().to_string(), "d28cf493bcc5df4c");
+        assert_eq!(
+            wordlist.unordered_fingerprint().to_string(),
+            "915c12c1fdddf021"
+        );
+
+        let empty = BorrowedWordList::new([]);
+        assert_eq!(empty.fingerprint().to_string(), "cbf29ce484222325");
+        assert_eq!(empty.unordered_fingerprint(), Fingerprint::from_u64(0));
+        assert_eq!(
+            "cbf29ce484222325".parse::<Fingerprint>(),
+            Ok(empty.fingerprint())
+        );
+    }
 
     #[test]
     fn test_includes_word() {
@@ -105,13 +563,19 @@ impl OwnedWordList {
     ///
     /// `word_file` must be a [Path] to a text file containing words.
     ///  
-    /// Each line of the text file is considered a single word.
+    /// Each line of the text file is considered a single word. Files compressed with gzip or
+    /// zstd are decompressed transparently (see [Compression]).
     pub fn from_file(word_file: &Path) -> io::Result<Self> {
-        let word_file = fs::File::open(word_file)?;
+        Self::from_reader(BufReader::new(fs::File::open(word_file)?))
+    }
 
+    /// Construct a new `OwnedWordList` from the lines read from `reader`
+    ///
+    /// Like [from_file](OwnedWordList::from_file), but for any reader, such as standard input.
+    pub fn from_reader(reader: impl BufRead) -> io::Result<Self> {
         let mut word_vec: Vec<String> = Vec::new();
 
-        let lines_iter = BufReader::new(word_file).lines();
+        let lines_iter = decompressed(reader)?.lines();
 
         for line in lines_iter {
             word_vec.push(line?);
@@ -119,6 +583,45 @@ impl OwnedWordList {
 
         Ok(Self::new(word_vec))
     }
+
+    /// Construct a new `OwnedWordList` from the lines of a text file that `options` keep
+    ///
+    /// See [WordlistLoadOptions] for the available options.
+    pub fn from_file_with_options(
+        word_file: &Path,
+        options: &WordlistLoadOptions
+    ) -> io::Result<Self> {
+        Self::from_file_with_report(word_file, options).map(|(wordlist, _)| wordlist)
+    }
+
+    /// Like [from_file_with_options](OwnedWordList::from_file_with_options), but also returns
+    /// a [LoadReport] listing the lines skipped in lenient mode
+    pub fn from_file_with_report(
+        word_file: &Path,
+        options: &WordlistLoadOptions
+    ) -> io::Result<(Self, LoadReport)> {
+        Self::from_reader_with_report(BufReader::new(fs::File::open(word_file)?), options)
+    }
+
+    /// Construct a new `OwnedWordList` from the lines read from `reader` that `options` keep
+    ///
+    /// Like [from_file_with_options](OwnedWordList::from_file_with_options), but for any reader.
+    pub fn from_reader_with_options(
+        reader: impl BufRead,
+        options: &WordlistLoadOptions
+    ) -> io::Result<Self> {
+        Self::from_reader_with_report(reader, options).map(|(wordlist, _)| wordlist)
+    }
+
+    /// Like [from_reader_with_options](OwnedWordList::from_reader_with_options), but also returns
+    /// a [LoadReport] listing the lines skipped in lenient mode
+    pub fn from_reader_with_report(
+        reader: impl BufRead,
+        options: &WordlistLoadOptions
+    ) -> io::Result<(Self, LoadReport)> {
+        let (word_vec, report) = options.read_with_report(reader)?;
+        Ok((Self::new(word_vec), report))
+    }
 }
 
 impl FromIterator<String> for OwnedWordList {
@@ -127,25 +630,29 @@ impl FromIterator<String> for OwnedWordList {
     }
 }
 
-impl<'a> Wordlist<'a> for OwnedWordList {
+impl Wordlist for OwnedWordList {
     // this long type has to be written out because impl trait syntax
     // cannot be used for associated types
-    type IterType = std::iter::Map<std::slice::Iter<'a, String>, fn(&String) -> &str>;
+    type IterType<'a> = std::iter::Map<std::slice::Iter<'a, String>, fn(&String) -> &str>;
 
     fn includes_word(&self, word: &str) -> bool {
         let word = String::from(word);
         self.word_vec.contains(&word)
     }
 
-    fn iter(&'a self) -> Self::IterType {
+    fn iter(&self) -> Self::IterType<'_> {
         self.word_vec.iter().map(|p| p.as_str())
     }
+
+    fn len(&self) -> usize {
+        self.word_vec.len()
+    }
 }
 
 #[cfg(test)]
 mod ownedwordlist_tests {
-    use super::{OwnedWordList, Wordlist};
-    use crate::default_wordlist::default_wordlist;
+    use super::{OwnedWordList, Wordlist, WordlistLoadOptions};
+    use crate::default_wordlist::{default_wordlist, default_wordlist_content};
     use std::path::Path;
 
     #[test]
@@ -175,6 +682,27 @@ mod ownedwordlist_tests {
         }
     }
 
+    #[test]
+    fn test_shared_in_arc() {
+        use crate::anagram::find_proper_anagrams;
+        use std::{sync::Arc, thread};
+
+        let list: Arc<OwnedWordList> = Arc::new(
+            ["race", "care", "cow"]
+                .map(String::from)
+                .into_iter()
+                .collect()
+        );
+        let shared = Arc::clone(&list);
+        let found = thread::spawn(move || find_proper_anagrams("acre", &shared, true).count())
+            .join()
+            .unwrap();
+
+        assert_eq!(found, 2);
+        assert_eq!(list.len(), 3);
+        assert!(list.iter().eq(["race", "care", "cow"]));
+    }
+
     #[test]
     fn test_default_vs_file() {
         let default_wordlist = match default_wordlist() {
@@ -189,5 +717,420 @@ mod ownedwordlist_tests {
         for (defword, ownedword) in default_wordlist.iter().zip(wordlist_from_file.iter()) {
             assert_eq!(defword, ownedword);
         }
+
+        let content = std::fs::read_to_string("words.txt").unwrap();
+        assert_eq!(
+            default_wordlist_content(),
+            Some(content.trim_end_matches('\n'))
+        );
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let text = "race\r\ncare\n\nacre\n";
+        let list = OwnedWordList::from_reader(text.as_bytes()).unwrap();
+        assert_eq!(
+            list.iter().collect::<Vec<&str>>(),
+            vec!["race", "care", "", "acre"]
+        );
+
+        let options = WordlistLoadOptions::cleaned();
+        let list = OwnedWordList::from_reader_with_options(text.as_bytes(), &options).unwrap();
+        assert_eq!(
+            list.iter().collect::<Vec<&str>>(),
+            vec!["race", "care", "acre"]
+        );
+    }
+}
+
+/// A [Wordlist] with a weight (such as a frequency count) for each word
+///
+/// Weights can be used to rank anagrams by how common their words are
+/// (see [FrequencyScorer](crate::anagram::FrequencyScorer)),
+/// or to leave out rare words entirely (see [with_min_weight](WeightedWordlist::with_min_weight)).
+pub trait WeightedWordlist: Wordlist {
+    /// Returns the weight of `word`, or `None` if the word isn't in this `Wordlist`
+    fn weight(&self, word: &str) -> Option<u64>;
+
+    /// Returns the sum of the weights of every word
+    fn total_weight(&self) -> u64 {
+        self.iter()
+            .filter_map(|word| self.weight(word))
+            .fold(0, u64::saturating_add)
+    }
+
+    /// Sorts `words` from highest to lowest weight
+    ///
+    /// Words with equal weights keep their order, and words that aren't in this `Wordlist`
+    /// are placed last.
+    ///
+    ///# Examples
+    /// ```
+    /// use anagrambot::wordlist::{FrequencyWordList, WeightedWordlist};
+    ///
+    /// let wordlist: FrequencyWordList = [("acre", 10), ("care", 500), ("race", 300)]
+    ///     .into_iter()
+    ///     .map(|(word, count)| (String::from(word), count))
+    ///     .collect();
+    ///
+    /// let mut words = ["acre", "race", "care"];
+    /// wordlist.sort_by_weight(&mut words);
+    /// assert_eq!(words, ["care", "race", "acre"]);
+    /// ```
+    fn sort_by_weight(&self, words: &mut [&str]) {
+        words.sort_by_key(|word| std::cmp::Reverse(self.weight(word)));
+    }
+
+    /// Returns a view of this `Wordlist` containing only the words with at least `min_weight`
+    ///
+    /// The view can be passed to any anagram search to leave out rare words.
+    ///
+    ///# Examples
+    /// ```
+    /// use anagrambot::anagram::find_proper_anagrams;
+    /// use anagrambot::wordlist::{FrequencyWordList, WeightedWordlist};
+    ///
+    /// let wordlist: FrequencyWordList = [("acre", 10), ("care", 500), ("race", 300)]
+    ///     .into_iter()
+    ///     .map(|(word, count)| (String::from(word), count))
+    ///     .collect();
+    ///
+    /// let common_words = wordlist.with_min_weight(100);
+    /// let anagrams: Vec<&str> = find_proper_anagrams("race", &common_words, true).collect();
+    /// assert_eq!(anagrams, vec!["care"]);
+    /// ```
+    fn with_min_weight(&self, min_weight: u64) -> MinWeightWordlist<'_, Self>
+    where
+        Self: Sized
+    {
+        MinWeightWordlist {
+            wordlist: self,
+            min_weight
+        }
+    }
+}
+
+impl<W> WeightedWordlist for &W
+where
+    W: WeightedWordlist + ?Sized
+{
+    fn weight(&self, word: &str) -> Option<u64> {
+        (**self).weight(word)
+    }
+}
+
+impl<W> WeightedWordlist for Arc<W>
+where
+    W: WeightedWordlist + ?Sized
+{
+    fn weight(&self, word: &str) -> Option<u64> {
+        (**self).weight(word)
+    }
+}
+
+/// A [WeightedWordlist] implementor that owns its words and their frequencies
+///
+/// Frequency lists are commonly stored as text files with one word and its count per line,
+/// separated by a tab; [from_file](FrequencyWordList::from_file) reads this format.
+pub struct FrequencyWordList {
+    word_vec: Vec<String>,
+    frequencies: HashMap<String, u64>
+}
+
+impl FrequencyWordList {
+    /// Construct a new `FrequencyWordList` from an iterator of words and their frequencies
+    ///
+    /// If a word appears more than once, its frequencies are added together and
+    /// the word keeps the position of its first appearance.
+    pub fn new(word_iter: impl IntoIterator<Item = (String, u64)>) -> Self {
+        let mut word_vec = Vec::new();
+        let mut frequencies: HashMap<String, u64> = HashMap::new();
+        for (word, frequency) in word_iter {
+            match frequencies.get_mut(&word) {
+                Some(existing) => *existing = existing.saturating_add(frequency),
+                None => {
+                    frequencies.insert(word.clone(), frequency);
+                    word_vec.push(word);
+                }
+            }
+        }
+        Self {
+            word_vec,
+            frequencies
+        }
+    }
+
+    /// Construct a new `FrequencyWordList` from the contents of a text file
+    ///
+    /// `word_file` must be a [Path] to a text file containing words and their frequencies.
+    ///
+    /// Each line of the text file must contain a single word, a tab, and the word's frequency
+    /// (a whole number). Returns an error of kind [InvalidData](io::ErrorKind::InvalidData)
+    /// if a line is not in this format.
+    pub fn from_file(word_file: &Path) -> io::Result<Self> {
+        Self::from_file_with_options(word_file, &WordlistLoadOptions::new())
+    }
+
+    /// Construct a new `FrequencyWordList` from the lines of a text file that `options` keep
+    ///
+    /// Like [from_file](FrequencyWordList::from_file), but blank lines and comments can be
+    /// skipped, and options that leave out words apply to the word on each line.
+    /// See [WordlistLoadOptions] for the available options.
+    pub fn from_file_with_options(
+        word_file: &Path,
+        options: &WordlistLoadOptions
+    ) -> io::Result<Self> {
+        Self::from_file_with_report(word_file, options).map(|(wordlist, _)| wordlist)
+    }
+
+    /// Like [from_file_with_options](FrequencyWordList::from_file_with_options), but also
+    /// returns a [LoadReport] listing the lines skipped in lenient mode
+    pub fn from_file_with_report(
+        word_file: &Path,
+        options: &WordlistLoadOptions
+    ) -> io::Result<(Self, LoadReport)> {
+        Self::from_reader_with_report(BufReader::new(fs::File::open(word_file)?), options)
+    }
+
+    /// Construct a new `FrequencyWordList` from the lines read from `reader`
+    ///
+    /// Like [from_file](FrequencyWordList::from_file), but for any reader, such as standard input.
+    pub fn from_reader(reader: impl BufRead) -> io::Result<Self> {
+        Self::from_reader_with_options(reader, &WordlistLoadOptions::new())
+    }
+
+    /// Construct a new `FrequencyWordList` from the lines read from `reader` that `options` keep
+    ///
+    /// Like [from_file_with_options](FrequencyWordList::from_file_with_options), but for any reader.
+    pub fn from_reader_with_options(
+        reader: impl BufRead,
+        options: &WordlistLoadOptions
+    ) -> io::Result<Self> {
+        Self::from_reader_with_report(reader, options).map(|(wordlist, _)| wordlist)
+    }
+
+    /// Like [from_reader_with_options](FrequencyWordList::from_reader_with_options), but also returns
+    /// a [LoadReport] listing the lines skipped in lenient mode
+    pub fn from_reader_with_report(
+        reader: impl BufRead,
+        options: &WordlistLoadOptions
+    ) -> io::Result<(Self, LoadReport)> {
+        let mut word_vec: Vec<(String, u64)> = Vec::new();
+
+        let report = options.read_lines(reader, |filter, line| {
+            let (word, frequency) = line
+                .rsplit_once('\t')
+                .ok_or("expected a word and a frequency separated by a tab")?;
+            let frequency = frequency
+                .trim()
+                .parse()
+                .map_err(|_| "frequency is not a whole number")?;
+            let word = filter.trim(word);
+            if filter.accepts_word(word) {
+                word_vec.push((String::from(word), frequency));
+            }
+            Ok(())
+        })?;
+
+        Ok((Self::new(word_vec), report))
+    }
+}
+
+impl FromIterator<(String, u64)> for FrequencyWordList {
+    fn from_iter<T: IntoIterator<Item = (String, u64)>>(iter: T) -> Self {
+        FrequencyWordList::new(iter)
+    }
+}
+
+impl Wordlist for FrequencyWordList {
+    type IterType<'a> = std::iter::Map<std::slice::Iter<'a, String>, fn(&String) -> &str>;
+
+    fn includes_word(&self, word: &str) -> bool {
+        self.frequencies.contains_key(word)
+    }
+
+    fn iter(&self) -> Self::IterType<'_> {
+        self.word_vec.iter().map(|p| p.as_str())
+    }
+
+    fn len(&self) -> usize {
+        self.word_vec.len()
+    }
+}
+
+impl WeightedWordlist for FrequencyWordList {
+    fn weight(&self, word: &str) -> Option<u64> {
+        self.frequencies.get(word).copied()
+    }
+}
+
+/// A view of a [WeightedWordlist] that only contains the words with at least a minimum weight
+///
+/// The return value of [WeightedWordlist::with_min_weight]
+pub struct MinWeightWordlist<'w, W> {
+    wordlist: &'w W,
+    min_weight: u64
+}
+
+impl<W> MinWeightWordlist<'_, W>
+where
+    W: WeightedWordlist
+{
+    fn is_heavy_enough(&self, word: &str) -> bool {
+        is_heavy_enough(self.wordlist, self.min_weight, word)
+    }
+}
+
+/// Returns true if `word` has at least `min_weight` in `wordlist`
+fn is_heavy_enough<W>(wordlist: &W, min_weight: u64, word: &str) -> bool
+where
+    W: WeightedWordlist
+{
+    wordlist
+        .weight(word)
+        .is_some_and(|weight| weight >= min_weight)
+}
+
+impl<W> Wordlist for MinWeightWordlist<'_, W>
+where
+    W: WeightedWordlist
+{
+    type IterType<'a>
+        = MinWeightIter<'a, W>
+    where
+        Self: 'a;
+
+    fn includes_word(&self, word: &str) -> bool {
+        self.is_heavy_enough(word)
+    }
+
+    fn iter(&self) -> Self::IterType<'_> {
+        MinWeightIter {
+            word_iter: self.wordlist.iter(),
+            wordlist: self.wordlist,
+            min_weight: self.min_weight
+        }
+    }
+
+    fn words_with_signature(&self, word: &str) -> Vec<&str> {
+        let mut words = self.wordlist.words_with_signature(word);
+        words.retain(|word| self.is_heavy_enough(word));
+        words
+    }
+
+    fn has_signature_lookup(&self) -> bool {
+        self.wordlist.has_signature_lookup()
+    }
+
+    fn words_fitting(&self, letters: &BTreeMap<char, u32>, case_sensitive: bool) -> Vec<&str> {
+        let mut words = self.wordlist.words_fitting(letters, case_sensitive);
+        words.retain(|word| self.is_heavy_enough(word));
+        words
+    }
+
+    fn words_with_prefix(&self, prefix: &str) -> Vec<&str> {
+        let mut words = self.wordlist.words_with_prefix(prefix);
+        words.retain(|word| self.is_heavy_enough(word));
+        words
+    }
+}
+
+impl<W> WeightedWordlist for MinWeightWordlist<'_, W>
+where
+    W: WeightedWordlist
+{
+    fn weight(&self, word: &str) -> Option<u64> {
+        self.wordlist
+            .weight(word)
+            .filter(|weight| *weight >= self.min_weight)
+    }
+}
+
+/// The iterator type of [MinWeightWordlist]
+pub struct MinWeightIter<'a, W>
+where
+    W: WeightedWordlist + 'a
+{
+    word_iter: W::IterType<'a>,
+    wordlist: &'a W,
+    min_weight: u64
+}
+
+impl<'a, W> Iterator for MinWeightIter<'a, W>
+where
+    W: WeightedWordlist + 'a
+{
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (wordlist, min_weight) = (self.wordlist, self.min_weight);
+        self.word_iter
+            .find(|word| is_heavy_enough(wordlist, min_weight, word))
+    }
+}
+
+#[cfg(test)]
+mod frequencywordlist_tests {
+    use super::{FrequencyWordList, WeightedWordlist, Wordlist, WordlistLoadOptions};
+    use std::{fs, io};
+
+    fn test_wordlist() -> FrequencyWordList {
+        [("race", 300), ("care", 500), ("acre", 10), ("race", 5)]
+            .into_iter()
+            .map(|(word, count)| (String::from(word), count))
+            .collect()
+    }
+
+    #[test]
+    fn test_duplicates_are_summed() {
+        let list = test_wordlist();
+        let words: Vec<&str> = list.iter().collect();
+        assert_eq!(words, vec!["race", "care", "acre"]);
+        assert_eq!(list.weight("race"), Some(305));
+        assert_eq!(list.weight("cow"), None);
+        assert_eq!(list.total_weight(), 815);
+    }
+
+    #[test]
+    fn test_min_weight() {
+        let list = test_wordlist();
+        let view = list.with_min_weight(300);
+        let words: Vec<&str> = view.iter().collect();
+        assert_eq!(words, vec!["race", "care"]);
+        assert!(view.includes_word("care"));
+        assert!(!view.includes_word("acre"));
+        assert_eq!(view.weight("acre"), None);
+    }
+
+    #[test]
+    fn test_from_file() {
+        let dir = std::env::temp_dir().join(format!("anagrambot-freq-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let good_path = dir.join("good.tsv");
+        fs::write(&good_path, "race\t300\ncare\t500\n").unwrap();
+        let list = FrequencyWordList::from_file(&good_path).unwrap();
+        assert_eq!(list.weight("care"), Some(500));
+
+        let bad_path = dir.join("bad.tsv");
+        fs::write(&bad_path, "race\t300\ncare\n").unwrap();
+        let err = FrequencyWordList::from_file(&bad_path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().starts_with("line 2"));
+
+        let options = WordlistLoadOptions::new().with_lenient(true);
+        let (list, report) = FrequencyWordList::from_file_with_report(&bad_path, &options).unwrap();
+        assert_eq!(list.iter().collect::<Vec<&str>>(), vec!["race"]);
+        assert_eq!(report.skipped().len(), 1);
+        assert_eq!(report.skipped()[0].line_number(), 2);
+
+        let repeated_path = dir.join("repeated.tsv");
+        fs::write(&repeated_path, "race\t300\ncare\t500\nrace\t5\n").unwrap();
+        let options = WordlistLoadOptions::cleaned();
+        let list = FrequencyWordList::from_file_with_options(&repeated_path, &options).unwrap();
+        assert_eq!(list.iter().collect::<Vec<&str>>(), vec!["race", "care"]);
+        assert_eq!(list.weight("race"), Some(305));
+
+        fs::remove_dir_all(&dir).unwrap();
     }
 }
@@ -0,0 +1,179 @@
+//! Words that must never appear in anagrams
+
+use super::{Filtered, WordPredicate, WordlistLoadOptions};
+
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, BufRead, BufReader},
+    path::Path
+};
+
+/// The text of the blocklist shipped with anagrambot
+const DEFAULT_BLOCKLIST: &str = include_str!("../../blocklist.txt");
+
+/// A list of words (such as slurs) that must never appear in anagrams
+///
+/// A `Blocklist` holds exact words, which block a word equal to them (or to them followed by
+/// `'s`), and patterns, which block every word containing them. Both are matched ignoring case.
+///
+/// Blocked words are kept out of anagram searches by searching a view of the wordlist that
+/// leaves them out (see [without_blocked](Blocklist::without_blocked)). Loose anagrams
+/// can still spell a pattern across the words of a phrase, so phrases should also be checked
+/// with [blocks_phrase](Blocklist::blocks_phrase) before they are shown.
+///
+///# Examples
+/// ```
+/// use anagrambot::anagram::find_proper_anagrams;
+/// use anagrambot::wordlist::{Blocklist, BorrowedWordList};
+///
+/// let wordlist: BorrowedWordList = ["race", "care", "acre", "acer"].into_iter().collect();
+/// let blocklist = Blocklist::new().with_word("acre").with_pattern("cer");
+///
+/// let safe_words = blocklist.without_blocked(&wordlist);
+/// let anagrams: Vec<&str> = find_proper_anagrams("care", &safe_words, true).collect();
+/// assert_eq!(anagrams, vec!["race"]);
+///
+/// assert!(blocklist.blocks_phrase("ace race"));
+/// assert!(!blocklist.blocks_phrase("car"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Blocklist {
+    // both are lowercase
+    words: HashSet<String>,
+    patterns: Vec<String>
+}
+
+impl Blocklist {
+    /// Construct a new `Blocklist` that doesn't block anything
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the blocklist shipped with anagrambot
+    ///
+    /// It is a short list of slurs and strong profanity, meant as a starting point for bots
+    /// that post anagrams publicly.
+    pub fn default_blocklist() -> Self {
+        Self::from_reader(DEFAULT_BLOCKLIST.as_bytes())
+            .expect("the default blocklist is always readable")
+    }
+
+    /// Construct a new `Blocklist` from the contents of a text file
+    ///
+    /// Each line of the file holds a word to block, or `*text*` to block every word containing
+    /// `text`. Blank lines and lines starting with `#` are skipped.
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        Self::from_reader(BufReader::new(fs::File::open(path)?))
+    }
+
+    /// Construct a new `Blocklist` from the lines read from `reader`
+    ///
+    /// Like [from_file](Blocklist::from_file), but for any reader.
+    pub fn from_reader(reader: impl BufRead) -> io::Result<Self> {
+        let lines = WordlistLoadOptions::cleaned().read(reader)?;
+        Ok(lines.iter().fold(Self::new(), |blocklist, line| {
+            match line
+                .strip_prefix('*')
+                .and_then(|line| line.strip_suffix('*'))
+            {
+                Some(pattern) if !pattern.is_empty() => blocklist.with_pattern(pattern),
+                _ => blocklist.with_word(line)
+            }
+        }))
+    }
+
+    /// Block `word`
+    pub fn with_word(mut self, word: &str) -> Self {
+        self.words.insert(word.to_lowercase());
+        self
+    }
+
+    /// Block every word containing `pattern`
+    ///
+    /// Spaces are removed from `pattern`, so that it also matches across the words of a phrase.
+    pub fn with_pattern(mut self, pattern: &str) -> Self {
+        self.patterns.push(pattern.to_lowercase().replace(' ', ""));
+        self
+    }
+
+    /// Returns true if this `Blocklist` doesn't block anything
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty() && self.patterns.is_empty()
+    }
+
+    /// Returns true if `word` is blocked
+    pub fn blocks_word(&self, word: &str) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        let word = word.to_lowercase();
+        let stem = word.strip_suffix("'s").unwrap_or(&word);
+        self.words.contains(&word) || self.words.contains(stem) || self.matches_pattern(&word)
+    }
+
+    /// Returns true if any word of `phrase` is blocked, or if a pattern appears in `phrase`
+    /// once the spaces between its words are removed
+    pub fn blocks_phrase(&self, phrase: &str) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        phrase.split(' ').any(|word| self.blocks_word(word))
+            || self.matches_pattern(&phrase.to_lowercase().replace(' ', ""))
+    }
+
+    /// Returns a view of `wordlist` without the words this `Blocklist` blocks
+    pub fn without_blocked<W>(&self, wordlist: W) -> Filtered<W, &Self> {
+        Filtered::new(wordlist, self)
+    }
+
+    fn matches_pattern(&self, lowercase: &str) -> bool {
+        self.patterns
+            .iter()
+            .any(|pattern| lowercase.contains(pattern.as_str()))
+    }
+}
+
+/// Keeps the words that aren't blocked
+impl WordPredicate for Blocklist {
+    fn matches(&self, word: &str) -> bool {
+        !self.blocks_word(word)
+    }
+}
+
+/// Keeps the words that aren't blocked
+impl WordPredicate for &Blocklist {
+    fn matches(&self, word: &str) -> bool {
+        !self.blocks_word(word)
+    }
+}
+
+#[cfg(test)]
+mod blocklist_tests {
+    use super::Blocklist;
+
+    #[test]
+    fn test_from_reader() {
+        let text = "# comment\nAcre\n\n*ace*\n**\n";
+        let blocklist = Blocklist::from_reader(text.as_bytes()).unwrap();
+
+        assert!(blocklist.blocks_word("acre"));
+        assert!(blocklist.blocks_word("ACRE's"));
+        assert!(!blocklist.blocks_word("acres"));
+        assert!(blocklist.blocks_word("Races"));
+        assert!(blocklist.blocks_word("**"));
+        assert!(!blocklist.blocks_word("care"));
+
+        assert!(blocklist.blocks_phrase("car acre"));
+        assert!(blocklist.blocks_phrase("Ra Ce"));
+        assert!(!blocklist.blocks_phrase("car e"));
+    }
+
+    #[test]
+    fn test_default_blocklist() {
+        let blocklist = Blocklist::default_blocklist();
+        assert!(!blocklist.is_empty());
+        assert!(!blocklist.blocks_phrase("race car"));
+        assert!(!Blocklist::new().blocks_phrase("anything"));
+    }
+}
@@ -0,0 +1,170 @@
+//! Looking up words ignoring case
+
+use super::{fold_case, Fingerprint, WeightedWordlist, Wordlist};
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::OnceLock
+};
+
+/// A view of a [Wordlist] that looks up words ignoring case quickly
+///
+/// The first time a word is looked up ignoring case, a table of every word of the wordlist
+/// by its case-folded form is built; later lookups only use the table. Exact lookups
+/// ([includes_word](Wordlist::includes_word)) and iteration are passed on to the wordlist.
+///
+/// Like the [combinators](super::Union), a `CaseFoldedWordlist` owns the wordlist it is a view
+/// of, so it is usually created from a reference to it.
+///
+///# Examples
+/// ```
+/// use anagrambot::anagram::are_proper_anagrams;
+/// use anagrambot::wordlist::{BorrowedWordList, CaseFoldedWordlist, Wordlist};
+///
+/// let wordlist: BorrowedWordList = ["Race", "care", "acre"].into_iter().collect();
+/// let folded = CaseFoldedWordlist::new(&wordlist);
+///
+/// assert!(!folded.includes_word("race"));
+/// assert!(folded.includes_word_ignoring_case("race"));
+/// assert_eq!(folded.canonical_spelling("RACE"), Some("Race"));
+/// assert!(are_proper_anagrams("race", "Care", &folded, false));
+/// ```
+pub struct CaseFoldedWordlist<W> {
+    wordlist: W,
+    // each case-folded word, and its spellings in the wordlist in order
+    spellings: OnceLock<HashMap<String, Vec<String>>>
+}
+
+impl<W> CaseFoldedWordlist<W> {
+    /// Construct a new `CaseFoldedWordlist` view of `wordlist`
+    pub fn new(wordlist: W) -> Self {
+        Self {
+            wordlist,
+            spellings: OnceLock::new()
+        }
+    }
+
+    /// Returns every spelling of `word` in the wordlist (ignoring case), in order
+    pub fn spellings(&self, word: &str) -> &[String]
+    where
+        W: Wordlist
+    {
+        let spellings = self.spellings.get_or_init(|| {
+            let mut spellings: HashMap<String, Vec<String>> = HashMap::new();
+            for word in self.wordlist.iter() {
+                spellings
+                    .entry(fold_case(word))
+                    .or_default()
+                    .push(String::from(word));
+            }
+            spellings
+        });
+        spellings.get(&fold_case(word)).map_or(&[], Vec::as_slice)
+    }
+}
+
+impl<W> Wordlist for CaseFoldedWordlist<W>
+where
+    W: Wordlist
+{
+    type IterType<'a>
+        = W::IterType<'a>
+    where
+        Self: 'a;
+
+    fn includes_word(&self, word: &str) -> bool {
+        self.wordlist.includes_word(word)
+    }
+
+    fn iter(&self) -> Self::IterType<'_> {
+        self.wordlist.iter()
+    }
+
+    fn canonical_spelling(&self, word: &str) -> Option<&str> {
+        let spellings = self.spellings(word);
+        spellings
+            .iter()
+            .find(|spelling| *spelling == word)
+            .or(spellings.first())
+            .map(String::as_str)
+    }
+
+    fn includes_word_ignoring_case(&self, word: &str) -> bool {
+        !self.spellings(word).is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.wordlist.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.wordlist.is_empty()
+    }
+
+    fn words_with_signature(&self, word: &str) -> Vec<&str> {
+        self.wordlist.words_with_signature(word)
+    }
+
+    fn has_signature_lookup(&self) -> bool {
+        self.wordlist.has_signature_lookup()
+    }
+
+    fn words_fitting(&self, letters: &BTreeMap<char, u32>, case_sensitive: bool) -> Vec<&str> {
+        self.wordlist.words_fitting(letters, case_sensitive)
+    }
+
+    fn words_with_prefix(&self, prefix: &str) -> Vec<&str> {
+        self.wordlist.words_with_prefix(prefix)
+    }
+
+    fn fingerprint(&self) -> Fingerprint {
+        self.wordlist.fingerprint()
+    }
+
+    fn unordered_fingerprint(&self) -> Fingerprint {
+        self.wordlist.unordered_fingerprint()
+    }
+}
+
+impl<W> WeightedWordlist for CaseFoldedWordlist<W>
+where
+    W: WeightedWordlist
+{
+    fn weight(&self, word: &str) -> Option<u64> {
+        self.wordlist.weight(word)
+    }
+}
+
+#[cfg(test)]
+mod case_tests {
+    use super::CaseFoldedWordlist;
+    use crate::wordlist::{BorrowedWordList, Wordlist};
+
+    #[test]
+    fn test_matches_default_methods() {
+        let wordlist: BorrowedWordList = ["Race", "race", "RACE", "care", "Ärger", ""]
+            .into_iter()
+            .collect();
+        let folded = CaseFoldedWordlist::new(&wordlist);
+
+        assert_eq!(folded.spellings("rAcE"), &["Race", "race", "RACE"]);
+        for word in ["race", "RACE", "rAce", "Care", "ärger", "", "acre", "rac"] {
+            assert_eq!(
+                folded.canonical_spelling(word),
+                wordlist.canonical_spelling(word)
+            );
+            assert_eq!(
+                folded.includes_word_ignoring_case(word),
+                wordlist.includes_word_ignoring_case(word)
+            );
+            for case_sensitive in [true, false] {
+                assert_eq!(
+                    folded.includes_word_with_case(word, case_sensitive),
+                    wordlist.includes_word_with_case(word, case_sensitive)
+                );
+            }
+        }
+        assert_eq!(folded.canonical_spelling("rAce"), Some("Race"));
+        assert_eq!(folded.canonical_spelling("RACE"), Some("RACE"));
+    }
+}
@@ -0,0 +1,215 @@
+//! [Wordlist] implementations for standard library collections
+//!
+//! Slices iterate over their words in order and look words up by checking each of
+//! them, just like an [OwnedWordList](super::OwnedWordList). Sets look words up with their
+//! own fast lookups. A `BTreeSet` iterates over its words in sorted order, and also finds the
+//! words with a prefix quickly. A `HashSet` iterates over its words in an arbitrary order, which
+//! can differ between two sets with the same words, so a loose anagram search checkpoint can
+//! only be resumed with the same set.
+
+use super::Wordlist;
+
+use std::{
+    collections::{btree_set, hash_set, BTreeSet, HashSet},
+    hash::BuildHasher,
+    iter::{Copied, Map},
+    ops::Bound,
+    slice
+};
+
+impl Wordlist for [&str] {
+    type IterType<'a>
+        = Copied<slice::Iter<'a, &'a str>>
+    where
+        Self: 'a;
+
+    fn includes_word(&self, word: &str) -> bool {
+        self.contains(&word)
+    }
+
+    fn iter(&self) -> Self::IterType<'_> {
+        <[&str]>::iter(self).copied()
+    }
+
+    fn len(&self) -> usize {
+        <[&str]>::len(self)
+    }
+}
+
+impl Wordlist for [String] {
+    type IterType<'a> = Map<slice::Iter<'a, String>, fn(&String) -> &str>;
+
+    fn includes_word(&self, word: &str) -> bool {
+        <[String]>::iter(self).any(|candidate| candidate == word)
+    }
+
+    fn iter(&self) -> Self::IterType<'_> {
+        <[String]>::iter(self).map(|p| p.as_str())
+    }
+
+    fn len(&self) -> usize {
+        <[String]>::len(self)
+    }
+}
+
+impl<S> Wordlist for HashSet<&str, S>
+where
+    S: BuildHasher
+{
+    type IterType<'a>
+        = Copied<hash_set::Iter<'a, &'a str>>
+    where
+        Self: 'a;
+
+    fn includes_word(&self, word: &str) -> bool {
+        self.contains(word)
+    }
+
+    fn iter(&self) -> Self::IterType<'_> {
+        HashSet::iter(self).copied()
+    }
+
+    fn len(&self) -> usize {
+        HashSet::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        HashSet::is_empty(self)
+    }
+}
+
+impl<S> Wordlist for HashSet<String, S>
+where
+    S: BuildHasher
+{
+    type IterType<'a>
+        = Map<hash_set::Iter<'a, String>, fn(&String) -> &str>
+    where
+        Self: 'a;
+
+    fn includes_word(&self, word: &str) -> bool {
+        self.contains(word)
+    }
+
+    fn iter(&self) -> Self::IterType<'_> {
+        HashSet::iter(self).map(|p| p.as_str())
+    }
+
+    fn len(&self) -> usize {
+        HashSet::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        HashSet::is_empty(self)
+    }
+}
+
+impl Wordlist for BTreeSet<&str> {
+    type IterType<'a>
+        = Copied<btree_set::Iter<'a, &'a str>>
+    where
+        Self: 'a;
+
+    fn includes_word(&self, word: &str) -> bool {
+        self.contains(word)
+    }
+
+    fn iter(&self) -> Self::IterType<'_> {
+        BTreeSet::iter(self).copied()
+    }
+
+    fn len(&self) -> usize {
+        BTreeSet::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        BTreeSet::is_empty(self)
+    }
+
+    fn words_with_prefix(&self, prefix: &str) -> Vec<&str> {
+        // the words starting with prefix are the ones right after it in sorted order
+        self.range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .copied()
+            .take_while(|word| word.starts_with(prefix))
+            .collect()
+    }
+}
+
+impl Wordlist for BTreeSet<String> {
+    type IterType<'a> = Map<btree_set::Iter<'a, String>, fn(&String) -> &str>;
+
+    fn includes_word(&self, word: &str) -> bool {
+        self.contains(word)
+    }
+
+    fn iter(&self) -> Self::IterType<'_> {
+        BTreeSet::iter(self).map(|p| p.as_str())
+    }
+
+    fn len(&self) -> usize {
+        BTreeSet::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        BTreeSet::is_empty(self)
+    }
+
+    fn words_with_prefix(&self, prefix: &str) -> Vec<&str> {
+        // the words starting with prefix are the ones right after it in sorted order
+        self.range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .map(String::as_str)
+            .take_while(|word| word.starts_with(prefix))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod collections_tests {
+    use crate::{
+        anagram::{find_loose_anagrams, find_proper_anagrams},
+        wordlist::{BorrowedWordList, Wordlist}
+    };
+    use std::collections::{BTreeSet, HashSet};
+
+    const TEST_WORD_SET: [&str; 7] = ["race", "care", "acre", "car", "racecar", "Race", "cow"];
+
+    /// Checks that `wordlist` (with the words of TEST_WORD_SET in the order `iter` returns
+    /// them) behaves like a BorrowedWordList with the same words
+    fn check(wordlist: &impl Wordlist) {
+        let borrowed: BorrowedWordList = wordlist.iter().collect();
+
+        assert_eq!(wordlist.len(), TEST_WORD_SET.len());
+        assert!(!wordlist.is_empty());
+        for word in TEST_WORD_SET.into_iter().chain(["rac", "", "RACE"]) {
+            assert_eq!(wordlist.includes_word(word), borrowed.includes_word(word));
+            assert_eq!(
+                wordlist.words_with_prefix(word),
+                borrowed.words_with_prefix(word)
+            );
+        }
+        assert!(find_proper_anagrams("race", wordlist, true)
+            .eq(find_proper_anagrams("race", &borrowed, true)));
+        assert!(find_loose_anagrams("racecar", wordlist, 1, false)
+            .eq(find_loose_anagrams("racecar", &borrowed, 1, false)));
+    }
+
+    #[test]
+    fn test_matches_borrowed_wordlist() {
+        let strs: Vec<&str> = TEST_WORD_SET.to_vec();
+        let strings: Vec<String> = TEST_WORD_SET.into_iter().map(String::from).collect();
+
+        check(&strs.as_slice());
+        check(&strings.as_slice());
+        check(&strs.iter().copied().collect::<HashSet<&str>>());
+        check(&strings.clone().into_iter().collect::<HashSet<String>>());
+        check(&strs.iter().copied().collect::<BTreeSet<&str>>());
+        check(&strings.clone().into_iter().collect::<BTreeSet<String>>());
+    }
+
+    #[test]
+    fn test_btree_set_prefix() {
+        let set: BTreeSet<&str> = TEST_WORD_SET.into_iter().collect();
+        assert_eq!(set.words_with_prefix("rac"), vec!["race", "racecar"]);
+        assert_eq!(set.words_with_prefix("z"), Vec::<&str>::new());
+    }
+}
@@ -0,0 +1,677 @@
+//! Views that combine or filter other wordlists
+
+use super::{distinct, WeightedWordlist, Wordlist};
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    ops::{Bound, RangeBounds}
+};
+
+/// A [Wordlist] containing every word that is in either of two wordlists
+///
+/// Iterating over a `Union` returns the words of the first wordlist and then the words of the
+/// second wordlist that aren't in the first, without any duplicates. Words are looked up in
+/// the two wordlists as needed; nothing is copied.
+///
+/// Like the other combinators, a `Union` owns the wordlists it combines, so it is usually
+/// created from references to them. Combinators can be nested, since a reference to a
+/// `Wordlist` is also a `Wordlist`.
+///
+///# Examples
+/// ```
+/// use anagrambot::wordlist::{BorrowedWordList, Difference, Union, Wordlist};
+///
+/// let collins: BorrowedWordList = ["race", "care", "acre"].into_iter().collect();
+/// let extra: BorrowedWordList = ["care", "crea"].into_iter().collect();
+/// let blocked: BorrowedWordList = ["acre"].into_iter().collect();
+///
+/// let union = Union::new(&collins, &extra);
+/// assert_eq!(union.iter().collect::<Vec<&str>>(), vec!["race", "care", "acre", "crea"]);
+///
+/// let allowed = Difference::new(union, &blocked);
+/// assert_eq!(allowed.iter().collect::<Vec<&str>>(), vec!["race", "care", "crea"]);
+/// assert!(!allowed.includes_word("acre"));
+/// ```
+pub struct Union<A, B> {
+    first: A,
+    second: B
+}
+
+impl<A, B> Union<A, B> {
+    /// Construct a new `Union` of `first` and `second`
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+}
+
+impl<A, B> Wordlist for Union<A, B>
+where
+    A: Wordlist,
+    B: Wordlist
+{
+    type IterType<'a>
+        = UnionIter<'a, A, B>
+    where
+        Self: 'a;
+
+    fn includes_word(&self, word: &str) -> bool {
+        self.first.includes_word(word) || self.second.includes_word(word)
+    }
+
+    fn iter(&self) -> Self::IterType<'_> {
+        UnionIter {
+            first: self.first.iter(),
+            second: self.second.iter(),
+            seen: HashSet::new()
+        }
+    }
+
+    fn words_with_signature(&self, word: &str) -> Vec<&str> {
+        let first = self.first.words_with_signature(word);
+        let second = self.second.words_with_signature(word);
+        distinct(first.into_iter().chain(second))
+    }
+
+    fn has_signature_lookup(&self) -> bool {
+        self.first.has_signature_lookup() && self.second.has_signature_lookup()
+    }
+
+    fn words_fitting(&self, letters: &BTreeMap<char, u32>, case_sensitive: bool) -> Vec<&str> {
+        let first = self.first.words_fitting(letters, case_sensitive);
+        let second = self.second.words_fitting(letters, case_sensitive);
+        distinct(first.into_iter().chain(second))
+    }
+
+    fn words_with_prefix(&self, prefix: &str) -> Vec<&str> {
+        let first = self.first.words_with_prefix(prefix);
+        let second = self.second.words_with_prefix(prefix);
+        distinct(first.into_iter().chain(second))
+    }
+}
+
+impl<A, B> WeightedWordlist for Union<A, B>
+where
+    A: WeightedWordlist,
+    B: WeightedWordlist
+{
+    /// Returns the weight of `word` in the first wordlist, or in the second wordlist if it
+    /// isn't in the first
+    fn weight(&self, word: &str) -> Option<u64> {
+        self.first.weight(word).or_else(|| self.second.weight(word))
+    }
+}
+
+/// The iterator type of [Union]
+pub struct UnionIter<'a, A, B>
+where
+    A: Wordlist + 'a,
+    B: Wordlist + 'a
+{
+    first: A::IterType<'a>,
+    second: B::IterType<'a>,
+    // every word returned so far, to skip duplicates
+    seen: HashSet<&'a str>
+}
+
+impl<'a, A, B> Iterator for UnionIter<'a, A, B>
+where
+    A: Wordlist + 'a,
+    B: Wordlist + 'a
+{
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let seen = &mut self.seen;
+        self.first
+            .find(|word| seen.insert(word))
+            .or_else(|| self.second.find(|word| seen.insert(word)))
+    }
+}
+
+/// A [Wordlist] containing the words of one wordlist that are also in another
+///
+/// Iterating over an `Intersection` returns the words of the first wordlist (in its order) that
+/// the second wordlist includes. Since [includes_word](Wordlist::includes_word) is called on
+/// the second wordlist for every word of the first, the second should be a wordlist with fast
+/// lookups, such as a [TrieWordList](super::TrieWordList).
+///
+///# Examples
+/// ```
+/// use anagrambot::wordlist::{BorrowedWordList, Intersection, TrieWordList, Wordlist};
+///
+/// let collins: BorrowedWordList = ["race", "care", "acre"].into_iter().collect();
+/// let common: TrieWordList = ["care", "race", "the"].into_iter().map(String::from).collect();
+///
+/// let common_collins = Intersection::new(&collins, &common);
+/// assert_eq!(common_collins.iter().collect::<Vec<&str>>(), vec!["race", "care"]);
+/// assert!(!common_collins.includes_word("the"));
+/// ```
+pub struct Intersection<A, B> {
+    first: A,
+    second: B
+}
+
+impl<A, B> Intersection<A, B> {
+    /// Construct a new `Intersection` of `first` and `second`
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+}
+
+impl<A, B> Wordlist for Intersection<A, B>
+where
+    A: Wordlist,
+    B: Wordlist
+{
+    type IterType<'a>
+        = IntersectionIter<'a, A, B>
+    where
+        Self: 'a;
+
+    fn includes_word(&self, word: &str) -> bool {
+        self.first.includes_word(word) && self.second.includes_word(word)
+    }
+
+    fn iter(&self) -> Self::IterType<'_> {
+        IntersectionIter {
+            word_iter: self.first.iter(),
+            second: &self.second
+        }
+    }
+
+    fn words_with_signature(&self, word: &str) -> Vec<&str> {
+        let mut words = self.first.words_with_signature(word);
+        words.retain(|word| self.second.includes_word(word));
+        words
+    }
+
+    fn has_signature_lookup(&self) -> bool {
+        self.first.has_signature_lookup()
+    }
+
+    fn words_fitting(&self, letters: &BTreeMap<char, u32>, case_sensitive: bool) -> Vec<&str> {
+        let mut words = self.first.words_fitting(letters, case_sensitive);
+        words.retain(|word| self.second.includes_word(word));
+        words
+    }
+
+    fn words_with_prefix(&self, prefix: &str) -> Vec<&str> {
+        let mut words = self.first.words_with_prefix(prefix);
+        words.retain(|word| self.second.includes_word(word));
+        words
+    }
+}
+
+impl<A, B> WeightedWordlist for Intersection<A, B>
+where
+    A: WeightedWordlist,
+    B: Wordlist
+{
+    /// Returns the weight of `word` in the first wordlist, if it is in both wordlists
+    fn weight(&self, word: &str) -> Option<u64> {
+        self.first
+            .weight(word)
+            .filter(|_| self.second.includes_word(word))
+    }
+}
+
+/// The iterator type of [Intersection]
+pub struct IntersectionIter<'a, A, B>
+where
+    A: Wordlist + 'a
+{
+    word_iter: A::IterType<'a>,
+    second: &'a B
+}
+
+impl<'a, A, B> Iterator for IntersectionIter<'a, A, B>
+where
+    A: Wordlist + 'a,
+    B: Wordlist + 'a
+{
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let second = self.second;
+        self.word_iter.find(|word| second.includes_word(word))
+    }
+}
+
+/// A [Wordlist] containing the words of one wordlist that aren't in another
+///
+/// Useful for leaving a blocklist out of a wordlist. Iterating over a `Difference` returns
+/// the words of the first wordlist (in its order) that the second wordlist doesn't include, so
+/// as with an [Intersection], the second wordlist should have fast lookups.
+///
+/// See [Union] for an example.
+pub struct Difference<A, B> {
+    first: A,
+    second: B
+}
+
+impl<A, B> Difference<A, B> {
+    /// Construct a new `Difference` containing the words of `first` that aren't in `second`
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+}
+
+impl<A, B> Wordlist for Difference<A, B>
+where
+    A: Wordlist,
+    B: Wordlist
+{
+    type IterType<'a>
+        = DifferenceIter<'a, A, B>
+    where
+        Self: 'a;
+
+    fn includes_word(&self, word: &str) -> bool {
+        self.first.includes_word(word) && !self.second.includes_word(word)
+    }
+
+    fn iter(&self) -> Self::IterType<'_> {
+        DifferenceIter {
+            word_iter: self.first.iter(),
+            second: &self.second
+        }
+    }
+
+    fn words_with_signature(&self, word: &str) -> Vec<&str> {
+        let mut words = self.first.words_with_signature(word);
+        words.retain(|word| !self.second.includes_word(word));
+        words
+    }
+
+    fn has_signature_lookup(&self) -> bool {
+        self.first.has_signature_lookup()
+    }
+
+    fn words_fitting(&self, letters: &BTreeMap<char, u32>, case_sensitive: bool) -> Vec<&str> {
+        let mut words = self.first.words_fitting(letters, case_sensitive);
+        words.retain(|word| !self.second.includes_word(word));
+        words
+    }
+
+    fn words_with_prefix(&self, prefix: &str) -> Vec<&str> {
+        let mut words = self.first.words_with_prefix(prefix);
+        words.retain(|word| !self.second.includes_word(word));
+        words
+    }
+}
+
+impl<A, B> WeightedWordlist for Difference<A, B>
+where
+    A: WeightedWordlist,
+    B: Wordlist
+{
+    /// Returns the weight of `word` in the first wordlist, if it isn't in the second
+    fn weight(&self, word: &str) -> Option<u64> {
+        self.first
+            .weight(word)
+            .filter(|_| !self.second.includes_word(word))
+    }
+}
+
+/// The iterator type of [Difference]
+pub struct DifferenceIter<'a, A, B>
+where
+    A: Wordlist + 'a
+{
+    word_iter: A::IterType<'a>,
+    second: &'a B
+}
+
+impl<'a, A, B> Iterator for DifferenceIter<'a, A, B>
+where
+    A: Wordlist + 'a,
+    B: Wordlist + 'a
+{
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let second = self.second;
+        self.word_iter.find(|word| !second.includes_word(word))
+    }
+}
+
+/// A test deciding which words a [Filtered] view keeps
+///
+/// Implemented for [WordFilter] and for any closure taking a `&str` and returning a `bool`.
+pub trait WordPredicate {
+    /// Returns true if `word` should be kept
+    fn matches(&self, word: &str) -> bool;
+}
+
+impl<F> WordPredicate for F
+where
+    F: Fn(&str) -> bool
+{
+    fn matches(&self, word: &str) -> bool {
+        self(word)
+    }
+}
+
+/// A [Wordlist] containing the words of another wordlist that match a [WordPredicate]
+///
+///# Examples
+/// ```
+/// use anagrambot::wordlist::{BorrowedWordList, Filtered, WordFilter, Wordlist};
+///
+/// let wordlist: BorrowedWordList = ["a", "race", "care", "racecar", "café"].into_iter().collect();
+///
+/// let filter = WordFilter::new().with_length(3..=5).with_alphabet("abcdefghijklmnopqrstuvwxyz");
+/// let filtered = Filtered::new(&wordlist, filter);
+/// assert_eq!(filtered.iter().collect::<Vec<&str>>(), vec!["race", "care"]);
+///
+/// let no_r = Filtered::new(&wordlist, |word: &str| !word.contains('r'));
+/// assert_eq!(no_r.iter().collect::<Vec<&str>>(), vec!["a", "café"]);
+/// ```
+pub struct Filtered<W, P> {
+    wordlist: W,
+    predicate: P
+}
+
+impl<W, P> Filtered<W, P> {
+    /// Construct a new `Filtered` view of the words of `wordlist` that match `predicate`
+    pub fn new(wordlist: W, predicate: P) -> Self {
+        Self {
+            wordlist,
+            predicate
+        }
+    }
+}
+
+impl<W, P> Wordlist for Filtered<W, P>
+where
+    W: Wordlist,
+    P: WordPredicate
+{
+    type IterType<'a>
+        = FilteredIter<'a, W, P>
+    where
+        Self: 'a;
+
+    fn includes_word(&self, word: &str) -> bool {
+        self.predicate.matches(word) && self.wordlist.includes_word(word)
+    }
+
+    fn iter(&self) -> Self::IterType<'_> {
+        FilteredIter {
+            word_iter: self.wordlist.iter(),
+            predicate: &self.predicate
+        }
+    }
+
+    fn words_with_signature(&self, word: &str) -> Vec<&str> {
+        let mut words = self.wordlist.words_with_signature(word);
+        words.retain(|word| self.predicate.matches(word));
+        words
+    }
+
+    fn has_signature_lookup(&self) -> bool {
+        self.wordlist.has_signature_lookup()
+    }
+
+    fn words_fitting(&self, letters: &BTreeMap<char, u32>, case_sensitive: bool) -> Vec<&str> {
+        let mut words = self.wordlist.words_fitting(letters, case_sensitive);
+        words.retain(|word| self.predicate.matches(word));
+        words
+    }
+
+    fn words_with_prefix(&self, prefix: &str) -> Vec<&str> {
+        let mut words = self.wordlist.words_with_prefix(prefix);
+        words.retain(|word| self.predicate.matches(word));
+        words
+    }
+}
+
+impl<W, P> WeightedWordlist for Filtered<W, P>
+where
+    W: WeightedWordlist,
+    P: WordPredicate
+{
+    fn weight(&self, word: &str) -> Option<u64> {
+        self.wordlist
+            .weight(word)
+            .filter(|_| self.predicate.matches(word))
+    }
+}
+
+/// The iterator type of [Filtered]
+pub struct FilteredIter<'a, W, P>
+where
+    W: Wordlist + 'a
+{
+    word_iter: W::IterType<'a>,
+    predicate: &'a P
+}
+
+impl<'a, W, P> Iterator for FilteredIter<'a, W, P>
+where
+    W: Wordlist + 'a,
+    P: WordPredicate
+{
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let predicate = self.predicate;
+        self.word_iter.find(|word| predicate.matches(word))
+    }
+}
+
+/// A [WordPredicate] for the common ways of filtering words
+///
+/// A word matches a `WordFilter` if it passes every test that has been set; a new `WordFilter`
+/// matches every word.
+#[derive(Debug, Clone)]
+pub struct WordFilter {
+    length: (Bound<usize>, Bound<usize>),
+    alphabet: Option<BTreeSet<char>>,
+    #[cfg(feature = "regex")]
+    pattern: Option<regex_lite::Regex>
+}
+
+impl Default for WordFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WordFilter {
+    /// Construct a new `WordFilter` that matches every word
+    pub fn new() -> Self {
+        Self {
+            length: (Bound::Unbounded, Bound::Unbounded),
+            alphabet: None,
+            #[cfg(feature = "regex")]
+            pattern: None
+        }
+    }
+
+    /// Only match words whose length (in characters, not counting spaces) is in `length`
+    pub fn with_length(mut self, length: impl RangeBounds<usize>) -> Self {
+        self.length = (length.start_bound().cloned(), length.end_bound().cloned());
+        self
+    }
+
+    /// Only match words made entirely of the characters in `alphabet` (and spaces)
+    ///
+    /// Characters are compared exactly, so to allow both cases of a letter, include both in
+    /// `alphabet`.
+    pub fn with_alphabet(mut self, alphabet: &str) -> Self {
+        self.alphabet = Some(alphabet.chars().collect());
+        self
+    }
+
+    /// Only match words matching the regular expression `pattern`
+    ///
+    /// The pattern can match any part of a word; use `^` and `$` to match whole words.
+    /// Returns an error if `pattern` isn't a valid regular expression.
+    ///
+    /// Requires the `regex` feature.
+    ///
+    ///# Examples
+    /// ```
+    /// use anagrambot::wordlist::{WordFilter, WordPredicate};
+    ///
+    /// let filter = WordFilter::new().with_pattern("^c.r").unwrap();
+    /// assert!(filter.matches("care"));
+    /// assert!(!filter.matches("race"));
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn with_pattern(mut self, pattern: &str) -> Result<Self, regex_lite::Error> {
+        self.pattern = Some(regex_lite::Regex::new(pattern)?);
+        Ok(self)
+    }
+}
+
+impl WordPredicate for WordFilter {
+    fn matches(&self, word: &str) -> bool {
+        let length = word.chars().filter(|letter| *letter != ' ').count();
+        if !self.length.contains(&length) {
+            return false;
+        }
+        if let Some(alphabet) = &self.alphabet {
+            if !word
+                .chars()
+                .all(|letter| letter == ' ' || alphabet.contains(&letter))
+            {
+                return false;
+            }
+        }
+        #[cfg(feature = "regex")]
+        if let Some(pattern) = &self.pattern {
+            if !pattern.is_match(word) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod combine_tests {
+    use super::{Difference, Filtered, Intersection, Union, WordFilter, WordPredicate};
+    use crate::wordlist::{
+        BorrowedWordList, FrequencyWordList, TrieWordList, WeightedWordlist, Wordlist
+    };
+    use std::collections::BTreeMap;
+
+    fn words(wordlist: &impl Wordlist) -> Vec<&str> {
+        wordlist.iter().collect()
+    }
+
+    #[test]
+    fn test_union() {
+        let first: BorrowedWordList = ["race", "care", "race"].into_iter().collect();
+        let second: BorrowedWordList = ["acre", "care", "acre"].into_iter().collect();
+
+        let union = Union::new(&first, &second);
+        assert_eq!(words(&union), vec!["race", "care", "acre"]);
+        assert!(union.includes_word("acre"));
+        assert!(!union.includes_word("car"));
+    }
+
+    #[test]
+    fn test_intersection_and_difference() {
+        let first: BorrowedWordList = ["race", "care", "acre"].into_iter().collect();
+        let second: BorrowedWordList = ["care", "car"].into_iter().collect();
+
+        let intersection = Intersection::new(&first, &second);
+        assert_eq!(words(&intersection), vec!["care"]);
+        assert!(!intersection.includes_word("car"));
+
+        let difference = Difference::new(&first, &second);
+        assert_eq!(words(&difference), vec!["race", "acre"]);
+        assert!(!difference.includes_word("care"));
+        assert!(!difference.includes_word("car"));
+    }
+
+    #[test]
+    fn test_search_methods_match_iter() {
+        let first: TrieWordList = ["race", "care", "race", "acre", "car"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let second: TrieWordList = ["acer", "care", "cart", "crate"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let letters = BTreeMap::from([('a', 1), ('c', 1), ('e', 1), ('r', 1), ('t', 1)]);
+
+        fn check(view: &impl Wordlist, letters: &BTreeMap<char, u32>) {
+            // BorrowedWordList uses the default implementations
+            let scanned: BorrowedWordList = view.iter().collect();
+            assert_eq!(view.len(), scanned.len());
+            assert_eq!(
+                view.words_with_signature("care"),
+                scanned.words_with_signature("care")
+            );
+            assert_eq!(
+                view.words_fitting(letters, true),
+                scanned.words_fitting(letters, true)
+            );
+            assert_eq!(
+                view.words_with_prefix("ca"),
+                scanned.words_with_prefix("ca")
+            );
+        }
+        check(&Union::new(&first, &second), &letters);
+        check(&Intersection::new(&first, &second), &letters);
+        check(&Difference::new(&first, &second), &letters);
+        check(
+            &Filtered::new(&first, |word: &str| word != "acre"),
+            &letters
+        );
+    }
+
+    #[test]
+    fn test_weights() {
+        let first: FrequencyWordList = [("race", 300), ("care", 500)]
+            .into_iter()
+            .map(|(word, count)| (String::from(word), count))
+            .collect();
+        let second: FrequencyWordList = [("care", 1), ("acre", 10)]
+            .into_iter()
+            .map(|(word, count)| (String::from(word), count))
+            .collect();
+
+        let union = Union::new(&first, &second);
+        assert_eq!(union.weight("care"), Some(500));
+        assert_eq!(union.weight("acre"), Some(10));
+        assert_eq!(union.total_weight(), 810);
+
+        let difference = Difference::new(&first, &second);
+        assert_eq!(difference.weight("care"), None);
+        assert_eq!(difference.weight("race"), Some(300));
+
+        let filtered = Filtered::new(&first, |word: &str| word.starts_with('c'));
+        assert_eq!(filtered.weight("race"), None);
+        assert_eq!(words(&filtered.with_min_weight(100)), vec!["care"]);
+    }
+
+    #[test]
+    fn test_word_filter() {
+        let filter = WordFilter::new();
+        assert!(filter.matches(""));
+        assert!(filter.matches("anything at all"));
+
+        let filter = WordFilter::new().with_length(4..);
+        assert!(filter.matches("ice cream"));
+        assert!(filter.matches("ab cd"));
+        assert!(!filter.matches("a bc"));
+
+        let filter = WordFilter::new().with_alphabet("acer");
+        assert!(filter.matches("race car"));
+        assert!(!filter.matches("Race"));
+
+        #[cfg(feature = "regex")]
+        {
+            let filter = WordFilter::new().with_pattern("a$").unwrap();
+            assert!(filter.matches("area"));
+            assert!(!filter.matches("race"));
+            assert!(WordFilter::new().with_pattern("(").is_err());
+        }
+    }
+}
@@ -0,0 +1,664 @@
+//! Importing Hunspell dictionaries
+
+use super::{Encoding, OwnedWordList, WordlistLoadOptions};
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{self, BufRead, BufReader},
+    path::Path
+};
+
+/// The UTF-8 byte order mark, which many dictionaries start with
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+
+/// A flag, encoded as a number whatever the dictionary's flag type is
+type Flag = u64;
+
+/// How flags are written in a dictionary (the `FLAG` directive of the affix file)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FlagType {
+    /// one character per flag (the default)
+    Short,
+    /// two characters per flag
+    Long,
+    /// comma separated decimal numbers
+    Numeric
+}
+
+/// One character of an affix condition
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ConditionPart {
+    /// `.`, any character
+    Any,
+    /// a literal character
+    Char(char),
+    /// `[abc]`, or `[^abc]` if negated
+    Set { chars: Vec<char>, negated: bool }
+}
+
+impl ConditionPart {
+    fn matches(&self, character: char) -> bool {
+        match self {
+            ConditionPart::Any => true,
+            ConditionPart::Char(expected) => character == *expected,
+            ConditionPart::Set { chars, negated } => chars.contains(&character) != *negated
+        }
+    }
+}
+
+/// One rule of a prefix or suffix class
+#[derive(Debug, Clone)]
+struct AffixRule {
+    strip: String,
+    add: String,
+    // flags of the affixed word, such as classes of a second suffix
+    continuation: Vec<Flag>,
+    condition: Vec<ConditionPart>
+}
+
+/// A prefix or suffix class: the rules named by one flag
+#[derive(Debug, Clone, Default)]
+struct AffixClass {
+    // whether words can have affixes of this class and of the other kind at once
+    cross_product: bool,
+    rules: Vec<AffixRule>
+}
+
+/// A Hunspell dictionary: a `.dic` file of stems, and the `.aff` file of rules used to expand
+/// them into full words
+///
+/// Dictionaries for most languages (such as the ones used by LibreOffice and Firefox) are
+/// distributed in this format. [expand](HunspellDictionary::expand) applies the prefix and
+/// suffix rules of each stem, including cross products of prefixes and suffixes and a second
+/// level of suffixes, and leaves out stems flagged `FORBIDDENWORD` or `ONLYINCOMPOUND`.
+/// Compound words (`COMPOUNDFLAG` and friends) are not generated, and dictionaries must be
+/// UTF-8 or ISO8859-1 (`SET` in the affix file).
+///
+///# Examples
+/// ```
+/// use anagrambot::wordlist::{HunspellDictionary, HunspellOptions, Wordlist};
+///
+/// let aff = "SET UTF-8\nPFX U Y 1\nPFX U 0 un .\nSFX S Y 2\nSFX S y ies [^aeiou]y\nSFX S 0 s [^y]\n";
+/// let dic = "3\nspy/S\nread\ntie/US\n";
+///
+/// let dictionary = HunspellDictionary::from_readers(aff.as_bytes(), dic.as_bytes()).unwrap();
+/// let wordlist = dictionary.to_wordlist(&HunspellOptions::new());
+/// let words: Vec<&str> = wordlist.iter().collect();
+/// assert_eq!(words, vec!["spy", "spies", "read", "tie", "ties", "untie", "unties"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct HunspellDictionary {
+    flag_type: FlagType,
+    // flag sets referred to by number in place of flags (the AF directive), numbered from 1
+    aliases: Vec<Vec<Flag>>,
+    prefixes: HashMap<Flag, AffixClass>,
+    suffixes: HashMap<Flag, AffixClass>,
+    need_affix: Option<Flag>,
+    forbidden: Option<Flag>,
+    only_in_compound: Option<Flag>,
+    full_strip: bool,
+    stems: Vec<(String, Vec<Flag>)>
+}
+
+/// Options for expanding a [HunspellDictionary] into words
+///
+/// Flags are written the same way as in the dictionary (for example `X`, or `Aa` in a
+/// dictionary with `FLAG long`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HunspellOptions {
+    required_flags: Vec<String>,
+    excluded_flags: Vec<String>
+}
+
+impl HunspellOptions {
+    /// Returns options that expand every stem
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only expands stems that have `flag`, returning the modified options
+    ///
+    /// Can be called more than once to require several flags.
+    pub fn with_required_flag(mut self, flag: impl Into<String>) -> Self {
+        self.required_flags.push(flag.into());
+        self
+    }
+
+    /// Leaves out stems that have `flag`, returning the modified options
+    ///
+    /// Many dictionaries mark proper nouns or offensive words with a flag, which can be used
+    /// to leave them out. Can be called more than once to exclude several flags.
+    pub fn with_excluded_flag(mut self, flag: impl Into<String>) -> Self {
+        self.excluded_flags.push(flag.into());
+        self
+    }
+}
+
+impl HunspellDictionary {
+    /// Loads the dictionary made of the affix file at `aff_file` and the stems at `dic_file`
+    ///
+    /// Returns an error of kind [InvalidData](io::ErrorKind::InvalidData) if the affix file is
+    /// invalid, or of kind [Unsupported](io::ErrorKind::Unsupported) if the dictionary uses an
+    /// encoding other than UTF-8 or ISO8859-1.
+    pub fn from_files(aff_file: &Path, dic_file: &Path) -> io::Result<Self> {
+        Self::from_readers(
+            BufReader::new(fs::File::open(aff_file)?),
+            BufReader::new(fs::File::open(dic_file)?)
+        )
+    }
+
+    /// Loads the dictionary made of the affix file read from `aff` and the stems read from `dic`
+    ///
+    /// Like [from_files](HunspellDictionary::from_files), but for any readers.
+    ///
+    /// Files starting with a UTF-8 byte order mark are read as UTF-8.
+    pub fn from_readers(mut aff: impl BufRead, mut dic: impl BufRead) -> io::Result<Self> {
+        let mut aff_bytes = Vec::new();
+        aff.read_to_end(&mut aff_bytes)?;
+        let mut dic_bytes = Vec::new();
+        dic.read_to_end(&mut dic_bytes)?;
+        let has_bom = aff_bytes.starts_with(UTF8_BOM) || dic_bytes.starts_with(UTF8_BOM);
+        let aff_bytes = aff_bytes.strip_prefix(UTF8_BOM).unwrap_or(&aff_bytes);
+        let dic_bytes = dic_bytes.strip_prefix(UTF8_BOM).unwrap_or(&dic_bytes);
+        let read_lines = |bytes: &[u8], encoding| {
+            WordlistLoadOptions::new()
+                .with_encoding(encoding)
+                .read(bytes)
+        };
+
+        // the encoding is named in the affix file itself, in ASCII
+        let encoding = match read_lines(aff_bytes, Encoding::Latin1)?
+            .iter()
+            .find_map(|line| line.trim().strip_prefix("SET "))
+            .map(str::trim)
+        {
+            _ if has_bom => Encoding::Utf8,
+            None | Some("ISO8859-1") | Some("ISO-8859-1") => Encoding::Latin1,
+            Some("UTF-8") => Encoding::Utf8,
+            Some(other) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("unsupported dictionary encoding {}", other)
+                ))
+            }
+        };
+
+        let mut dictionary = Self::parse_aff(&read_lines(aff_bytes, encoding)?)?;
+        dictionary.parse_dic(&read_lines(dic_bytes, encoding)?);
+        Ok(dictionary)
+    }
+
+    fn parse_aff(lines: &[String]) -> io::Result<Self> {
+        let mut dictionary = Self {
+            flag_type: FlagType::Short,
+            aliases: Vec::new(),
+            prefixes: HashMap::new(),
+            suffixes: HashMap::new(),
+            need_affix: None,
+            forbidden: None,
+            only_in_compound: None,
+            full_strip: false,
+            stems: Vec::new()
+        };
+        // the kind and flag of the affix class being read, and how many of its rules are left
+        let mut open_class: Option<(bool, Flag, usize)> = None;
+        // the first AF line is the number of aliases, and the rest are the aliases
+        let mut alias_count: Option<usize> = None;
+
+        for (line_index, line) in lines.iter().enumerate() {
+            let invalid_line = |reason: &str| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: {}", line_index + 1, reason)
+                )
+            };
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (directive, args) = match fields.split_first() {
+                Some((directive, args)) if !directive.starts_with('#') => (*directive, args),
+                _ => continue
+            };
+            match (directive, args) {
+                ("FLAG", [flag_type, ..]) => {
+                    dictionary.flag_type = match *flag_type {
+                        "long" => FlagType::Long,
+                        "num" => FlagType::Numeric,
+                        // UTF-8 flags are one character each, just like the default
+                        _ => FlagType::Short
+                    }
+                }
+                ("AF", [flags, ..]) => match alias_count {
+                    None => {
+                        alias_count = Some(
+                            flags
+                                .parse()
+                                .map_err(|_| invalid_line("expected the number of aliases"))?
+                        )
+                    }
+                    Some(_) => {
+                        let flags = dictionary.parse_raw_flags(flags);
+                        dictionary.aliases.push(flags);
+                    }
+                },
+                ("NEEDAFFIX", [flag, ..]) => dictionary.need_affix = dictionary.first_flag(flag),
+                ("FORBIDDENWORD", [flag, ..]) => dictionary.forbidden = dictionary.first_flag(flag),
+                ("ONLYINCOMPOUND", [flag, ..]) => {
+                    dictionary.only_in_compound = dictionary.first_flag(flag)
+                }
+                ("FULLSTRIP", _) => dictionary.full_strip = true,
+                ("PFX" | "SFX", [flag, rest @ ..]) => {
+                    let is_prefix = directive == "PFX";
+                    let flag = dictionary
+                        .first_flag(flag)
+                        .ok_or_else(|| invalid_line("invalid affix flag"))?;
+                    match open_class {
+                        Some((open_prefix, open_flag, remaining))
+                            if open_prefix == is_prefix && open_flag == flag && remaining > 0 =>
+                        {
+                            let rule = dictionary
+                                .parse_rule(rest)
+                                .ok_or_else(|| invalid_line("expected an affix rule"))?;
+                            dictionary
+                                .classes(is_prefix)
+                                .entry(flag)
+                                .or_default()
+                                .rules
+                                .push(rule);
+                            open_class = Some((is_prefix, flag, remaining - 1));
+                        }
+                        _ => {
+                            let (cross_product, count) = match rest {
+                                [cross_product, count, ..] => (
+                                    *cross_product == "Y",
+                                    count
+                                        .parse()
+                                        .map_err(|_| invalid_line("expected a rule count"))?
+                                ),
+                                _ => return Err(invalid_line("expected an affix class header"))
+                            };
+                            dictionary
+                                .classes(is_prefix)
+                                .entry(flag)
+                                .or_default()
+                                .cross_product = cross_product;
+                            open_class = Some((is_prefix, flag, count));
+                        }
+                    }
+                }
+                _ => ()
+            }
+        }
+        Ok(dictionary)
+    }
+
+    fn classes(&mut self, is_prefix: bool) -> &mut HashMap<Flag, AffixClass> {
+        if is_prefix {
+            &mut self.prefixes
+        } else {
+            &mut self.suffixes
+        }
+    }
+
+    /// Parses the fields of a rule after its flag: strip, add (and flags), and condition
+    fn parse_rule(&self, fields: &[&str]) -> Option<AffixRule> {
+        let (strip, add, condition) = match fields {
+            [strip, add] => (*strip, *add, "."),
+            [strip, add, condition, ..] => (*strip, *add, *condition),
+            _ => return None
+        };
+        let (add, continuation) = match add.split_once('/') {
+            Some((add, flags)) => (add, self.parse_flags(flags)),
+            None => (add, Vec::new())
+        };
+        let zero_is_empty = |text: &str| {
+            if text == "0" {
+                String::new()
+            } else {
+                text.to_string()
+            }
+        };
+        Some(AffixRule {
+            strip: zero_is_empty(strip),
+            add: zero_is_empty(add),
+            continuation,
+            condition: parse_condition(condition)
+        })
+    }
+
+    /// Parses a set of flags, or the number of an alias for one
+    fn parse_flags(&self, flags: &str) -> Vec<Flag> {
+        if self.aliases.is_empty() {
+            return self.parse_raw_flags(flags);
+        }
+        flags
+            .parse::<usize>()
+            .ok()
+            .and_then(|alias| alias.checked_sub(1))
+            .and_then(|index| self.aliases.get(index))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Parses a set of flags written out in full
+    fn parse_raw_flags(&self, flags: &str) -> Vec<Flag> {
+        match self.flag_type {
+            FlagType::Short => flags.chars().map(Flag::from).collect(),
+            FlagType::Long => {
+                let chars: Vec<char> = flags.chars().collect();
+                chars
+                    .chunks(2)
+                    .map(|pair| {
+                        pair.iter()
+                            .fold(0, |flag, character| flag << 32 | Flag::from(*character))
+                    })
+                    .collect()
+            }
+            FlagType::Numeric => flags
+                .split(',')
+                .filter_map(|flag| flag.trim().parse().ok())
+                .collect()
+        }
+    }
+
+    fn first_flag(&self, flag: &str) -> Option<Flag> {
+        self.parse_raw_flags(flag).first().copied()
+    }
+
+    fn parse_dic(&mut self, lines: &[String]) {
+        let mut lines = lines.iter().map(|line| line.trim()).peekable();
+        // the first line is the (approximate) number of stems
+        if lines
+            .peek()
+            .is_some_and(|line| line.parse::<usize>().is_ok())
+        {
+            lines.next();
+        }
+        for line in lines {
+            // anything after whitespace is morphological data
+            let entry = line.split(['\t', ' ']).next().unwrap_or("");
+            if entry.is_empty() || entry.starts_with('#') {
+                continue;
+            }
+            let (stem, flags) = split_entry(entry);
+            let flags = flags
+                .map(|flags| self.parse_flags(flags))
+                .unwrap_or_default();
+            self.stems.push((stem, flags));
+        }
+    }
+
+    /// Returns the number of stems in the dictionary
+    pub fn stem_count(&self) -> usize {
+        self.stems.len()
+    }
+
+    /// Returns every distinct word formed from the stems that `options` keep and their affixes,
+    /// in the order of the stems
+    ///
+    /// Each stem comes before the words formed from it.
+    pub fn expand(&self, options: &HunspellOptions) -> Vec<String> {
+        let parse_all = |flags: &[String]| -> Vec<Flag> {
+            flags
+                .iter()
+                .filter_map(|flag| self.first_flag(flag))
+                .collect()
+        };
+        let required = parse_all(&options.required_flags);
+        let mut excluded = parse_all(&options.excluded_flags);
+        excluded.extend(self.forbidden);
+        excluded.extend(self.only_in_compound);
+
+        let mut seen = HashSet::new();
+        let mut words = Vec::new();
+        let mut emit = |word: String| {
+            if seen.insert(word.clone()) {
+                words.push(word);
+            }
+        };
+        for (stem, flags) in &self.stems {
+            if required.iter().all(|flag| flags.contains(flag))
+                && !excluded.iter().any(|flag| flags.contains(flag))
+            {
+                self.expand_stem(stem, flags, &mut emit);
+            }
+        }
+        words
+    }
+
+    /// Returns the words of [expand](HunspellDictionary::expand) as an [OwnedWordList]
+    pub fn to_wordlist(&self, options: &HunspellOptions) -> OwnedWordList {
+        OwnedWordList::new(self.expand(options))
+    }
+
+    fn expand_stem(&self, stem: &str, flags: &[Flag], emit: &mut impl FnMut(String)) {
+        if !self.needs_affix(flags) {
+            emit(stem.to_string());
+        }
+
+        // suffixed words that can also take a prefix
+        let mut cross_suffixed = Vec::new();
+        for (class, rule) in self.rules(&self.suffixes, flags) {
+            let Some(word) = self.apply_suffix(rule, stem) else {
+                continue;
+            };
+            if !self.needs_affix(&rule.continuation) {
+                emit(word.clone());
+            }
+            for (_, second_rule) in self.rules(&self.suffixes, &rule.continuation) {
+                if let Some(word) = self.apply_suffix(second_rule, &word) {
+                    if !self.needs_affix(&second_rule.continuation) {
+                        emit(word);
+                    }
+                }
+            }
+            if class.cross_product {
+                cross_suffixed.push(word);
+            }
+        }
+
+        for (class, rule) in self.rules(&self.prefixes, flags) {
+            if let Some(word) = self.apply_prefix(rule, stem) {
+                if !self.needs_affix(&rule.continuation) {
+                    emit(word);
+                }
+            }
+            if class.cross_product {
+                for suffixed in &cross_suffixed {
+                    if let Some(word) = self.apply_prefix(rule, suffixed) {
+                        emit(word);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the rules of the classes in `classes` named by `flags`, with their class
+    fn rules<'d>(
+        &'d self,
+        classes: &'d HashMap<Flag, AffixClass>,
+        flags: &'d [Flag]
+    ) -> impl Iterator<Item = (&'d AffixClass, &'d AffixRule)> {
+        flags
+            .iter()
+            .filter_map(|flag| classes.get(flag))
+            .flat_map(|class| class.rules.iter().map(move |rule| (class, rule)))
+    }
+
+    fn needs_affix(&self, flags: &[Flag]) -> bool {
+        self.need_affix.is_some_and(|flag| flags.contains(&flag))
+    }
+
+    fn apply_suffix(&self, rule: &AffixRule, word: &str) -> Option<String> {
+        let base = word.strip_suffix(rule.strip.as_str())?;
+        let fits = rule.condition.len() <= word.chars().count()
+            && word
+                .chars()
+                .rev()
+                .zip(rule.condition.iter().rev())
+                .all(|(character, part)| part.matches(character));
+        (fits && (self.full_strip || !base.is_empty())).then(|| format!("{}{}", base, rule.add))
+    }
+
+    fn apply_prefix(&self, rule: &AffixRule, word: &str) -> Option<String> {
+        let base = word.strip_prefix(rule.strip.as_str())?;
+        let fits = rule.condition.len() <= word.chars().count()
+            && word
+                .chars()
+                .zip(rule.condition.iter())
+                .all(|(character, part)| part.matches(character));
+        (fits && (self.full_strip || !base.is_empty())).then(|| format!("{}{}", rule.add, base))
+    }
+}
+
+/// Parses an affix condition, a simplified regular expression matching one character at a time
+fn parse_condition(condition: &str) -> Vec<ConditionPart> {
+    // a lone `.` means there is no condition
+    if condition == "." {
+        return Vec::new();
+    }
+    let mut parts = Vec::new();
+    let mut chars = condition.chars();
+    while let Some(character) = chars.next() {
+        parts.push(match character {
+            '.' => ConditionPart::Any,
+            '[' => {
+                let mut set: Vec<char> = chars.by_ref().take_while(|c| *c != ']').collect();
+                let negated = set.first() == Some(&'^');
+                if negated {
+                    set.remove(0);
+                }
+                ConditionPart::Set {
+                    chars: set,
+                    negated
+                }
+            }
+            character => ConditionPart::Char(character)
+        });
+    }
+    parts
+}
+
+/// Splits a `.dic` entry into its stem and flags, at the first `/` that isn't escaped as `\/`
+fn split_entry(entry: &str) -> (String, Option<&str>) {
+    let mut stem = String::new();
+    let mut chars = entry.char_indices();
+    while let Some((position, character)) = chars.next() {
+        match character {
+            '\\' if entry[position + 1..].starts_with('/') => {
+                stem.push('/');
+                chars.next();
+            }
+            '/' => return (stem, Some(&entry[position + 1..])),
+            character => stem.push(character)
+        }
+    }
+    (stem, None)
+}
+
+impl OwnedWordList {
+    /// Construct a new `OwnedWordList` from a Hunspell dictionary, expanding its stems
+    ///
+    /// `aff_file` and `dic_file` must be [Path]s to the dictionary's affix and stem files.
+    /// See [HunspellDictionary] for details.
+    pub fn from_hunspell(
+        aff_file: &Path,
+        dic_file: &Path,
+        options: &HunspellOptions
+    ) -> io::Result<Self> {
+        Ok(HunspellDictionary::from_files(aff_file, dic_file)?.to_wordlist(options))
+    }
+}
+
+#[cfg(test)]
+mod hunspell_tests {
+    use super::{HunspellDictionary, HunspellOptions};
+
+    fn expand(aff: &str, dic: &str, options: &HunspellOptions) -> Vec<String> {
+        HunspellDictionary::from_readers(aff.as_bytes(), dic.as_bytes())
+            .unwrap()
+            .expand(options)
+    }
+
+    const AFF: &str = "\
+# a small English-like affix file
+SET UTF-8
+NEEDAFFIX Z
+FORBIDDENWORD !
+
+PFX R Y 1
+PFX R 0 re .
+
+SFX D Y 4
+SFX D 0 d e
+SFX D y ied [^aeiou]y
+SFX D 0 ed [aeiou]y
+SFX D 0 ed [^ey]
+
+SFX G N 1
+SFX G e ing/T e
+
+SFX T N 1
+SFX T 0 s .
+";
+
+    #[test]
+    fn test_expand() {
+        let dic = "5\ncare/DRG\ncry/D\nplay/D\nride/GZ\ncreat/!\n";
+        assert_eq!(
+            expand(AFF, dic, &HunspellOptions::new()),
+            vec![
+                "care", "cared", "caring", "carings", "recare", "recared", "cry", "cried", "play",
+                "played", "riding", "ridings"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flag_filters() {
+        let dic = "care/DR\ncry/D\nplay\n";
+        assert_eq!(
+            expand(AFF, dic, &HunspellOptions::new().with_required_flag("D")),
+            vec!["care", "cared", "recare", "recared", "cry", "cried"]
+        );
+        assert_eq!(
+            expand(AFF, dic, &HunspellOptions::new().with_excluded_flag("R")),
+            vec!["cry", "cried", "play"]
+        );
+    }
+
+    #[test]
+    fn test_long_flags_and_aliases() {
+        let aff = "FLAG long\nAF 2\nAF Pl\nAF PlRe\nSFX Pl Y 1\nSFX Pl 0 s .\nPFX Re Y 1\nPFX Re 0 re .\n";
+        assert_eq!(
+            expand(aff, "2\nturn/2\nwin/1\n", &HunspellOptions::new()),
+            vec!["turn", "turns", "return", "returns", "win", "wins"]
+        );
+    }
+
+    #[test]
+    fn test_latin1_dictionary() {
+        let aff = b"SET ISO8859-1\nSFX E Y 1\nSFX E 0 \xe9 .\n";
+        let dic = b"1\ncaf/E\n";
+        let dictionary = HunspellDictionary::from_readers(&aff[..], &dic[..]).unwrap();
+        assert_eq!(
+            dictionary.expand(&HunspellOptions::new()),
+            vec!["caf", "café"]
+        );
+    }
+
+    #[test]
+    fn test_byte_order_marks() {
+        let aff = "\u{feff}SET UTF-8\nSFX S Y 1\nSFX S 0 s .\n";
+        let dic = "\u{feff}2\ncafé/S\nété\n";
+        assert_eq!(
+            expand(aff, dic, &HunspellOptions::new()),
+            vec!["café", "cafés", "été"]
+        );
+        // the byte order mark is enough to read the dictionary as UTF-8
+        let aff = "\u{feff}SFX S Y 1\nSFX S 0 s .\n";
+        assert_eq!(
+            expand(aff, "1\nété/S\n", &HunspellOptions::new()),
+            vec!["été", "étés"]
+        );
+    }
+}
@@ -0,0 +1,495 @@
+//! Precompiled binary wordlist indexes
+//!
+//! Parsing a large text wordlist takes time on every run. An index stores the words of a
+//! wordlist (along with their frequencies, if it has them) in a binary format that can be
+//! used directly, without parsing; see [write_index] and [IndexFile].
+
+use super::{WeightedWordlist, Wordlist};
+
+use std::{fs, io::Write, ops::Range, path::Path};
+
+mod format;
+pub(super) use format::signature;
+use format::Layout;
+pub use format::{Fingerprint, IndexError};
+
+/// Writes an index of the words in `wordlist` to `writer`
+///
+/// The index can be loaded with [IndexFile::open] or [IndexedWordList::from_bytes].
+/// Words keep their order (and any duplicates), so the loaded wordlist behaves exactly
+/// like `wordlist`, and has the same [fingerprint](Wordlist::fingerprint). The fingerprints are
+/// stored in the index, so they don't have to be computed again when it is loaded.
+///
+///# Examples
+/// ```
+/// use anagrambot::wordlist::{write_index, BorrowedWordList, IndexedWordList, Wordlist};
+///
+/// let wordlist: BorrowedWordList = ["race", "car", "care", "acre"].into_iter().collect();
+/// let mut index = Vec::new();
+/// write_index(&wordlist, &mut index).unwrap();
+///
+/// let indexed = IndexedWordList::from_bytes(&index).unwrap();
+/// assert!(indexed.iter().eq(wordlist.iter()));
+/// assert!(indexed.includes_word("acre"));
+/// ```
+pub fn write_index<T>(wordlist: &T, writer: &mut impl Write) -> Result<(), IndexError>
+where
+    T: Wordlist
+{
+    let words: Vec<&str> = wordlist.iter().collect();
+    writer.write_all(&format::encode(&words, None)?)?;
+    Ok(())
+}
+
+/// Writes an index of the words in `wordlist` and their weights to `writer`
+///
+/// Like [write_index], but the loaded [IndexedWordList] also has the weight of each word.
+pub fn write_weighted_index<T>(wordlist: &T, writer: &mut impl Write) -> Result<(), IndexError>
+where
+    T: WeightedWordlist
+{
+    let words: Vec<&str> = wordlist.iter().collect();
+    let weights: Vec<u64> = words
+        .iter()
+        .map(|word| wordlist.weight(word).unwrap_or(0))
+        .collect();
+    writer.write_all(&format::encode(&words, Some(&weights))?)?;
+    Ok(())
+}
+
+/// Returns true if `bytes` starts like an index file
+///
+/// Useful for telling index files apart from text wordlists.
+pub fn is_index(bytes: &[u8]) -> bool {
+    bytes.starts_with(&format::MAGIC)
+}
+
+/// A [Wordlist] implementor that uses the words of an index in place, without copying them
+///
+/// Created from the bytes of an index with [from_bytes](IndexedWordList::from_bytes), or from
+/// an [IndexFile]. Checking if a word is in the list, finding its weight, and finding the words
+/// with the same letters as a word (see [words_with_signature](IndexedWordList::words_with_signature))
+/// are all binary searches.
+///
+/// If the index was written with [write_weighted_index], this is also a [WeightedWordlist];
+/// otherwise, every word has no weight.
+#[derive(Clone, Copy)]
+pub struct IndexedWordList<'d> {
+    word_count: usize,
+    fingerprint: Fingerprint,
+    unordered_fingerprint: Fingerprint,
+    offsets: &'d [u8],
+    signature_order: &'d [u8],
+    frequencies: Option<&'d [u8]>,
+    words: &'d str
+}
+
+impl<'d> IndexedWordList<'d> {
+    /// Uses the index in `bytes` as a `Wordlist`
+    ///
+    /// Returns an error if `bytes` isn't an index, was written by an incompatible version of
+    /// anagrambot, or doesn't match its checksum. This checks every byte of the index, so for
+    /// an index that is used several times, prefer loading it once with [IndexFile].
+    pub fn from_bytes(bytes: &'d [u8]) -> Result<Self, IndexError> {
+        let layout = format::parse(bytes)?;
+        Ok(Self::from_layout(bytes, &layout))
+    }
+
+    /// Uses `bytes` as a `Wordlist`; `layout` must be the result of parsing `bytes`
+    fn from_layout(bytes: &'d [u8], layout: &Layout) -> Self {
+        let words = &bytes[layout.words.clone()];
+        Self {
+            word_count: layout.word_count,
+            fingerprint: layout.fingerprint,
+            unordered_fingerprint: layout.unordered_fingerprint,
+            offsets: &bytes[layout.offsets.clone()],
+            signature_order: &bytes[layout.signature_order.clone()],
+            frequencies: layout.frequencies.clone().map(|range| &bytes[range]),
+            // SAFETY: parsing checked that the words are valid UTF-8
+            words: unsafe { std::str::from_utf8_unchecked(words) }
+        }
+    }
+
+    /// Returns the number of words in this `Wordlist` (including duplicates)
+    pub fn len(&self) -> usize {
+        self.word_count
+    }
+
+    /// Returns true if this `Wordlist` has no words
+    pub fn is_empty(&self) -> bool {
+        self.word_count == 0
+    }
+
+    /// Returns an iterator over the words of the index, borrowing only the index
+    ///
+    /// Unlike [iter](Wordlist::iter), the words can outlive this `IndexedWordList`.
+    pub fn words(&self) -> IndexedWords<'d> {
+        IndexedWords {
+            wordlist: *self,
+            indices: 0..self.word_count
+        }
+    }
+
+    /// Checks that this index was built from `source`, which must have the same words in the
+    /// same order
+    ///
+    /// Returns [IndexError::WordlistMismatch] if the [fingerprint](Wordlist::fingerprint) of
+    /// `source` is not the one stored in the index, such as when words were added to a wordlist
+    /// file after it was indexed.
+    ///
+    ///# Examples
+    /// ```
+    /// use anagrambot::wordlist::{write_index, BorrowedWordList, IndexedWordList};
+    ///
+    /// let wordlist: BorrowedWordList = ["race", "care"].into_iter().collect();
+    /// let mut index = Vec::new();
+    /// write_index(&wordlist, &mut index).unwrap();
+    /// let indexed = IndexedWordList::from_bytes(&index).unwrap();
+    /// assert!(indexed.check_source(&wordlist).is_ok());
+    ///
+    /// let changed: BorrowedWordList = ["race", "care", "acre"].into_iter().collect();
+    /// assert!(indexed.check_source(&changed).is_err());
+    /// ```
+    pub fn check_source(&self, source: &impl Wordlist) -> Result<(), IndexError> {
+        let found = source.fingerprint();
+        if found != self.fingerprint {
+            return Err(IndexError::WordlistMismatch {
+                expected: self.fingerprint,
+                found
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns true if the index has a weight for each word
+    pub fn has_weights(&self) -> bool {
+        self.frequencies.is_some()
+    }
+
+    /// Returns the word at `index` (in wordlist order)
+    fn word(&self, index: usize) -> &'d str {
+        let start = format::read_u32(self.offsets, index) as usize;
+        let end = format::read_u32(self.offsets, index + 1) as usize;
+        &self.words[start..end]
+    }
+
+    /// Returns the range of positions in the signature order of words with `word`'s signature
+    fn signature_range(&self, word: &str) -> Range<usize> {
+        let target = format::signature(word);
+        let signature_at = |position| format::signature(self.word(self.index_at(position)));
+        let partition = |pred: &dyn Fn(&Vec<char>) -> bool| {
+            // binary search for the first position where pred is false
+            let (mut low, mut high) = (0, self.word_count);
+            while low < high {
+                let middle = low + (high - low) / 2;
+                if pred(&signature_at(middle)) {
+                    low = middle + 1;
+                } else {
+                    high = middle;
+                }
+            }
+            low
+        };
+        partition(&|signature| *signature < target)..partition(&|signature| *signature <= target)
+    }
+
+    /// Returns the index (in wordlist order) of the word at `position` in the signature order
+    fn index_at(&self, position: usize) -> usize {
+        format::read_u32(self.signature_order, position) as usize
+    }
+
+    /// Returns the index of the first appearance of `word`, if it is in this `Wordlist`
+    fn find(&self, word: &str) -> Option<usize> {
+        self.signature_range(word)
+            .map(|position| self.index_at(position))
+            .find(|index| self.word(*index) == word)
+    }
+
+    /// Returns every word with the same letters as `word` (ignoring spaces), in wordlist order
+    ///
+    /// These are the proper anagrams of `word` (including `word` itself, if it is in this
+    /// `Wordlist`), with case sensitive matching.
+    ///
+    ///# Examples
+    /// ```
+    /// use anagrambot::wordlist::{write_index, BorrowedWordList, IndexedWordList};
+    ///
+    /// let wordlist: BorrowedWordList = ["race", "car", "care", "acre"].into_iter().collect();
+    /// let mut index = Vec::new();
+    /// write_index(&wordlist, &mut index).unwrap();
+    /// let indexed = IndexedWordList::from_bytes(&index).unwrap();
+    ///
+    /// let words: Vec<&str> = indexed.words_with_signature("crae").collect();
+    /// assert_eq!(words, vec!["race", "care", "acre"]);
+    /// ```
+    pub fn words_with_signature(&self, word: &str) -> impl Iterator<Item = &'d str> + '_ {
+        self.signature_range(word)
+            .map(|position| self.word(self.index_at(position)))
+    }
+}
+
+/// An iterator over the words of an [IndexedWordList]
+pub struct IndexedWords<'d> {
+    wordlist: IndexedWordList<'d>,
+    indices: Range<usize>
+}
+
+impl<'d> Iterator for IndexedWords<'d> {
+    type Item = &'d str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.indices.next().map(|index| self.wordlist.word(index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
+
+impl<'d> Wordlist for IndexedWordList<'d> {
+    type IterType<'a>
+        = IndexedWords<'a>
+    where
+        Self: 'a;
+
+    fn includes_word(&self, word: &str) -> bool {
+        self.find(word).is_some()
+    }
+
+    fn iter(&self) -> Self::IterType<'_> {
+        self.words()
+    }
+
+    fn len(&self) -> usize {
+        IndexedWordList::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        IndexedWordList::is_empty(self)
+    }
+
+    fn words_with_signature(&self, word: &str) -> Vec<&str> {
+        IndexedWordList::words_with_signature(self, word).collect()
+    }
+
+    fn has_signature_lookup(&self) -> bool {
+        true
+    }
+
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint
+    }
+
+    fn unordered_fingerprint(&self) -> Fingerprint {
+        self.unordered_fingerprint
+    }
+}
+
+impl WeightedWordlist for IndexedWordList<'_> {
+    fn weight(&self, word: &str) -> Option<u64> {
+        let frequencies = self.frequencies?;
+        self.find(word)
+            .map(|index| format::read_u64(frequencies, index))
+    }
+}
+
+/// The bytes of an index file, in memory or mapped from disk
+enum IndexData {
+    Owned(Vec<u8>),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap)
+}
+
+impl IndexData {
+    fn bytes(&self) -> &[u8] {
+        match self {
+            IndexData::Owned(bytes) => bytes,
+            #[cfg(feature = "mmap")]
+            IndexData::Mapped(map) => map
+        }
+    }
+}
+
+/// A loaded index file
+///
+/// The index is checked once when it is loaded; afterwards, [wordlist](IndexFile::wordlist)
+/// is free.
+///
+///# Examples
+/// ```no_run
+/// use anagrambot::anagram::find_proper_anagrams;
+/// use anagrambot::wordlist::IndexFile;
+/// use std::path::Path;
+///
+/// let index = IndexFile::open(Path::new("words.idx")).unwrap();
+/// let wordlist = index.wordlist();
+/// let anagrams: Vec<&str> = find_proper_anagrams("race", &wordlist, true).collect();
+/// ```
+pub struct IndexFile {
+    data: IndexData,
+    layout: Layout
+}
+
+impl IndexFile {
+    /// Loads the index file at `path`
+    ///
+    /// The whole file is checked when it is loaded: its checksum, and that every word and offset
+    /// is valid. Loading therefore takes time proportional to the size of the file, though it
+    /// is much faster than loading a text wordlist since no words are parsed or allocated.
+    ///
+    /// With the `mmap` feature, the file is memory mapped instead of read into a buffer, so its
+    /// pages are shared with the operating system's file cache rather than copied. The file must
+    /// not be modified while it is mapped.
+    pub fn open(path: &Path) -> Result<Self, IndexError> {
+        #[cfg(feature = "mmap")]
+        {
+            let file = fs::File::open(path)?;
+            // SAFETY: the map is only read, and the file is documented not to be
+            // modified while it is in use
+            let map = unsafe { memmap2::Mmap::map(&file)? };
+            Self::new(IndexData::Mapped(map))
+        }
+        #[cfg(not(feature = "mmap"))]
+        Self::from_vec(fs::read(path)?)
+    }
+
+    /// Uses `bytes` as an index file
+    pub fn from_vec(bytes: Vec<u8>) -> Result<Self, IndexError> {
+        Self::new(IndexData::Owned(bytes))
+    }
+
+    fn new(data: IndexData) -> Result<Self, IndexError> {
+        let layout = format::parse(data.bytes())?;
+        Ok(Self { data, layout })
+    }
+
+    /// Returns the words of this index as a [Wordlist]
+    pub fn wordlist(&self) -> IndexedWordList<'_> {
+        IndexedWordList::from_layout(self.data.bytes(), &self.layout)
+    }
+}
+
+#[cfg(test)]
+mod index_tests {
+    use super::{
+        write_index, write_weighted_index, IndexError, IndexFile, IndexedWordList,
+        WeightedWordlist, Wordlist
+    };
+    use crate::wordlist::{BorrowedWordList, FrequencyWordList};
+
+    const TEST_WORD_SET: [&str; 8] = [
+        "race",
+        "car",
+        "care",
+        "Race",
+        "acre",
+        "car",
+        "",
+        "ice cream"
+    ];
+
+    fn test_index() -> Vec<u8> {
+        let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+        let mut index = Vec::new();
+        write_index(&wordlist, &mut index).unwrap();
+        index
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let index = test_index();
+        let indexed = IndexedWordList::from_bytes(&index).unwrap();
+
+        assert_eq!(indexed.len(), TEST_WORD_SET.len());
+        assert!(indexed.iter().eq(TEST_WORD_SET));
+        for word in TEST_WORD_SET {
+            assert!(indexed.includes_word(word));
+        }
+        for word in ["rac", "RACE", "icecream", "cars"] {
+            assert!(!indexed.includes_word(word));
+        }
+        assert!(!indexed.has_weights());
+        assert_eq!(indexed.weight("race"), None);
+    }
+
+    #[test]
+    fn test_fingerprints() {
+        let index = test_index();
+        let indexed = IndexedWordList::from_bytes(&index).unwrap();
+        let wordlist: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+
+        assert_eq!(indexed.fingerprint(), wordlist.fingerprint());
+        assert_eq!(
+            indexed.unordered_fingerprint(),
+            wordlist.unordered_fingerprint()
+        );
+        assert!(indexed.check_source(&wordlist).is_ok());
+
+        let reversed: BorrowedWordList = TEST_WORD_SET.into_iter().rev().collect();
+        assert_eq!(
+            indexed.unordered_fingerprint(),
+            reversed.unordered_fingerprint()
+        );
+        assert!(matches!(
+            indexed.check_source(&reversed),
+            Err(IndexError::WordlistMismatch { expected, found })
+                if expected == wordlist.fingerprint() && found == reversed.fingerprint()
+        ));
+    }
+
+    #[test]
+    fn test_weights() {
+        let wordlist: FrequencyWordList = [("race", 300), ("care", 500), ("acre", 10)]
+            .into_iter()
+            .map(|(word, count)| (String::from(word), count))
+            .collect();
+        let mut index = Vec::new();
+        write_weighted_index(&wordlist, &mut index).unwrap();
+
+        let file = IndexFile::from_vec(index).unwrap();
+        let indexed = file.wordlist();
+        assert!(indexed.has_weights());
+        assert_eq!(indexed.weight("care"), Some(500));
+        assert_eq!(indexed.weight("car"), None);
+        assert_eq!(indexed.total_weight(), 810);
+    }
+
+    #[test]
+    fn test_invalid_indexes() {
+        let index = test_index();
+
+        assert!(matches!(
+            IndexedWordList::from_bytes(b"race\ncar\n"),
+            Err(IndexError::NotAnIndex)
+        ));
+
+        let mut wrong_version = index.clone();
+        wrong_version[8] = 99;
+        assert!(matches!(
+            IndexedWordList::from_bytes(&wrong_version),
+            Err(IndexError::UnsupportedVersion(99))
+        ));
+
+        let mut modified = index.clone();
+        *modified.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            IndexedWordList::from_bytes(&modified),
+            Err(IndexError::ChecksumMismatch)
+        ));
+
+        assert!(matches!(
+            IndexedWordList::from_bytes(&index[..index.len() - 1]),
+            Err(IndexError::ChecksumMismatch)
+        ));
+
+        // the header (including the fingerprints) is also covered by the checksum
+        for position in [12, 40] {
+            let mut modified = index.clone();
+            modified[position] ^= 1;
+            assert!(matches!(
+                IndexedWordList::from_bytes(&modified),
+                Err(IndexError::ChecksumMismatch)
+            ));
+        }
+    }
+}
@@ -0,0 +1,342 @@
+//! The binary layout of wordlist index files
+//!
+//! This file only depends on `std`, so that it can also be included (with `#[path]`)
+//! by code outside of the library.
+//!
+//! An index file is a 56 byte header followed by four sections, with every number stored
+//! little endian:
+//!
+//! | Bytes | Content |
+//! |-------|---------|
+//! | 8 | magic number ([MAGIC]) |
+//! | 4 | format version ([FORMAT_VERSION]) |
+//! | 4 | flags (bit 0 is set if the index has frequencies) |
+//! | 8 | number of words (`n`) |
+//! | 8 | length of the word data in bytes |
+//! | 8 | [fingerprint](Fingerprint::of_words) of the words |
+//! | 8 | [unordered fingerprint](Fingerprint::of_words_unordered) of the words |
+//! | 8 | 64-bit FNV-1a checksum of the rest of the header, then everything after it |
+//! | 4 × (`n` + 1) | offset of each word in the word data, then the length of the word data |
+//! | 4 × `n` | index of each word, sorted by signature (see [signature]) |
+//! | 8 × `n` | frequency of each word (only if the index has frequencies) |
+//! | any | the words, as UTF-8 text with no separators |
+
+use std::{error::Error, fmt, io, num::ParseIntError, ops::Range, str::FromStr};
+
+/// The first 8 bytes of every index file
+pub(super) const MAGIC: [u8; 8] = *b"ANAGRIDX";
+/// The version of the index format written by this version of anagrambot
+pub(super) const FORMAT_VERSION: u32 = 3;
+
+const HEADER_LEN: usize = 56;
+/// The position of the checksum in the header
+const CHECKSUM_START: usize = 48;
+const FLAG_FREQUENCIES: u32 = 1;
+
+/// The error returned when an index can't be written or loaded
+#[derive(Debug)]
+pub enum IndexError {
+    /// Reading or writing the index file failed
+    Io(io::Error),
+    /// The file is not an index (it doesn't start with the index magic number)
+    NotAnIndex,
+    /// The index was written in a format version this version of anagrambot can't read
+    UnsupportedVersion(u32),
+    /// The content of the index doesn't match its checksum
+    ChecksumMismatch,
+    /// The index matches its checksum, but its content is invalid
+    Corrupt(&'static str),
+    /// The wordlist has too many words (or too much text) to be stored in an index
+    TooLarge,
+    /// The index was built from a different wordlist than the one it was checked against
+    WordlistMismatch {
+        /// The fingerprint of the words in the index
+        expected: Fingerprint,
+        /// The fingerprint of the wordlist the index was checked against
+        found: Fingerprint
+    }
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexError::Io(err) => write!(f, "{}", err),
+            IndexError::NotAnIndex => write!(f, "not a wordlist index file"),
+            IndexError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported index format version {} (expected version {}); rebuild the index",
+                version, FORMAT_VERSION
+            ),
+            IndexError::ChecksumMismatch => write!(
+                f,
+                "index checksum mismatch (the file is damaged or incomplete); rebuild the index"
+            ),
+            IndexError::Corrupt(reason) => write!(f, "corrupt index: {}", reason),
+            IndexError::TooLarge => write!(f, "wordlist is too large to be indexed"),
+            IndexError::WordlistMismatch { expected, found } => write!(
+                f,
+                "index was built from a different wordlist (expected fingerprint {}, found {}); \
+                 rebuild the index",
+                expected, found
+            )
+        }
+    }
+}
+
+impl Error for IndexError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            IndexError::Io(err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for IndexError {
+    fn from(err: io::Error) -> Self {
+        IndexError::Io(err)
+    }
+}
+
+/// Returns the signature of `word`: its letters (ignoring spaces) in sorted order
+///
+/// Letters are compared case sensitively: two different words are proper anagrams of each other
+/// in a case sensitive comparison exactly when they have the same signature.
+pub(crate) fn signature(word: &str) -> Vec<char> {
+    let mut letters: Vec<char> = word.chars().filter(|letter| *letter != ' ').collect();
+    letters.sort_unstable();
+    letters
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+
+/// Continues the 64-bit FNV-1a hash `hash` with `bytes`
+fn fnv1a(hash: u64, bytes: impl IntoIterator<Item = u8>) -> u64 {
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    bytes.into_iter().fold(hash, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Computes the checksum of an index: the 64-bit FNV-1a hash of its header (without the
+/// checksum itself) followed by its `body`
+fn checksum(header: &[u8], body: &[u8]) -> u64 {
+    let hash = fnv1a(FNV_OFFSET_BASIS, header[..CHECKSUM_START].iter().copied());
+    fnv1a(hash, body.iter().copied())
+}
+
+/// A fingerprint of the content of a wordlist
+///
+/// Persisted data built from a wordlist (such as an index or a search checkpoint) records the
+/// fingerprint of the wordlist, so that it can tell whether it is used with the same one.
+/// Fingerprints only depend on the words, and are the same on every platform and in every
+/// version of anagrambot. They are shown and parsed as 16 hexadecimal digits.
+///
+/// A fingerprint is not a cryptographic hash: it tells accidental changes apart, but different
+/// wordlists can be made to have the same fingerprint on purpose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fingerprint(u64);
+
+impl Fingerprint {
+    /// Computes the fingerprint of `words`, in order
+    ///
+    /// This is the 64-bit FNV-1a hash of every word, with each word followed by a `0xFF` byte
+    /// (which never appears in UTF-8 text).
+    pub fn of_words<'w>(words: impl IntoIterator<Item = &'w str>) -> Self {
+        Fingerprint(words.into_iter().fold(FNV_OFFSET_BASIS, |hash, word| {
+            fnv1a(hash, word.bytes().chain([0xFF]))
+        }))
+    }
+
+    /// Computes a fingerprint of `words` that doesn't depend on their order
+    ///
+    /// Two lists of words have the same unordered fingerprint if they have the same words, each
+    /// appearing the same number of times. This is the sum (wrapping on overflow) of the
+    /// FNV-1a hash of each word followed by a `0xFF` byte, after mixing each hash with the
+    /// finalizer of SplitMix64.
+    pub fn of_words_unordered<'w>(words: impl IntoIterator<Item = &'w str>) -> Self {
+        Fingerprint(words.into_iter().fold(0, |sum: u64, word| {
+            let mut hash = fnv1a(FNV_OFFSET_BASIS, word.bytes().chain([0xFF]));
+            hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d049bb133111eb);
+            sum.wrapping_add(hash ^ (hash >> 31))
+        }))
+    }
+
+    /// Returns the fingerprint with the value `value`
+    pub fn from_u64(value: u64) -> Self {
+        Fingerprint(value)
+    }
+
+    /// Returns the value of this fingerprint
+    pub fn to_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl FromStr for Fingerprint {
+    type Err = ParseIntError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        u64::from_str_radix(text, 16).map(Fingerprint)
+    }
+}
+
+/// Encodes `words` (and their `frequencies`, if given) as an index file
+///
+/// `frequencies` must have one entry per word.
+pub(super) fn encode(words: &[&str], frequencies: Option<&[u64]>) -> Result<Vec<u8>, IndexError> {
+    let data_len: usize = words.iter().map(|word| word.len()).sum();
+    if u32::try_from(words.len()).is_err() || u32::try_from(data_len).is_err() {
+        return Err(IndexError::TooLarge);
+    }
+
+    let mut body = Vec::with_capacity(words.len() * 16 + data_len);
+    let mut offset: u32 = 0;
+    for word in words {
+        body.extend(offset.to_le_bytes());
+        offset += word.len() as u32;
+    }
+    body.extend(offset.to_le_bytes());
+
+    // the sort is stable, so words with the same signature stay in wordlist order
+    let signatures: Vec<Vec<char>> = words.iter().map(|word| signature(word)).collect();
+    let mut signature_order: Vec<u32> = (0..words.len() as u32).collect();
+    signature_order.sort_by(|a, b| signatures[*a as usize].cmp(&signatures[*b as usize]));
+    for index in signature_order {
+        body.extend(index.to_le_bytes());
+    }
+
+    if let Some(frequencies) = frequencies {
+        for frequency in frequencies {
+            body.extend(frequency.to_le_bytes());
+        }
+    }
+    for word in words {
+        body.extend(word.as_bytes());
+    }
+
+    let flags = if frequencies.is_some() {
+        FLAG_FREQUENCIES
+    } else {
+        0
+    };
+    let mut index = Vec::with_capacity(HEADER_LEN + body.len());
+    index.extend(MAGIC);
+    index.extend(FORMAT_VERSION.to_le_bytes());
+    index.extend(flags.to_le_bytes());
+    index.extend((words.len() as u64).to_le_bytes());
+    index.extend((data_len as u64).to_le_bytes());
+    index.extend(Fingerprint::of_words(words.iter().copied()).0.to_le_bytes());
+    index.extend(
+        Fingerprint::of_words_unordered(words.iter().copied())
+            .0
+            .to_le_bytes()
+    );
+    index.extend(checksum(&index, &body).to_le_bytes());
+    index.extend(body);
+    Ok(index)
+}
+
+/// The positions of the sections of a valid index file
+#[derive(Debug, Clone)]
+pub(super) struct Layout {
+    pub(super) word_count: usize,
+    pub(super) fingerprint: Fingerprint,
+    pub(super) unordered_fingerprint: Fingerprint,
+    pub(super) offsets: Range<usize>,
+    pub(super) signature_order: Range<usize>,
+    pub(super) frequencies: Option<Range<usize>>,
+    pub(super) words: Range<usize>
+}
+
+/// Reads the `index`th little endian `u32` of `bytes`
+pub(super) fn read_u32(bytes: &[u8], index: usize) -> u32 {
+    let start = index * 4;
+    u32::from_le_bytes(bytes[start..start + 4].try_into().unwrap())
+}
+
+/// Reads the `index`th little endian `u64` of `bytes`
+pub(super) fn read_u64(bytes: &[u8], index: usize) -> u64 {
+    let start = index * 8;
+    u64::from_le_bytes(bytes[start..start + 8].try_into().unwrap())
+}
+
+/// Checks that `bytes` is a valid index file, returning the positions of its sections
+///
+/// After this succeeds, the word data is valid UTF-8, every word offset is in bounds,
+/// in order, and on a character boundary, and every word index is less than the word count.
+pub(super) fn parse(bytes: &[u8]) -> Result<Layout, IndexError> {
+    if bytes.len() < MAGIC.len() || bytes[..MAGIC.len()] != MAGIC {
+        return Err(IndexError::NotAnIndex);
+    }
+    if bytes.len() < HEADER_LEN {
+        return Err(IndexError::ChecksumMismatch);
+    }
+    let version = read_u32(bytes, 2);
+    if version != FORMAT_VERSION {
+        return Err(IndexError::UnsupportedVersion(version));
+    }
+    let flags = read_u32(bytes, 3);
+    let word_count = read_u64(bytes, 2);
+    let data_len = read_u64(bytes, 3);
+    if checksum(bytes, &bytes[HEADER_LEN..]) != read_u64(bytes, 6) {
+        return Err(IndexError::ChecksumMismatch);
+    }
+
+    let word_count = usize::try_from(word_count).map_err(|_| IndexError::TooLarge)?;
+    let data_len = usize::try_from(data_len).map_err(|_| IndexError::TooLarge)?;
+    // bounding these by the file length keeps the section sizes below from overflowing; the
+    // sections are checked against the file length once their sizes are known
+    if word_count > bytes.len() || data_len > bytes.len() {
+        return Err(IndexError::Corrupt("sections don't match the file length"));
+    }
+    let has_frequencies = flags & FLAG_FREQUENCIES != 0;
+    let frequencies_len = if has_frequencies { word_count * 8 } else { 0 };
+    let section = |start: usize, len: usize| start..start + len;
+    let offsets = section(HEADER_LEN, (word_count + 1) * 4);
+    let signature_order = section(offsets.end, word_count * 4);
+    let frequencies = section(signature_order.end, frequencies_len);
+    let words = section(frequencies.end, data_len);
+    if words.end != bytes.len() {
+        return Err(IndexError::Corrupt("sections don't match the file length"));
+    }
+
+    let data = std::str::from_utf8(&bytes[words.clone()])
+        .map_err(|_| IndexError::Corrupt("words are not valid UTF-8"))?;
+    let offset_bytes = &bytes[offsets.clone()];
+    let mut previous = 0;
+    for index in 0..=word_count {
+        let offset = read_u32(offset_bytes, index) as usize;
+        if offset < previous || !data.is_char_boundary(offset) {
+            return Err(IndexError::Corrupt("invalid word offset"));
+        }
+        previous = offset;
+    }
+    if previous != data_len {
+        return Err(IndexError::Corrupt(
+            "word offsets don't match the word data"
+        ));
+    }
+    let order_bytes = &bytes[signature_order.clone()];
+    if (0..word_count).any(|index| read_u32(order_bytes, index) as usize >= word_count) {
+        return Err(IndexError::Corrupt("invalid word index"));
+    }
+
+    Ok(Layout {
+        word_count,
+        fingerprint: Fingerprint(read_u64(bytes, 4)),
+        unordered_fingerprint: Fingerprint(read_u64(bytes, 5)),
+        offsets,
+        signature_order,
+        frequencies: has_frequencies.then_some(frequencies),
+        words
+    })
+}
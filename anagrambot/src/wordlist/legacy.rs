@@ -0,0 +1,134 @@
+//! The `Wordlist` trait as it was before it used a generic associated type
+//!
+//! Kept so that code written for the old trait can be moved over gradually.
+
+// the items of this module are deprecated, but still implemented here
+#![allow(deprecated)]
+
+use super::Wordlist;
+
+/// The old form of [Wordlist], with a lifetime parameter instead of a generic associated type
+///
+/// Every [Wordlist] implements `LegacyWordlist`, so generic code bounded by
+/// `T: LegacyWordlist<'a>` keeps working with every wordlist. A type that only implements
+/// `LegacyWordlist` can still be searched by wrapping a reference to it in a [Legacy].
+///
+/// To move an implementation over to [Wordlist], turn `IterType` into `IterType<'a>` (adding
+/// `where Self: 'a` if the type has lifetime or type parameters) and replace `&'a self` with
+/// `&self`.
+#[deprecated(note = "implement or use Wordlist instead")]
+pub trait LegacyWordlist<'a> {
+    /// The type of iterator that the `iter` method returns
+    ///
+    /// Must be an [Iterator] yielding `&str`
+    type IterType: Iterator<Item = &'a str>;
+
+    /// Returns an an iterator that returns all words
+    ///
+    /// Unlike the IntoIterator trait, does not consume the `Wordlist`
+    fn iter(&'a self) -> Self::IterType;
+
+    /// Given a word, returns true if the word is contained within this `Wordlist`
+    fn includes_word(&self, word: &str) -> bool;
+}
+
+impl<'a, W> LegacyWordlist<'a> for W
+where
+    W: Wordlist + ?Sized + 'a
+{
+    type IterType = W::IterType<'a>;
+
+    fn iter(&'a self) -> Self::IterType {
+        Wordlist::iter(self)
+    }
+
+    fn includes_word(&self, word: &str) -> bool {
+        Wordlist::includes_word(self, word)
+    }
+}
+
+/// A [Wordlist] view of a reference to a [LegacyWordlist]
+///
+/// Lets the anagram searches use types that only implement the old trait.
+///
+///# Examples
+/// ```
+/// #![allow(deprecated)]
+/// use anagrambot::anagram::find_proper_anagrams;
+/// use anagrambot::wordlist::{Legacy, LegacyWordlist};
+///
+/// struct OldWordList(Vec<String>);
+///
+/// impl<'a> LegacyWordlist<'a> for OldWordList {
+///     type IterType = std::iter::Map<std::slice::Iter<'a, String>, fn(&String) -> &str>;
+///
+///     fn iter(&'a self) -> Self::IterType {
+///         self.0.iter().map(|word| word.as_str())
+///     }
+///
+///     fn includes_word(&self, word: &str) -> bool {
+///         self.0.iter().any(|candidate| candidate == word)
+///     }
+/// }
+///
+/// let old = OldWordList(["race", "care", "cow"].map(String::from).to_vec());
+/// let wordlist = Legacy::new(&old);
+/// let anagrams: Vec<&str> = find_proper_anagrams("acre", &wordlist, true).collect();
+/// assert_eq!(anagrams, vec!["race", "care"]);
+/// ```
+#[deprecated(note = "implement Wordlist for the wrapped type instead")]
+pub struct Legacy<'w, W: ?Sized> {
+    wordlist: &'w W
+}
+
+impl<'w, W> Legacy<'w, W>
+where
+    W: LegacyWordlist<'w> + ?Sized
+{
+    /// Construct a new `Legacy` view of `wordlist`
+    pub fn new(wordlist: &'w W) -> Self {
+        Self { wordlist }
+    }
+}
+
+impl<'w, W> Wordlist for Legacy<'w, W>
+where
+    W: LegacyWordlist<'w> + ?Sized
+{
+    type IterType<'a>
+        = std::iter::Map<W::IterType, fn(&'w str) -> &'a str>
+    where
+        Self: 'a;
+
+    fn iter(&self) -> Self::IterType<'_> {
+        self.wordlist.iter().map(|word| word)
+    }
+
+    fn includes_word(&self, word: &str) -> bool {
+        self.wordlist.includes_word(word)
+    }
+}
+
+#[cfg(test)]
+mod legacy_tests {
+    use super::{Legacy, LegacyWordlist};
+    use crate::wordlist::{BorrowedWordList, Wordlist};
+
+    fn legacy_words<'a, T>(wordlist: &'a T) -> Vec<&'a str>
+    where
+        T: LegacyWordlist<'a>
+    {
+        wordlist.iter().collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let wordlist: BorrowedWordList = ["race", "care"].into_iter().collect();
+        assert_eq!(legacy_words(&wordlist), vec!["race", "care"]);
+
+        let view = Legacy::new(&wordlist);
+        assert!(Wordlist::iter(&view).eq(Wordlist::iter(&wordlist)));
+        assert!(Wordlist::includes_word(&view, "care"));
+        assert!(!Wordlist::includes_word(&view, "acre"));
+    }
+}
@@ -0,0 +1,510 @@
+//! Options for cleaning up wordlists as they are loaded
+
+use std::{
+    collections::HashSet,
+    fmt, fs,
+    io::{self, BufRead, BufReader, Read},
+    path::Path
+};
+
+mod compression;
+pub use compression::{decompressed, open_decompressed, Compression};
+
+mod encoding;
+pub use encoding::Encoding;
+
+/// How duplicate words are detected by [WordlistLoadOptions::with_dedupe]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dedupe {
+    /// Words are duplicates only if they are exactly the same
+    CaseSensitive,
+    /// Words are duplicates if they are the same ignoring case (e.g. "Race" and "race")
+    CaseInsensitive
+}
+
+/// A kind of character that can be used to leave words out of a wordlist
+///
+/// See [WordlistLoadOptions::with_excluded].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharacterClass {
+    /// Apostrophes (`'` and `’`), such as in possessives like "cat's"
+    Apostrophe,
+    /// Uppercase letters, such as in proper nouns like "London"
+    Uppercase,
+    /// Digits
+    Digit,
+    /// Anything other than letters and spaces
+    NonAlphabetic,
+    /// Anything other than ASCII characters
+    NonAscii
+}
+
+impl CharacterClass {
+    /// Returns true if `character` belongs to this class
+    pub fn contains(&self, character: char) -> bool {
+        match self {
+            CharacterClass::Apostrophe => matches!(character, '\'' | '’'),
+            CharacterClass::Uppercase => character.is_uppercase(),
+            CharacterClass::Digit => character.is_numeric(),
+            CharacterClass::NonAlphabetic => !(character.is_alphabetic() || character == ' '),
+            CharacterClass::NonAscii => !character.is_ascii()
+        }
+    }
+}
+
+/// Options controlling how wordlist files are read, and which lines become words
+///
+/// By default ([new](WordlistLoadOptions::new)), files must be UTF-8, and every line is used
+/// as a word exactly as it is, which is how the `from_file` constructors of every wordlist
+/// behave. Options can be added with the `with_` methods, and
+/// [cleaned](WordlistLoadOptions::cleaned) is a good starting point for most wordlist files.
+///
+/// Options are applied to each line in this order: trimming, skipping blank lines and
+/// comments, then leaving out words that are too short, contain excluded characters,
+/// or are duplicates of an earlier word.
+///
+///# Examples
+/// ```
+/// use anagrambot::wordlist::{BorrowedWordList, CharacterClass, Dedupe, WordlistLoadOptions, Wordlist};
+///
+/// let text = "# animals\r\ncat\r\ncat's\r\n\r\nCat\r\nox\r\ncat\r\n";
+/// let options = WordlistLoadOptions::cleaned()
+///     .with_dedupe(Dedupe::CaseInsensitive)
+///     .with_excluded(CharacterClass::Apostrophe)
+///     .with_min_length(3);
+///
+/// let wordlist = BorrowedWordList::from_text_with_options(text, &options);
+/// let words: Vec<&str> = wordlist.iter().collect();
+/// assert_eq!(words, vec!["cat"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WordlistLoadOptions {
+    trim: bool,
+    skip_blank_lines: bool,
+    comment_prefix: Option<String>,
+    dedupe: Option<Dedupe>,
+    min_length: usize,
+    excluded: Vec<CharacterClass>,
+    encoding: Encoding,
+    lenient: bool
+}
+
+impl WordlistLoadOptions {
+    /// Returns options that use every line as a word, exactly as it is
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns options that trim whitespace, skip blank lines and `#` comments,
+    /// and leave out exact duplicates
+    pub fn cleaned() -> Self {
+        Self::new()
+            .with_trim(true)
+            .with_skip_blank_lines(true)
+            .with_comment_prefix("#")
+            .with_dedupe(Dedupe::CaseSensitive)
+    }
+
+    /// Sets whether whitespace (including a trailing `\r`) is removed from the start and end of
+    /// each line, returning the modified options
+    pub fn with_trim(mut self, trim: bool) -> Self {
+        self.trim = trim;
+        self
+    }
+
+    /// Sets whether empty lines are skipped, returning the modified options
+    pub fn with_skip_blank_lines(mut self, skip_blank_lines: bool) -> Self {
+        self.skip_blank_lines = skip_blank_lines;
+        self
+    }
+
+    /// Skips lines starting with `prefix`, returning the modified options
+    pub fn with_comment_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.comment_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Leaves out words that are duplicates of an earlier word, returning the modified options
+    ///
+    /// Only applies to lists with one word per line. The rows of tagged and frequency lists for
+    /// the same word are always merged instead, so that no tags or counts are lost.
+    pub fn with_dedupe(mut self, dedupe: Dedupe) -> Self {
+        self.dedupe = Some(dedupe);
+        self
+    }
+
+    /// Leaves out words with fewer than `min_length` characters, returning the modified options
+    pub fn with_min_length(mut self, min_length: usize) -> Self {
+        self.min_length = min_length;
+        self
+    }
+
+    /// Leaves out words containing any character in `class`, returning the modified options
+    ///
+    /// Can be called more than once to exclude several classes.
+    pub fn with_excluded(mut self, class: CharacterClass) -> Self {
+        if !self.excluded.contains(&class) {
+            self.excluded.push(class);
+        }
+        self
+    }
+
+    /// Sets the text encoding of wordlist files, returning the modified options
+    ///
+    /// See [Encoding] for the available encodings. The default is UTF-8.
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Sets whether invalid lines are skipped instead of failing the whole load,
+    /// returning the modified options
+    ///
+    /// A line is invalid if it can't be decoded, or (for wordlists with more than one column,
+    /// such as a [FrequencyWordList](super::FrequencyWordList)) isn't in the expected format.
+    /// Skipped lines are listed in the [LoadReport] returned by the `from_file_with_report`
+    /// constructors.
+    ///
+    ///# Examples
+    /// ```
+    /// use anagrambot::wordlist::{OwnedWordList, WordlistLoadOptions, Wordlist};
+    /// # let dir = std::env::temp_dir()
+    /// #     .join(format!("anagrambot-lenient-doctest-{}", std::process::id()));
+    /// # std::fs::create_dir_all(&dir).unwrap();
+    /// let path = dir.join("words.txt");
+    /// std::fs::write(&path, b"race\ncaf\xe9\ncar\n").unwrap();
+    ///
+    /// // the second line isn't valid UTF-8
+    /// assert!(OwnedWordList::from_file_with_options(&path, &WordlistLoadOptions::new()).is_err());
+    ///
+    /// let options = WordlistLoadOptions::new().with_lenient(true);
+    /// let (wordlist, report) = OwnedWordList::from_file_with_report(&path, &options).unwrap();
+    /// assert_eq!(wordlist.iter().collect::<Vec<&str>>(), vec!["race", "car"]);
+    /// assert_eq!(report.skipped()[0].to_string(), "line 2: invalid UTF-8");
+    /// # std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    pub fn with_lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Returns the words in `lines` that these options keep, in order
+    pub fn filter<'w>(&self, lines: impl IntoIterator<Item = &'w str>) -> Vec<&'w str> {
+        let mut filter = self.line_filter();
+        let mut word_vec = Vec::new();
+        for line in lines {
+            if let Some(word) = filter.line(line) {
+                if filter.keep_word(word) {
+                    word_vec.push(word);
+                }
+            }
+        }
+        word_vec
+    }
+
+    /// Reads the words these options keep from the text file at `path`, one word per line
+    ///
+    /// Compressed files are decompressed first (see [Compression]).
+    /// Any wordlist implementing `FromIterator<String>` can be created from the result.
+    pub fn read_file(&self, path: &Path) -> io::Result<Vec<String>> {
+        self.read_file_with_report(path)
+            .map(|(word_vec, _)| word_vec)
+    }
+
+    /// Like [read_file](WordlistLoadOptions::read_file), but also returns a [LoadReport]
+    pub fn read_file_with_report(&self, path: &Path) -> io::Result<(Vec<String>, LoadReport)> {
+        self.read_with_report(BufReader::new(fs::File::open(path)?))
+    }
+
+    /// Reads the words these options keep from `reader`, one word per line
+    ///
+    /// Like [read_file](WordlistLoadOptions::read_file), but for any reader, such as
+    /// standard input.
+    ///
+    ///# Examples
+    /// ```
+    /// use anagrambot::wordlist::WordlistLoadOptions;
+    ///
+    /// let text = "race\n# comment\ncare\n";
+    /// let words = WordlistLoadOptions::cleaned().read(text.as_bytes()).unwrap();
+    /// assert_eq!(words, vec!["race", "care"]);
+    /// ```
+    pub fn read(&self, reader: impl BufRead) -> io::Result<Vec<String>> {
+        self.read_with_report(reader).map(|(word_vec, _)| word_vec)
+    }
+
+    /// Like [read](WordlistLoadOptions::read), but also returns a [LoadReport]
+    pub fn read_with_report(&self, reader: impl BufRead) -> io::Result<(Vec<String>, LoadReport)> {
+        let mut word_vec = Vec::new();
+        let report = self.read_lines(reader, |filter, line| {
+            if filter.keep_word(line) {
+                word_vec.push(String::from(line));
+            }
+            Ok(())
+        })?;
+        Ok((word_vec, report))
+    }
+
+    /// Reads the lines of the text in `reader`, passing each one that isn't skipped as blank
+    /// or a comment to `parse_line`, along with the filter for the rest of the options
+    ///
+    /// `parse_line` returns the reason a line is invalid if it is. Invalid lines are listed in
+    /// the returned report in lenient mode, and fail the load with an error of kind
+    /// [InvalidData](io::ErrorKind::InvalidData) otherwise.
+    pub(super) fn read_lines(
+        &self,
+        reader: impl BufRead,
+        mut parse_line: impl FnMut(&mut LineFilter, &str) -> Result<(), String>
+    ) -> io::Result<LoadReport> {
+        let mut bytes = Vec::new();
+        decompressed(reader)?.read_to_end(&mut bytes)?;
+        let (encoding, lines) = encoding::decode_lines(&bytes, self.encoding);
+        let mut filter = self.line_filter();
+        let mut report = LoadReport {
+            encoding,
+            skipped: Vec::new()
+        };
+
+        for (line_index, line) in lines.enumerate() {
+            let result = line
+                .map_err(String::from)
+                .and_then(|line| match filter.line(&line) {
+                    Some(line) => parse_line(&mut filter, line),
+                    None => Ok(())
+                });
+            if let Err(reason) = result {
+                let skipped = SkippedLine {
+                    line_number: line_index + 1,
+                    reason
+                };
+                if !self.lenient {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        skipped.to_string()
+                    ));
+                }
+                report.skipped.push(skipped);
+            }
+        }
+        Ok(report)
+    }
+
+    pub(super) fn line_filter(&self) -> LineFilter<'_> {
+        LineFilter {
+            options: self,
+            seen: HashSet::new()
+        }
+    }
+}
+
+/// A line that was skipped while loading a wordlist in lenient mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedLine {
+    line_number: usize,
+    reason: String
+}
+
+impl SkippedLine {
+    /// Returns the number of the line (starting at 1)
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// Returns the reason the line was skipped
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl fmt::Display for SkippedLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line_number, self.reason)
+    }
+}
+
+/// Details of how a wordlist file was loaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadReport {
+    encoding: Encoding,
+    skipped: Vec<SkippedLine>
+}
+
+impl LoadReport {
+    /// Returns the encoding the file was read with (never [Encoding::Detect])
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Returns the lines that were skipped because they were invalid, in order
+    ///
+    /// Always empty unless the file was loaded in lenient mode.
+    pub fn skipped(&self) -> &[SkippedLine] {
+        &self.skipped
+    }
+}
+
+/// Applies [WordlistLoadOptions] to the lines of one wordlist
+pub(super) struct LineFilter<'o> {
+    options: &'o WordlistLoadOptions,
+    // words kept so far, lowercased if deduping case insensitively
+    seen: HashSet<String>
+}
+
+impl LineFilter<'_> {
+    /// Returns the content of `line`, or `None` if it is blank or a comment and should be skipped
+    pub(super) fn line<'l>(&self, line: &'l str) -> Option<&'l str> {
+        let line = self.trim(line);
+        let blank = self.options.skip_blank_lines && line.is_empty();
+        let comment = self
+            .options
+            .comment_prefix
+            .as_deref()
+            .is_some_and(|prefix| line.starts_with(prefix));
+        (!blank && !comment).then_some(line)
+    }
+
+    /// Trims `text` if the options trim lines
+    pub(super) fn trim<'l>(&self, text: &'l str) -> &'l str {
+        if self.options.trim {
+            text.trim()
+        } else {
+            text
+        }
+    }
+
+    /// Returns true if `word` is long enough and has no excluded characters
+    ///
+    /// Unlike [keep_word](LineFilter::keep_word), does not leave out duplicates. Used for lists
+    /// with more than one column, whose rows for the same word are merged instead.
+    pub(super) fn accepts_word(&self, word: &str) -> bool {
+        word.chars().count() >= self.options.min_length
+            && !word.chars().any(|character| {
+                self.options
+                    .excluded
+                    .iter()
+                    .any(|class| class.contains(character))
+            })
+    }
+
+    /// Returns true if `word` should be kept, remembering it to detect later duplicates
+    pub(super) fn keep_word(&mut self, word: &str) -> bool {
+        if !self.accepts_word(word) {
+            return false;
+        }
+        match self.options.dedupe {
+            None => true,
+            Some(Dedupe::CaseSensitive) => self.seen.insert(String::from(word)),
+            Some(Dedupe::CaseInsensitive) => self.seen.insert(word.to_lowercase())
+        }
+    }
+}
+
+#[cfg(test)]
+mod load_options_tests {
+    use super::{CharacterClass, Dedupe, Encoding, WordlistLoadOptions};
+    use std::{fs, io};
+
+    const TEST_LINES: [&str; 9] = [
+        "race ",
+        "car\r",
+        "",
+        "# comment",
+        "cat's",
+        "Race",
+        "race",
+        "r2d2",
+        "été"
+    ];
+
+    #[test]
+    fn test_default_keeps_lines_verbatim() {
+        assert_eq!(WordlistLoadOptions::new().filter(TEST_LINES), TEST_LINES);
+    }
+
+    #[test]
+    fn test_cleaned() {
+        assert_eq!(
+            WordlistLoadOptions::cleaned().filter(TEST_LINES),
+            vec!["race", "car", "cat's", "Race", "r2d2", "été"]
+        );
+        assert_eq!(
+            WordlistLoadOptions::cleaned()
+                .with_dedupe(Dedupe::CaseInsensitive)
+                .filter(TEST_LINES),
+            vec!["race", "car", "cat's", "r2d2", "été"]
+        );
+    }
+
+    #[test]
+    fn test_excluded_classes() {
+        let filter = |class| {
+            WordlistLoadOptions::cleaned()
+                .with_excluded(class)
+                .filter(TEST_LINES)
+        };
+        assert_eq!(
+            filter(CharacterClass::Apostrophe),
+            vec!["race", "car", "Race", "r2d2", "été"]
+        );
+        assert_eq!(
+            filter(CharacterClass::Uppercase),
+            vec!["race", "car", "cat's", "r2d2", "été"]
+        );
+        assert_eq!(
+            filter(CharacterClass::NonAlphabetic),
+            vec!["race", "car", "Race", "été"]
+        );
+        assert_eq!(
+            filter(CharacterClass::NonAscii),
+            vec!["race", "car", "cat's", "Race", "r2d2"]
+        );
+        assert_eq!(
+            WordlistLoadOptions::cleaned()
+                .with_min_length(4)
+                .filter(TEST_LINES),
+            vec!["race", "cat's", "Race", "r2d2"]
+        );
+    }
+
+    #[test]
+    fn test_read_file_encodings() {
+        let dir = std::env::temp_dir().join(format!("anagrambot-load-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("words.txt");
+        let options = WordlistLoadOptions::cleaned().with_encoding(Encoding::Detect);
+
+        fs::write(&path, b"caf\xe9\r\n# comment\r\nrace\r\n").unwrap();
+        let (words, report) = options.read_file_with_report(&path).unwrap();
+        assert_eq!(words, vec!["café", "race"]);
+        assert_eq!(report.encoding(), Encoding::Latin1);
+
+        let utf16: Vec<u8> = "\u{feff}café\nrace\n"
+            .encode_utf16()
+            .flat_map(u16::to_be_bytes)
+            .collect();
+        fs::write(&path, utf16).unwrap();
+        let (words, report) = options.read_file_with_report(&path).unwrap();
+        assert_eq!(words, vec!["café", "race"]);
+        assert_eq!(report.encoding(), Encoding::Utf16Be);
+
+        // one invalid line doesn't make the rest of a UTF-8 file Latin-1
+        fs::write(&path, b"caf\xc3\xa9\nrace\n\xffcar\n\xc3\xa9t\xc3\xa9\n").unwrap();
+        let lenient = options.clone().with_lenient(true);
+        let (words, report) = lenient.read_file_with_report(&path).unwrap();
+        assert_eq!(words, vec!["café", "race", "été"]);
+        assert_eq!(report.encoding(), Encoding::Utf8);
+        assert_eq!(report.skipped()[0].to_string(), "line 3: invalid UTF-8");
+        let err = options.read_file(&path).unwrap_err();
+        assert_eq!(err.to_string(), "line 3: invalid UTF-8");
+
+        fs::write(&path, b"race\n\xffcar\n").unwrap();
+        let err = WordlistLoadOptions::new().read_file(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "line 2: invalid UTF-8");
+
+        let missing = WordlistLoadOptions::new().read_file(&dir.join("missing.txt"));
+        assert_eq!(missing.unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,173 @@
+//! Reading compressed wordlist files
+
+use std::{
+    fmt, fs,
+    io::{self, BufRead, BufReader},
+    path::Path
+};
+
+/// The compression format of a wordlist file
+///
+/// Reading gzip files requires the `gzip` feature, and reading zstd files requires the `zstd`
+/// feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// Not compressed
+    None,
+    /// Compressed with gzip (usually a `.gz` file)
+    Gzip,
+    /// Compressed with Zstandard (usually a `.zst` file)
+    Zstd
+}
+
+const GZIP_MAGIC: &[u8] = &[0x1F, 0x8B];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xB5, 0x2F, 0xFD];
+
+impl Compression {
+    /// Detects the compression format of a file from its first bytes
+    ///
+    /// Files are detected by their content rather than their extension, so a compressed file
+    /// is read correctly whatever it is called.
+    pub fn detect(start: &[u8]) -> Compression {
+        if start.starts_with(GZIP_MAGIC) {
+            Compression::Gzip
+        } else if start.starts_with(ZSTD_MAGIC) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+
+    /// Returns true if this version of anagrambot was built with support for this format
+    pub fn is_supported(self) -> bool {
+        match self {
+            Compression::None => true,
+            Compression::Gzip => cfg!(feature = "gzip"),
+            Compression::Zstd => cfg!(feature = "zstd")
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Compression::None => "uncompressed",
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd"
+        })
+    }
+}
+
+/// Opens the file at `path` for reading, decompressing it if it is compressed
+///
+/// The compression format is detected with [Compression::detect]. Returns an error of kind
+/// [Unsupported](io::ErrorKind::Unsupported) if the file is compressed in a format that this
+/// version of anagrambot wasn't built to read.
+pub fn open_decompressed(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    decompressed(BufReader::new(fs::File::open(path)?))
+}
+
+/// Wraps `reader` so that its content is decompressed as it is read, if it is compressed
+///
+/// Like [open_decompressed], but for any reader, such as standard input.
+pub fn decompressed<'r>(mut reader: impl BufRead + 'r) -> io::Result<Box<dyn BufRead + 'r>> {
+    match Compression::detect(reader.fill_buf()?) {
+        Compression::None => Ok(Box::new(reader)),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => Ok(Box::new(BufReader::new(
+            flate2::bufread::MultiGzDecoder::new(reader)
+        ))),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => {
+            let decoder = ruzstd::decoding::StreamingDecoder::new(reader).map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid zstd data: {}", err)
+                )
+            })?;
+            Ok(Box::new(BufReader::new(decoder)))
+        }
+        #[allow(unreachable_patterns)]
+        compression => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "reading {} compressed wordlists requires the `{}` feature of anagrambot",
+                compression, compression
+            )
+        ))
+    }
+}
+
+#[cfg(test)]
+mod compression_tests {
+    use super::{decompressed, open_decompressed, Compression};
+    use std::{
+        fs,
+        io::{self, Read}
+    };
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Compression::detect(b"race\ncar\n"), Compression::None);
+        assert_eq!(Compression::detect(b""), Compression::None);
+        assert_eq!(Compression::detect(&[0x1F, 0x8B, 8, 0]), Compression::Gzip);
+        assert_eq!(
+            Compression::detect(&[0x28, 0xB5, 0x2F, 0xFD, 0]),
+            Compression::Zstd
+        );
+    }
+
+    #[test]
+    fn test_open_decompressed() {
+        let dir =
+            std::env::temp_dir().join(format!("anagrambot-compression-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let text = "race\ncare\nacre\n";
+        let read = |bytes: &[u8]| {
+            let path = dir.join("words");
+            fs::write(&path, bytes).unwrap();
+            let mut content = String::new();
+            open_decompressed(&path)?.read_to_string(&mut content)?;
+            io::Result::Ok(content)
+        };
+
+        assert_eq!(read(text.as_bytes()).unwrap(), text);
+        let mut content = String::new();
+        decompressed(text.as_bytes())
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, text);
+
+        #[cfg(feature = "gzip")]
+        {
+            use std::io::Write;
+
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(text.as_bytes()).unwrap();
+            assert_eq!(read(&encoder.finish().unwrap()).unwrap(), text);
+        }
+        #[cfg(not(feature = "gzip"))]
+        assert_eq!(
+            read(&[0x1F, 0x8B, 8, 0]).unwrap_err().kind(),
+            io::ErrorKind::Unsupported
+        );
+
+        #[cfg(feature = "zstd")]
+        {
+            let compressed = ruzstd::encoding::compress_to_vec(
+                text.as_bytes(),
+                ruzstd::encoding::CompressionLevel::Fastest
+            );
+            assert_eq!(read(&compressed).unwrap(), text);
+        }
+        #[cfg(not(feature = "zstd"))]
+        assert_eq!(
+            read(&[0x28, 0xB5, 0x2F, 0xFD, 0]).unwrap_err().kind(),
+            io::ErrorKind::Unsupported
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,279 @@
+//! Decoding the text of wordlist files
+
+use std::borrow::Cow;
+
+/// The text encoding of a wordlist file
+///
+/// See [WordlistLoadOptions::with_encoding](super::WordlistLoadOptions::with_encoding).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    /// UTF-8 (the default)
+    #[default]
+    Utf8,
+    /// UTF-16, little endian
+    Utf16Le,
+    /// UTF-16, big endian
+    Utf16Be,
+    /// ISO-8859-1 (Latin-1), where every byte is a character
+    Latin1,
+    /// Detect the encoding from the content of the file
+    ///
+    /// Files starting with a byte order mark are read as UTF-8 or UTF-16 (without the byte
+    /// order mark). Other files are read as UTF-8 if most of their lines with non-ASCII
+    /// characters are valid UTF-8, and as Latin-1 otherwise. This means a UTF-8 file with a few
+    /// invalid lines is still read as UTF-8, with those lines failing to decode.
+    Detect
+}
+
+impl std::fmt::Display for Encoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Latin1 => "Latin-1",
+            Encoding::Detect => "detected"
+        })
+    }
+}
+
+impl Encoding {
+    /// Decodes `bytes` as text in this encoding, replacing each line that can't be decoded with
+    /// `U+FFFD REPLACEMENT CHARACTER`
+    ///
+    /// Line endings are normalized to `\n`, and any byte order mark is removed. This is useful
+    /// for looking at the start of a wordlist file before loading it.
+    ///
+    ///# Examples
+    /// ```
+    /// use anagrambot::wordlist::Encoding;
+    ///
+    /// assert_eq!(Encoding::Detect.decode_lossy(b"caf\xe9\r\nrace"), "café\nrace");
+    /// assert_eq!(Encoding::Utf8.decode_lossy(b"caf\xe9\r\nrace"), "\u{fffd}\nrace");
+    /// ```
+    pub fn decode_lossy(self, bytes: &[u8]) -> String {
+        let (_, lines) = decode_lines(bytes, self);
+        let lines: Vec<Cow<str>> = lines
+            .map(|line| line.unwrap_or(Cow::Borrowed("\u{fffd}")))
+            .collect();
+        lines.join("\n")
+    }
+}
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16_LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16_BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+/// A decoded line, or the reason it couldn't be decoded
+pub(super) type DecodedLine<'b> = Result<Cow<'b, str>, &'static str>;
+
+/// Splits `bytes` into lines and decodes them with `encoding`
+///
+/// Lines end with `\n` or `\r\n`, which aren't part of the line; a final line ending doesn't
+/// start another line. A byte order mark for the encoding used is removed. Returns the encoding
+/// used (which is never [Encoding::Detect]).
+pub(super) fn decode_lines(
+    bytes: &[u8],
+    encoding: Encoding
+) -> (Encoding, Box<dyn Iterator<Item = DecodedLine<'_>> + '_>) {
+    let (encoding, bytes) = match encoding {
+        Encoding::Detect => detect(bytes),
+        encoding => (encoding, strip_bom(bytes, encoding))
+    };
+
+    let lines: Box<dyn Iterator<Item = DecodedLine>> = match encoding {
+        Encoding::Utf8 | Encoding::Detect => {
+            Box::new(split_lines(bytes, b'\n', b'\r').map(|line| {
+                std::str::from_utf8(line)
+                    .map(Cow::Borrowed)
+                    .map_err(|_| "invalid UTF-8")
+            }))
+        }
+        Encoding::Latin1 => Box::new(split_lines(bytes, b'\n', b'\r').map(|line| {
+            Ok(Cow::Owned(
+                line.iter().map(|byte| char::from(*byte)).collect()
+            ))
+        })),
+        Encoding::Utf16Le | Encoding::Utf16Be => {
+            let to_unit = if encoding == Encoding::Utf16Le {
+                u16::from_le_bytes
+            } else {
+                u16::from_be_bytes
+            };
+            let units: Vec<u16> = bytes
+                .chunks(2)
+                .map(|pair| match pair {
+                    [first, second] => to_unit([*first, *second]),
+                    // a lone final byte can't be decoded
+                    _ => 0xD800
+                })
+                .collect();
+            Box::new(
+                split_lines(&units, u16::from(b'\n'), u16::from(b'\r'))
+                    .map(|line| {
+                        char::decode_utf16(line.iter().copied())
+                            .collect::<Result<String, _>>()
+                            .map(Cow::Owned)
+                            .map_err(|_| "invalid UTF-16")
+                    })
+                    .collect::<Vec<DecodedLine>>()
+                    .into_iter()
+            )
+        }
+    };
+    (encoding, lines)
+}
+
+/// Detects the encoding of `bytes`, returning it and the bytes without any byte order mark
+fn detect(bytes: &[u8]) -> (Encoding, &[u8]) {
+    if let Some(rest) = bytes.strip_prefix(UTF8_BOM) {
+        (Encoding::Utf8, rest)
+    } else if let Some(rest) = bytes.strip_prefix(UTF16_LE_BOM) {
+        (Encoding::Utf16Le, rest)
+    } else if let Some(rest) = bytes.strip_prefix(UTF16_BE_BOM) {
+        (Encoding::Utf16Be, rest)
+    } else if std::str::from_utf8(bytes).is_ok() || mostly_utf8(bytes) {
+        (Encoding::Utf8, bytes)
+    } else {
+        (Encoding::Latin1, bytes)
+    }
+}
+
+/// Returns whether more of the lines of `bytes` with non-ASCII characters are valid UTF-8 than
+/// aren't
+fn mostly_utf8(bytes: &[u8]) -> bool {
+    let (valid, invalid) = split_lines(bytes, b'\n', b'\r')
+        .filter(|line| !line.is_ascii())
+        .fold((0, 0), |(valid, invalid), line| {
+            if std::str::from_utf8(line).is_ok() {
+                (valid + 1, invalid)
+            } else {
+                (valid, invalid + 1)
+            }
+        });
+    valid > invalid
+}
+
+/// Removes the byte order mark of `encoding` from the start of `bytes`, if there is one
+fn strip_bom(bytes: &[u8], encoding: Encoding) -> &[u8] {
+    let bom = match encoding {
+        Encoding::Utf8 => UTF8_BOM,
+        Encoding::Utf16Le => UTF16_LE_BOM,
+        Encoding::Utf16Be => UTF16_BE_BOM,
+        Encoding::Latin1 | Encoding::Detect => return bytes
+    };
+    bytes.strip_prefix(bom).unwrap_or(bytes)
+}
+
+/// Splits `text` into lines ending with `newline`, removing a `carriage_return` before it
+fn split_lines<T: PartialEq + Copy>(
+    text: &[T],
+    newline: T,
+    carriage_return: T
+) -> impl Iterator<Item = &[T]> {
+    let text = text.strip_suffix(&[newline]).unwrap_or(text);
+    // an empty text has no lines, rather than one empty line
+    let lines = (!text.is_empty()).then(|| text.split(move |unit| *unit == newline));
+    lines
+        .into_iter()
+        .flatten()
+        .map(move |line| line.strip_suffix(&[carriage_return]).unwrap_or(line))
+}
+
+#[cfg(test)]
+mod encoding_tests {
+    use super::{decode_lines, Encoding};
+
+    fn decode(bytes: &[u8], encoding: Encoding) -> (Encoding, Vec<Result<String, &str>>) {
+        let (encoding, lines) = decode_lines(bytes, encoding);
+        (
+            encoding,
+            lines
+                .map(|line| line.map(|line| line.into_owned()))
+                .collect()
+        )
+    }
+
+    #[test]
+    fn test_utf8_lines() {
+        assert_eq!(
+            decode(b"race\r\ncar\n\nacre\n", Encoding::Utf8),
+            (
+                Encoding::Utf8,
+                vec![
+                    Ok("race".into()),
+                    Ok("car".into()),
+                    Ok("".into()),
+                    Ok("acre".into())
+                ]
+            )
+        );
+        assert_eq!(decode(b"", Encoding::Utf8).1, vec![]);
+        assert_eq!(
+            decode(b"caf\xe9\ncar", Encoding::Utf8).1,
+            vec![Err("invalid UTF-8"), Ok("car".into())]
+        );
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            decode(b"caf\xe9\ncar", Encoding::Detect),
+            (Encoding::Latin1, vec![Ok("café".into()), Ok("car".into())])
+        );
+        // mostly UTF-8, so the invalid line isn't decoded as Latin-1
+        assert_eq!(
+            decode(
+                b"caf\xc3\xa9\n\xe9t\xe9\n\xc3\xa9t\xc3\xa9",
+                Encoding::Detect
+            ),
+            (
+                Encoding::Utf8,
+                vec![Ok("café".into()), Err("invalid UTF-8"), Ok("été".into())]
+            )
+        );
+        assert_eq!(
+            decode("\u{feff}café\n".as_bytes(), Encoding::Detect),
+            (Encoding::Utf8, vec![Ok("café".into())])
+        );
+
+        let utf16: Vec<u8> = "\u{feff}café\r\ncar"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        assert_eq!(
+            decode(&utf16, Encoding::Detect),
+            (Encoding::Utf16Le, vec![Ok("café".into()), Ok("car".into())])
+        );
+        let utf16: Vec<u8> = "\u{feff}car"
+            .encode_utf16()
+            .flat_map(u16::to_be_bytes)
+            .collect();
+        assert_eq!(
+            decode(&utf16, Encoding::Detect),
+            (Encoding::Utf16Be, vec![Ok("car".into())])
+        );
+    }
+
+    #[test]
+    fn test_bom_with_encoding() {
+        assert_eq!(
+            decode("\u{feff}café\n".as_bytes(), Encoding::Utf8).1,
+            vec![Ok("café".into())]
+        );
+        let utf16: Vec<u8> = "\u{feff}car"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        assert_eq!(decode(&utf16, Encoding::Utf16Le).1, vec![Ok("car".into())]);
+        // a byte order mark for a different encoding is part of the text
+        assert_eq!(
+            decode(&utf16, Encoding::Utf16Be).1,
+            vec![Ok("\u{fffe}\u{6300}\u{6100}\u{7200}".into())]
+        );
+        assert_eq!(
+            decode(b"\xef\xbb\xbfcar", Encoding::Latin1).1,
+            vec![Ok("\u{ef}\u{bb}\u{bf}car".into())]
+        );
+    }
+}
@@ -0,0 +1,358 @@
+//! Statistics about the words of a wordlist, and problems with them
+
+use super::{fold_case, signature, Fingerprint, Wordlist};
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt
+};
+
+/// A problem with a word of a wordlist, found by [WordlistStats]
+///
+/// Some problems are errors: the word can't be used as it is written. The others are warnings
+/// about words that the cleaned [load options](super::WordlistLoadOptions::cleaned) fix or skip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WordProblem {
+    /// The word is empty (a warning)
+    Empty,
+    /// The word starts or ends with whitespace (a warning)
+    SurroundingWhitespace,
+    /// The word contains whitespace other than single spaces between its letters (an error)
+    Whitespace,
+    /// The word contains a control character, such as a tab (an error)
+    ControlCharacter
+}
+
+impl WordProblem {
+    /// Returns the problem with `word`, if it has one
+    ///
+    /// A word with several problems has the one that is listed last in [WordProblem].
+    pub fn of(word: &str) -> Option<Self> {
+        let trimmed = word.trim();
+        if word.is_empty() {
+            Some(WordProblem::Empty)
+        } else if trimmed.chars().any(char::is_control) {
+            Some(WordProblem::ControlCharacter)
+        } else if trimmed.contains("  ")
+            || trimmed
+                .chars()
+                .any(|character| character.is_whitespace() && character != ' ')
+        {
+            Some(WordProblem::Whitespace)
+        } else if trimmed.len() != word.len() {
+            Some(WordProblem::SurroundingWhitespace)
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if this problem is an error rather than a warning
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self,
+            WordProblem::Whitespace | WordProblem::ControlCharacter
+        )
+    }
+}
+
+impl fmt::Display for WordProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            WordProblem::Empty => "empty word",
+            WordProblem::SurroundingWhitespace => "whitespace at the start or end of the word",
+            WordProblem::Whitespace => "whitespace other than single spaces within the word",
+            WordProblem::ControlCharacter => "control character in the word"
+        };
+        f.write_str(description)
+    }
+}
+
+/// A word with a [WordProblem]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlaggedWord {
+    position: usize,
+    word: String,
+    problem: WordProblem
+}
+
+impl FlaggedWord {
+    /// Returns the position of the word in the wordlist (starting at 0)
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the word
+    pub fn word(&self) -> &str {
+        &self.word
+    }
+
+    /// Returns the problem with the word
+    pub fn problem(&self) -> WordProblem {
+        self.problem
+    }
+}
+
+/// Statistics about the words of a wordlist, and the problems found in them
+///
+/// Useful for checking a new wordlist before using it. Words are counted as they are returned
+/// by [iter](Wordlist::iter), so load a wordlist file with
+/// [WordlistLoadOptions::new](super::WordlistLoadOptions::new) to check its lines exactly as
+/// they are written.
+///
+///# Examples
+/// ```
+/// use anagrambot::wordlist::{BorrowedWordList, WordProblem, WordlistStats};
+///
+/// let wordlist: BorrowedWordList =
+///     ["race", "care", "Race", "acre ", "race", "ca\tr"].into_iter().collect();
+/// let stats = WordlistStats::new(&wordlist);
+///
+/// assert_eq!(stats.word_count(), 6);
+/// assert_eq!(stats.distinct_count(), 5);
+/// assert_eq!(stats.duplicates(), [(String::from("race"), 2)]);
+/// assert_eq!(stats.case_variants(), [vec!["race", "Race"]]);
+/// assert_eq!(stats.anagram_classes(), [vec!["race", "care", "acre "]]);
+///
+/// let problems: Vec<WordProblem> = stats.flagged().iter().map(|word| word.problem()).collect();
+/// assert_eq!(
+///     problems,
+///     vec![WordProblem::SurroundingWhitespace, WordProblem::ControlCharacter]
+/// );
+/// assert!(stats.has_errors());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordlistStats {
+    word_count: usize,
+    distinct_count: usize,
+    fingerprint: Fingerprint,
+    unordered_fingerprint: Fingerprint,
+    length_counts: BTreeMap<usize, usize>,
+    character_counts: BTreeMap<char, usize>,
+    duplicates: Vec<(String, usize)>,
+    flagged: Vec<FlaggedWord>,
+    case_variants: Vec<Vec<String>>,
+    anagram_classes: Vec<Vec<String>>
+}
+
+impl WordlistStats {
+    /// Collects the statistics of `wordlist`
+    pub fn new(wordlist: &(impl Wordlist + ?Sized)) -> Self {
+        let mut stats = WordlistStats {
+            word_count: 0,
+            distinct_count: 0,
+            fingerprint: wordlist.fingerprint(),
+            unordered_fingerprint: wordlist.unordered_fingerprint(),
+            length_counts: BTreeMap::new(),
+            character_counts: BTreeMap::new(),
+            duplicates: Vec::new(),
+            flagged: Vec::new(),
+            case_variants: Vec::new(),
+            anagram_classes: Vec::new()
+        };
+        // each group is kept in the order its first word appears in
+        let mut occurrences: HashMap<&str, usize> = HashMap::new();
+        let mut distinct_words = Vec::new();
+        let mut spellings: Groups<String> = Groups::default();
+        let mut classes: Groups<Vec<char>> = Groups::default();
+
+        for (position, word) in wordlist.iter().enumerate() {
+            stats.word_count += 1;
+            *stats.length_counts.entry(word.chars().count()).or_insert(0) += 1;
+            for character in word.chars() {
+                *stats.character_counts.entry(character).or_insert(0) += 1;
+            }
+            if let Some(problem) = WordProblem::of(word) {
+                stats.flagged.push(FlaggedWord {
+                    position,
+                    word: String::from(word),
+                    problem
+                });
+            }
+
+            let count = occurrences.entry(word).or_insert(0);
+            *count += 1;
+            if *count == 1 {
+                distinct_words.push(word);
+                spellings.add(fold_case(word), word);
+                classes.add(signature(word), word);
+            }
+        }
+
+        stats.distinct_count = distinct_words.len();
+        stats.duplicates = distinct_words
+            .iter()
+            .map(|word| (String::from(*word), occurrences[word]))
+            .filter(|(_, count)| *count > 1)
+            .collect();
+        stats.case_variants = spellings.shared();
+        stats.anagram_classes = classes.shared();
+        // largest first, keeping classes of the same size in order
+        stats
+            .anagram_classes
+            .sort_by_key(|class| std::cmp::Reverse(class.len()));
+        stats
+    }
+
+    /// Returns the number of words, including duplicates
+    pub fn word_count(&self) -> usize {
+        self.word_count
+    }
+
+    /// Returns the number of distinct words
+    pub fn distinct_count(&self) -> usize {
+        self.distinct_count
+    }
+
+    /// Returns the [fingerprint](Wordlist::fingerprint) of the wordlist
+    pub fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint
+    }
+
+    /// Returns the [unordered fingerprint](Wordlist::unordered_fingerprint) of the wordlist
+    pub fn unordered_fingerprint(&self) -> Fingerprint {
+        self.unordered_fingerprint
+    }
+
+    /// Returns the number of words of each length (in characters, including spaces)
+    pub fn length_counts(&self) -> &BTreeMap<usize, usize> {
+        &self.length_counts
+    }
+
+    /// Returns the number of times each character appears in the words
+    pub fn character_counts(&self) -> &BTreeMap<char, usize> {
+        &self.character_counts
+    }
+
+    /// Returns each word that appears more than once along with the number of times it appears,
+    /// in the order the words first appear
+    pub fn duplicates(&self) -> &[(String, usize)] {
+        &self.duplicates
+    }
+
+    /// Returns the words with a [WordProblem], in order
+    pub fn flagged(&self) -> &[FlaggedWord] {
+        &self.flagged
+    }
+
+    /// Returns the number of flagged words whose problem is an error
+    pub fn error_count(&self) -> usize {
+        self.flagged
+            .iter()
+            .filter(|word| word.problem.is_error())
+            .count()
+    }
+
+    /// Returns true if any flagged word's problem is an error
+    pub fn has_errors(&self) -> bool {
+        self.error_count() > 0
+    }
+
+    /// Returns the groups of distinct words that are the same ignoring case, such as "Paris"
+    /// and "paris"
+    ///
+    /// Groups and the words in them are in the order the words first appear.
+    pub fn case_variants(&self) -> &[Vec<String>] {
+        &self.case_variants
+    }
+
+    /// Returns the groups of distinct words that are proper anagrams of each other (compared
+    /// case sensitively), largest first
+    ///
+    /// Only groups of at least two words are included. Groups of the same size and the words
+    /// in each group are in the order the words first appear.
+    pub fn anagram_classes(&self) -> &[Vec<String>] {
+        &self.anagram_classes
+    }
+}
+
+/// Words grouped by a key, in the order the first word of each group was added
+struct Groups<K> {
+    group_indices: HashMap<K, usize>,
+    groups: Vec<Vec<String>>
+}
+
+impl<K> Default for Groups<K> {
+    fn default() -> Self {
+        Self {
+            group_indices: HashMap::new(),
+            groups: Vec::new()
+        }
+    }
+}
+
+impl<K: std::hash::Hash + Eq> Groups<K> {
+    /// Adds `word` to the group for `key`
+    fn add(&mut self, key: K, word: &str) {
+        let next_index = self.groups.len();
+        let index = *self.group_indices.entry(key).or_insert(next_index);
+        if index == next_index {
+            self.groups.push(Vec::new());
+        }
+        self.groups[index].push(String::from(word));
+    }
+
+    /// Returns the groups with more than one word
+    fn shared(self) -> Vec<Vec<String>> {
+        self.groups
+            .into_iter()
+            .filter(|group| group.len() > 1)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod stats_tests {
+    use super::{WordProblem, WordlistStats};
+    use crate::wordlist::BorrowedWordList;
+
+    #[test]
+    fn test_word_problems() {
+        assert_eq!(WordProblem::of("race"), None);
+        assert_eq!(WordProblem::of("race car"), None);
+        assert_eq!(WordProblem::of(""), Some(WordProblem::Empty));
+        assert_eq!(
+            WordProblem::of(" race"),
+            Some(WordProblem::SurroundingWhitespace)
+        );
+        assert_eq!(WordProblem::of("race  car"), Some(WordProblem::Whitespace));
+        assert_eq!(
+            WordProblem::of("race\u{a0}car"),
+            Some(WordProblem::Whitespace)
+        );
+        assert_eq!(
+            WordProblem::of(" race\tcar"),
+            Some(WordProblem::ControlCharacter)
+        );
+        assert_eq!(
+            WordProblem::of("race\u{7}"),
+            Some(WordProblem::ControlCharacter)
+        );
+        assert!(!WordProblem::SurroundingWhitespace.is_error());
+        assert!(WordProblem::Whitespace.is_error());
+    }
+
+    #[test]
+    fn test_counts() {
+        let wordlist: BorrowedWordList = ["race", "car", "", "acre", "arc", "care", "été"]
+            .into_iter()
+            .collect();
+        let stats = WordlistStats::new(&wordlist);
+
+        assert_eq!(stats.word_count(), 7);
+        assert_eq!(stats.distinct_count(), 7);
+        assert!(stats.duplicates().is_empty());
+        assert_eq!(
+            stats.length_counts().iter().collect::<Vec<_>>(),
+            vec![(&0, &1), (&3, &3), (&4, &3)]
+        );
+        assert_eq!(stats.character_counts()[&'r'], 5);
+        assert_eq!(stats.character_counts()[&'é'], 2);
+        assert_eq!(
+            stats.anagram_classes(),
+            [vec!["race", "acre", "care"], vec!["car", "arc"]]
+        );
+        assert_eq!(stats.flagged().len(), 1);
+        assert_eq!(stats.flagged()[0].position(), 2);
+        assert!(!stats.has_errors());
+    }
+}
@@ -0,0 +1,407 @@
+//! Wordlists with part of speech tags
+
+use super::{LoadReport, Wordlist, WordlistLoadOptions};
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt, fs,
+    io::{self, BufRead, BufReader},
+    path::Path,
+    str::FromStr
+};
+
+/// A part of speech, using the [Universal Dependencies](https://universaldependencies.org/u/pos/)
+/// tag set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PartOfSpeech {
+    Adjective,
+    Adposition,
+    Adverb,
+    Auxiliary,
+    CoordinatingConjunction,
+    Determiner,
+    Interjection,
+    Noun,
+    Numeral,
+    Particle,
+    Pronoun,
+    ProperNoun,
+    Punctuation,
+    SubordinatingConjunction,
+    Symbol,
+    Verb,
+    Other
+}
+
+impl PartOfSpeech {
+    /// Every part of speech, in the order of their bits in [PosTags]
+    pub const ALL: [PartOfSpeech; 17] = [
+        PartOfSpeech::Adjective,
+        PartOfSpeech::Adposition,
+        PartOfSpeech::Adverb,
+        PartOfSpeech::Auxiliary,
+        PartOfSpeech::CoordinatingConjunction,
+        PartOfSpeech::Determiner,
+        PartOfSpeech::Interjection,
+        PartOfSpeech::Noun,
+        PartOfSpeech::Numeral,
+        PartOfSpeech::Particle,
+        PartOfSpeech::Pronoun,
+        PartOfSpeech::ProperNoun,
+        PartOfSpeech::Punctuation,
+        PartOfSpeech::SubordinatingConjunction,
+        PartOfSpeech::Symbol,
+        PartOfSpeech::Verb,
+        PartOfSpeech::Other
+    ];
+
+    /// Returns the Universal Dependencies tag for this part of speech (e.g. `NOUN`)
+    pub fn tag(&self) -> &'static str {
+        match self {
+            PartOfSpeech::Adjective => "ADJ",
+            PartOfSpeech::Adposition => "ADP",
+            PartOfSpeech::Adverb => "ADV",
+            PartOfSpeech::Auxiliary => "AUX",
+            PartOfSpeech::CoordinatingConjunction => "CCONJ",
+            PartOfSpeech::Determiner => "DET",
+            PartOfSpeech::Interjection => "INTJ",
+            PartOfSpeech::Noun => "NOUN",
+            PartOfSpeech::Numeral => "NUM",
+            PartOfSpeech::Particle => "PART",
+            PartOfSpeech::Pronoun => "PRON",
+            PartOfSpeech::ProperNoun => "PROPN",
+            PartOfSpeech::Punctuation => "PUNCT",
+            PartOfSpeech::SubordinatingConjunction => "SCONJ",
+            PartOfSpeech::Symbol => "SYM",
+            PartOfSpeech::Verb => "VERB",
+            PartOfSpeech::Other => "X"
+        }
+    }
+
+    fn bit(&self) -> u32 {
+        1 << (*self as u32)
+    }
+}
+
+impl fmt::Display for PartOfSpeech {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.tag())
+    }
+}
+
+/// The error returned when parsing an unknown part of speech tag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownTagError {
+    tag: String
+}
+
+impl fmt::Display for UnknownTagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown part of speech tag \"{}\"", self.tag)
+    }
+}
+
+impl Error for UnknownTagError {}
+
+impl FromStr for PartOfSpeech {
+    type Err = UnknownTagError;
+
+    /// Parses a Universal Dependencies tag (such as `NOUN` or `adj`), ignoring case
+    fn from_str(tag: &str) -> Result<Self, Self::Err> {
+        PartOfSpeech::ALL
+            .into_iter()
+            .find(|part_of_speech| part_of_speech.tag().eq_ignore_ascii_case(tag))
+            .ok_or_else(|| UnknownTagError {
+                tag: tag.to_string()
+            })
+    }
+}
+
+/// A set of parts of speech
+///
+/// A word can have several parts of speech (for example, "race" is both a noun and a verb).
+///
+///# Examples
+/// ```
+/// use anagrambot::wordlist::{PartOfSpeech, PosTags};
+///
+/// let tags: PosTags = [PartOfSpeech::Noun, PartOfSpeech::Verb].into_iter().collect();
+/// assert!(tags.contains(PartOfSpeech::Noun));
+/// assert!(!tags.contains(PartOfSpeech::Adjective));
+/// assert_eq!(tags.to_string(), "NOUN,VERB");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct PosTags(u32);
+
+impl PosTags {
+    /// Returns a set containing no parts of speech
+    pub fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Returns true if `part_of_speech` is in this set
+    pub fn contains(&self, part_of_speech: PartOfSpeech) -> bool {
+        self.0 & part_of_speech.bit() != 0
+    }
+
+    /// Returns true if this set and `other` have any parts of speech in common
+    pub fn intersects(&self, other: PosTags) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Returns true if this set contains no parts of speech
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Adds `part_of_speech` to this set
+    pub fn insert(&mut self, part_of_speech: PartOfSpeech) {
+        self.0 |= part_of_speech.bit();
+    }
+
+    /// Returns a set containing every part of speech in this set or `other`
+    pub fn union(&self, other: PosTags) -> PosTags {
+        PosTags(self.0 | other.0)
+    }
+
+    /// Returns an iterator over the parts of speech in this set
+    pub fn iter(&self) -> impl Iterator<Item = PartOfSpeech> + '_ {
+        PartOfSpeech::ALL
+            .into_iter()
+            .filter(|part_of_speech| self.contains(*part_of_speech))
+    }
+}
+
+impl From<PartOfSpeech> for PosTags {
+    fn from(part_of_speech: PartOfSpeech) -> Self {
+        PosTags(part_of_speech.bit())
+    }
+}
+
+impl FromIterator<PartOfSpeech> for PosTags {
+    fn from_iter<T: IntoIterator<Item = PartOfSpeech>>(iter: T) -> Self {
+        let mut tags = PosTags::empty();
+        for part_of_speech in iter {
+            tags.insert(part_of_speech);
+        }
+        tags
+    }
+}
+
+impl fmt::Display for PosTags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tags: Vec<&str> = self
+            .iter()
+            .map(|part_of_speech| part_of_speech.tag())
+            .collect();
+        f.write_str(&tags.join(","))
+    }
+}
+
+impl FromStr for PosTags {
+    type Err = UnknownTagError;
+
+    /// Parses a comma separated list of tags (such as `NOUN,VERB`)
+    fn from_str(tags: &str) -> Result<Self, Self::Err> {
+        tags.split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(PartOfSpeech::from_str)
+            .collect()
+    }
+}
+
+/// A [Wordlist] with the parts of speech of each word
+///
+/// Used to restrict loose anagram searches to grammatical phrases; see
+/// [LooseAnagramsIterator::with_templates](crate::anagram::LooseAnagramsIterator::with_templates).
+pub trait TaggedWordlist: Wordlist {
+    /// Returns the parts of speech of `word`
+    ///
+    /// Returns an empty set if the word isn't in this `Wordlist` or has no known parts of speech.
+    fn tags(&self, word: &str) -> PosTags;
+}
+
+/// A [TaggedWordlist] implementor that owns its words and their parts of speech
+pub struct TaggedWordList {
+    word_vec: Vec<String>,
+    tags: HashMap<String, PosTags>
+}
+
+impl TaggedWordList {
+    /// Construct a new `TaggedWordList` from an iterator of words and their parts of speech
+    ///
+    /// If a word appears more than once, it has all of the parts of speech it appears with and
+    /// keeps the position of its first appearance.
+    pub fn new(word_iter: impl IntoIterator<Item = (String, PosTags)>) -> Self {
+        let mut word_vec = Vec::new();
+        let mut tags: HashMap<String, PosTags> = HashMap::new();
+        for (word, word_tags) in word_iter {
+            match tags.get_mut(&word) {
+                Some(existing) => *existing = existing.union(word_tags),
+                None => {
+                    tags.insert(word.clone(), word_tags);
+                    word_vec.push(word);
+                }
+            }
+        }
+        Self { word_vec, tags }
+    }
+
+    /// Construct a new `TaggedWordList` from the contents of a text file
+    ///
+    /// `word_file` must be a [Path] to a text file containing words and their parts of speech.
+    ///
+    /// Each line of the text file must contain a single word, a tab, and a comma separated list
+    /// of the word's Universal Dependencies tags (e.g. `NOUN,VERB`). Returns an error of
+    /// kind [InvalidData](io::ErrorKind::InvalidData) if a line is not in this format.
+    pub fn from_file(word_file: &Path) -> io::Result<Self> {
+        Self::from_file_with_options(word_file, &WordlistLoadOptions::new())
+    }
+
+    /// Construct a new `TaggedWordList` from the lines of a text file that `options` keep
+    ///
+    /// Like [from_file](TaggedWordList::from_file), but blank lines and comments can be
+    /// skipped, and options that leave out words apply to the word on each line.
+    /// See [WordlistLoadOptions] for the available options.
+    pub fn from_file_with_options(
+        word_file: &Path,
+        options: &WordlistLoadOptions
+    ) -> io::Result<Self> {
+        Self::from_file_with_report(word_file, options).map(|(wordlist, _)| wordlist)
+    }
+
+    /// Like [from_file_with_options](TaggedWordList::from_file_with_options), but also returns
+    /// a [LoadReport] listing the lines skipped in lenient mode
+    pub fn from_file_with_report(
+        word_file: &Path,
+        options: &WordlistLoadOptions
+    ) -> io::Result<(Self, LoadReport)> {
+        Self::from_reader_with_report(BufReader::new(fs::File::open(word_file)?), options)
+    }
+
+    /// Construct a new `TaggedWordList` from the lines read from `reader`
+    ///
+    /// Like [from_file](TaggedWordList::from_file), but for any reader, such as standard input.
+    pub fn from_reader(reader: impl BufRead) -> io::Result<Self> {
+        Self::from_reader_with_options(reader, &WordlistLoadOptions::new())
+    }
+
+    /// Construct a new `TaggedWordList` from the lines read from `reader` that `options` keep
+    ///
+    /// Like [from_file_with_options](TaggedWordList::from_file_with_options), but for any reader.
+    pub fn from_reader_with_options(
+        reader: impl BufRead,
+        options: &WordlistLoadOptions
+    ) -> io::Result<Self> {
+        Self::from_reader_with_report(reader, options).map(|(wordlist, _)| wordlist)
+    }
+
+    /// Like [from_reader_with_options](TaggedWordList::from_reader_with_options), but also returns
+    /// a [LoadReport] listing the lines skipped in lenient mode
+    pub fn from_reader_with_report(
+        reader: impl BufRead,
+        options: &WordlistLoadOptions
+    ) -> io::Result<(Self, LoadReport)> {
+        let mut word_vec: Vec<(String, PosTags)> = Vec::new();
+
+        let report = options.read_lines(reader, |filter, line| {
+            let (word, tags) = line
+                .rsplit_once('\t')
+                .ok_or("expected a word and its tags separated by a tab")?;
+            let tags = tags
+                .parse()
+                .map_err(|err: UnknownTagError| err.to_string())?;
+            let word = filter.trim(word);
+            if filter.accepts_word(word) {
+                word_vec.push((String::from(word), tags));
+            }
+            Ok(())
+        })?;
+
+        Ok((Self::new(word_vec), report))
+    }
+}
+
+impl FromIterator<(String, PosTags)> for TaggedWordList {
+    fn from_iter<T: IntoIterator<Item = (String, PosTags)>>(iter: T) -> Self {
+        TaggedWordList::new(iter)
+    }
+}
+
+impl Wordlist for TaggedWordList {
+    type IterType<'a> = std::iter::Map<std::slice::Iter<'a, String>, fn(&String) -> &str>;
+
+    fn includes_word(&self, word: &str) -> bool {
+        self.tags.contains_key(word)
+    }
+
+    fn iter(&self) -> Self::IterType<'_> {
+        self.word_vec.iter().map(|p| p.as_str())
+    }
+
+    fn len(&self) -> usize {
+        self.word_vec.len()
+    }
+}
+
+impl TaggedWordlist for TaggedWordList {
+    fn tags(&self, word: &str) -> PosTags {
+        self.tags.get(word).copied().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod taggedwordlist_tests {
+    use super::{PartOfSpeech, PosTags, TaggedWordList, TaggedWordlist, Wordlist};
+    use crate::wordlist::WordlistLoadOptions;
+    use std::fs;
+
+    #[test]
+    fn test_parse_tags() {
+        let tags: PosTags = "noun, VERB".parse().unwrap();
+        assert_eq!(
+            tags,
+            [PartOfSpeech::Noun, PartOfSpeech::Verb]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(tags.to_string().parse::<PosTags>(), Ok(tags));
+        assert!("NOUN,THING".parse::<PosTags>().is_err());
+
+        let tags: PosTags = "PUNCT,SYM,X".parse().unwrap();
+        assert!(tags.contains(PartOfSpeech::Punctuation));
+        assert!(tags.contains(PartOfSpeech::Symbol));
+        assert_eq!(tags.to_string(), "PUNCT,SYM,X");
+    }
+
+    #[test]
+    fn test_duplicates_are_merged() {
+        let list: TaggedWordList = [("race", "NOUN"), ("fast", "ADJ"), ("race", "VERB")]
+            .into_iter()
+            .map(|(word, tags)| (String::from(word), tags.parse().unwrap()))
+            .collect();
+        let words: Vec<&str> = list.iter().collect();
+        assert_eq!(words, vec!["race", "fast"]);
+        assert!(list.tags("race").contains(PartOfSpeech::Verb));
+        assert!(list.tags("race").contains(PartOfSpeech::Noun));
+        assert!(list.tags("cow").is_empty());
+    }
+
+    #[test]
+    fn test_cleaned_file_keeps_every_row() {
+        let dir = std::env::temp_dir().join(format!("anagrambot-tagged-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("tagged.tsv");
+        fs::write(&path, "race\tNOUN\nfast\tADJ\n# comment\nrace\tVERB\n").unwrap();
+
+        let options = WordlistLoadOptions::cleaned();
+        let list = TaggedWordList::from_file_with_options(&path, &options).unwrap();
+        assert_eq!(list.iter().collect::<Vec<&str>>(), vec!["race", "fast"]);
+        assert!(list.tags("race").contains(PartOfSpeech::Noun));
+        assert!(list.tags("race").contains(PartOfSpeech::Verb));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,378 @@
+//! A wordlist stored as a trie
+
+use super::{decompressed, LoadReport, Wordlist, WordlistLoadOptions};
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, BufRead, BufReader},
+    path::Path
+};
+
+/// A node of the trie; the root is always `nodes[0]`
+#[derive(Default)]
+struct TrieNode {
+    // (letter, index into nodes) for each child, sorted by letter
+    children: Vec<(char, u32)>,
+    // index (into word_vec) of the first word ending at this node, if any
+    word: Option<u32>
+}
+
+/// A [Wordlist] implementor that owns its words and stores them in a trie
+///
+/// Words sharing a prefix share the trie nodes for that prefix. This makes checking if a word
+/// is in the list fast, and lets [words_fitting](Wordlist::words_fitting) skip every word
+/// starting with a prefix that doesn't fit into the available letters, instead of checking each
+/// word separately. Every loose anagram search starts with `words_fitting`, so searching a
+/// `TrieWordList` is much faster than searching an [OwnedWordList](super::OwnedWordList).
+///
+/// Iterating over a `TrieWordList` returns its words in the order they were added
+/// (including any duplicates), just like an `OwnedWordList`.
+///
+///# Examples
+/// ```
+/// use anagrambot::wordlist::{TrieWordList, Wordlist};
+/// use std::collections::BTreeMap;
+///
+/// let wordlist: TrieWordList = ["race", "car", "care", "cow"].into_iter().map(String::from).collect();
+/// assert!(wordlist.includes_word("care"));
+/// assert!(!wordlist.includes_word("ca"));
+///
+/// let letters = BTreeMap::from([('a', 1), ('c', 1), ('e', 1), ('r', 1)]);
+/// assert_eq!(wordlist.words_fitting(&letters, true), vec!["race", "car", "care"]);
+/// assert_eq!(wordlist.words_with_prefix("car"), vec!["car", "care"]);
+/// ```
+pub struct TrieWordList {
+    word_vec: Vec<String>,
+    nodes: Vec<TrieNode>
+}
+
+impl TrieWordList {
+    /// Construct a new `TrieWordList` from an iterator of [String]
+    pub fn new(word_iter: impl IntoIterator<Item = String>) -> Self {
+        let mut trie = Self {
+            word_vec: Vec::new(),
+            nodes: vec![TrieNode::default()]
+        };
+        for word in word_iter {
+            trie.insert(word);
+        }
+        trie
+    }
+
+    /// Construct a new `TrieWordList` from the contents of a text file
+    ///
+    /// `word_file` must be a [Path] to a text file containing words.
+    ///
+    /// Each line of the text file is considered a single word. Files compressed with gzip or
+    /// zstd are decompressed transparently (see [Compression](super::Compression)).
+    pub fn from_file(word_file: &Path) -> io::Result<Self> {
+        Self::from_reader(BufReader::new(fs::File::open(word_file)?))
+    }
+
+    /// Construct a new `TrieWordList` from the lines read from `reader`
+    ///
+    /// Like [from_file](TrieWordList::from_file), but for any reader, such as standard input.
+    pub fn from_reader(reader: impl BufRead) -> io::Result<Self> {
+        let mut word_vec: Vec<String> = Vec::new();
+
+        let lines_iter = decompressed(reader)?.lines();
+
+        for line in lines_iter {
+            word_vec.push(line?);
+        }
+
+        Ok(Self::new(word_vec))
+    }
+
+    /// Construct a new `TrieWordList` from the lines of a text file that `options` keep
+    ///
+    /// See [WordlistLoadOptions] for the available options.
+    pub fn from_file_with_options(
+        word_file: &Path,
+        options: &WordlistLoadOptions
+    ) -> io::Result<Self> {
+        Self::from_file_with_report(word_file, options).map(|(wordlist, _)| wordlist)
+    }
+
+    /// Like [from_file_with_options](TrieWordList::from_file_with_options), but also returns
+    /// a [LoadReport] listing the lines skipped in lenient mode
+    pub fn from_file_with_report(
+        word_file: &Path,
+        options: &WordlistLoadOptions
+    ) -> io::Result<(Self, LoadReport)> {
+        Self::from_reader_with_report(BufReader::new(fs::File::open(word_file)?), options)
+    }
+
+    /// Construct a new `TrieWordList` from the lines read from `reader` that `options` keep
+    ///
+    /// Like [from_file_with_options](TrieWordList::from_file_with_options), but for any reader.
+    pub fn from_reader_with_options(
+        reader: impl BufRead,
+        options: &WordlistLoadOptions
+    ) -> io::Result<Self> {
+        Self::from_reader_with_report(reader, options).map(|(wordlist, _)| wordlist)
+    }
+
+    /// Like [from_reader_with_options](TrieWordList::from_reader_with_options), but also returns
+    /// a [LoadReport] listing the lines skipped in lenient mode
+    pub fn from_reader_with_report(
+        reader: impl BufRead,
+        options: &WordlistLoadOptions
+    ) -> io::Result<(Self, LoadReport)> {
+        let (word_vec, report) = options.read_with_report(reader)?;
+        Ok((Self::new(word_vec), report))
+    }
+
+    fn insert(&mut self, word: String) {
+        let mut node = 0;
+        for letter in word.chars() {
+            node = match self.child(node, letter) {
+                Some(child) => child,
+                None => {
+                    let child = self.nodes.len() as u32;
+                    self.nodes.push(TrieNode::default());
+                    let children = &mut self.nodes[node as usize].children;
+                    // keep children sorted so that they can be binary searched
+                    let position =
+                        children.partition_point(|(child_letter, _)| *child_letter < letter);
+                    children.insert(position, (letter, child));
+                    child
+                }
+            };
+        }
+        let word_index = self.word_vec.len() as u32;
+        self.nodes[node as usize].word.get_or_insert(word_index);
+        self.word_vec.push(word);
+    }
+
+    fn child(&self, node: u32, letter: char) -> Option<u32> {
+        let children = &self.nodes[node as usize].children;
+        children
+            .binary_search_by_key(&letter, |(child_letter, _)| *child_letter)
+            .ok()
+            .map(|position| children[position].1)
+    }
+
+    /// Returns the words with the given indices into word_vec, in wordlist order
+    fn words_at(&self, mut word_indices: Vec<u32>) -> Vec<&str> {
+        word_indices.sort_unstable();
+        word_indices
+            .into_iter()
+            .map(|index| self.word_vec[index as usize].as_str())
+            .collect()
+    }
+
+    /// Adds every word in the subtrie at `node` to `word_indices`
+    fn collect_all(&self, node: u32, word_indices: &mut Vec<u32>) {
+        let node = &self.nodes[node as usize];
+        word_indices.extend(node.word);
+        for (_, child) in &node.children {
+            self.collect_all(*child, word_indices);
+        }
+    }
+
+    /// Adds the words in the subtrie at `node` that fit into `available` to `word_indices`
+    fn collect_fitting(
+        &self,
+        node: u32,
+        available: &mut BTreeMap<char, u32>,
+        case_sensitive: bool,
+        word_indices: &mut Vec<u32>
+    ) {
+        let node = &self.nodes[node as usize];
+        word_indices.extend(node.word);
+
+        for (letter, child) in &node.children {
+            if *letter == ' ' {
+                self.collect_fitting(*child, available, case_sensitive, word_indices);
+                continue;
+            }
+            let used: Vec<char> = if case_sensitive {
+                vec![*letter]
+            } else {
+                letter.to_lowercase().collect()
+            };
+            if take_letters(available, &used) {
+                self.collect_fitting(*child, available, case_sensitive, word_indices);
+                for letter in used {
+                    *available.entry(letter).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Removes each letter in `letters` from `available`, returning false (and leaving `available`
+/// unchanged) if there aren't enough of them
+fn take_letters(available: &mut BTreeMap<char, u32>, letters: &[char]) -> bool {
+    for (taken, letter) in letters.iter().enumerate() {
+        match available.get_mut(letter) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => {
+                // put back the letters already taken
+                for letter in &letters[..taken] {
+                    *available.entry(*letter).or_insert(0) += 1;
+                }
+                return false;
+            }
+        }
+    }
+    true
+}
+
+impl FromIterator<String> for TrieWordList {
+    fn from_iter<T: IntoIterator<Item = String>>(iter: T) -> Self {
+        TrieWordList::new(iter)
+    }
+}
+
+impl Wordlist for TrieWordList {
+    type IterType<'a> = std::iter::Map<std::slice::Iter<'a, String>, fn(&String) -> &str>;
+
+    fn includes_word(&self, word: &str) -> bool {
+        let mut node = 0;
+        for letter in word.chars() {
+            match self.child(node, letter) {
+                Some(child) => node = child,
+                None => return false
+            }
+        }
+        self.nodes[node as usize].word.is_some()
+    }
+
+    fn iter(&self) -> Self::IterType<'_> {
+        self.word_vec.iter().map(|p| p.as_str())
+    }
+
+    fn len(&self) -> usize {
+        self.word_vec.len()
+    }
+
+    fn words_fitting(&self, letters: &BTreeMap<char, u32>, case_sensitive: bool) -> Vec<&str> {
+        let mut available = letters.clone();
+        let mut word_indices = Vec::new();
+        self.collect_fitting(0, &mut available, case_sensitive, &mut word_indices);
+        self.words_at(word_indices)
+    }
+
+    fn words_with_prefix(&self, prefix: &str) -> Vec<&str> {
+        let mut node = 0;
+        for letter in prefix.chars() {
+            match self.child(node, letter) {
+                Some(child) => node = child,
+                None => return Vec::new()
+            }
+        }
+        let mut word_indices = Vec::new();
+        self.collect_all(node, &mut word_indices);
+        self.words_at(word_indices)
+    }
+}
+
+#[cfg(test)]
+mod triewordlist_tests {
+    use super::{TrieWordList, Wordlist};
+    use crate::{anagram::find_loose_anagrams, wordlist::BorrowedWordList};
+    use std::collections::BTreeMap;
+
+    const TEST_WORD_SET: [&str; 10] = [
+        "race",
+        "car",
+        "care",
+        "Race",
+        "racecar",
+        "car",
+        "ice cream",
+        "a",
+        "",
+        "cream"
+    ];
+
+    #[test]
+    fn test_matches_borrowed_wordlist() {
+        let trie: TrieWordList = TEST_WORD_SET.into_iter().map(String::from).collect();
+        let borrowed: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+
+        assert!(trie.iter().eq(borrowed.iter()));
+        for word in TEST_WORD_SET
+            .into_iter()
+            .chain(["rac", "cars", "ice", "icecream"])
+        {
+            assert_eq!(trie.includes_word(word), borrowed.includes_word(word));
+        }
+    }
+
+    #[test]
+    fn test_words_fitting() {
+        let trie: TrieWordList = TEST_WORD_SET.into_iter().map(String::from).collect();
+        let letters = BTreeMap::from([('a', 2), ('c', 3), ('e', 2), ('r', 2), ('i', 1), ('m', 1)]);
+
+        assert_eq!(
+            trie.words_fitting(&letters, true),
+            vec![
+                "race",
+                "car",
+                "care",
+                "racecar",
+                "ice cream",
+                "a",
+                "",
+                "cream"
+            ]
+        );
+        assert_eq!(
+            trie.words_fitting(&letters, false),
+            vec![
+                "race",
+                "car",
+                "care",
+                "Race",
+                "racecar",
+                "ice cream",
+                "a",
+                "",
+                "cream"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_matches_default_methods() {
+        let trie: TrieWordList = TEST_WORD_SET.into_iter().map(String::from).collect();
+        // BorrowedWordList uses the default implementations
+        let scanned: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+
+        assert_eq!(trie.len(), scanned.len());
+        let letters = BTreeMap::from([('a', 2), ('c', 2), ('e', 1), ('r', 2), ('R', 1)]);
+        for case_sensitive in [true, false] {
+            assert_eq!(
+                trie.words_fitting(&letters, case_sensitive),
+                scanned.words_fitting(&letters, case_sensitive)
+            );
+        }
+        for prefix in ["", "rac", "Rac", "car", "ice ", "x"] {
+            assert_eq!(
+                trie.words_with_prefix(prefix),
+                scanned.words_with_prefix(prefix)
+            );
+        }
+        assert_eq!(trie.words_with_prefix("rac"), vec!["race", "racecar"]);
+    }
+
+    #[test]
+    fn test_loose_anagrams_match_borrowed_wordlist() {
+        let trie: TrieWordList = TEST_WORD_SET.into_iter().map(String::from).collect();
+        let borrowed: BorrowedWordList = TEST_WORD_SET.into_iter().collect();
+
+        for case_sensitive in [true, false] {
+            for target in ["racecar", "Race car", "ice cream car", "arc"] {
+                let expected: Vec<String> =
+                    find_loose_anagrams(target, &borrowed, 1, case_sensitive).collect();
+                let found: Vec<String> =
+                    find_loose_anagrams(target, &trie, 1, case_sensitive).collect();
+                assert_eq!(found, expected);
+            }
+        }
+    }
+}